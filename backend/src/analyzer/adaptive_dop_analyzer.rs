@@ -0,0 +1,164 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::{tr, tr_list};
+
+/// Rows one driver comfortably processes; the recommended DOP aims at this volume per driver.
+const TARGET_ROWS_PER_DRIVER: u64 = 1_000_000;
+/// Rows per driver after adaptation from which the adapted DOP is clearly too low.
+const MAX_ROWS_PER_DRIVER: u64 = 10_000_000;
+/// Adapted pipelines busy for less than this (per driver) do not matter.
+const MIN_ADAPTED_ACTIVE_MS: f64 = 1000.0;
+
+/// Runtime adaptive DOP (`enable_runtime_adaptive_dop`): the DOP decisions visible through
+/// `COLLECT_STATS_SINK/SOURCE`, and adapted DOPs that are too low for the data they process.
+pub struct AdaptiveDopAnalyzer;
+
+impl AdaptiveDopAnalyzer {
+    pub fn report(profile: &Profile) -> Vec<AdaptiveDopDecision> {
+        let mut decisions = Vec::new();
+
+        for fragment in &profile.fragments {
+            for producer in &fragment.pipelines {
+                let Some(sink) = producer.operators.first().filter(|op| op.name == "COLLECT_STATS_SINK") else {
+                    continue;
+                };
+                let Some((consumer, source)) = fragment.pipelines.iter().find_map(|p| {
+                    let source = p.operators.last()?;
+                    (source.name == "COLLECT_STATS_SOURCE" && source.plan_node_id == sink.plan_node_id).then_some((p, source))
+                }) else {
+                    continue;
+                };
+
+                let initial_dop = Self::dop(producer, "DegreeOfParallelism").unwrap_or(1);
+                let adapted_dop = Self::dop(consumer, "DegreeOfParallelism").unwrap_or(1);
+                // every pipeline of the fragment after the decision runs at the adapted DOP
+                let adapted_active_ms = fragment.pipelines.iter()
+                    .filter(|p| p.id != producer.id && Self::dop(p, "DegreeOfParallelism") == Some(adapted_dop))
+                    .filter_map(|p| p.metrics.get("ActiveTime"))
+                    .filter_map(|v| ValueParser::parse_time_to_ms(v).ok())
+                    .fold(0.0, f64::max);
+
+                decisions.push(AdaptiveDopDecision {
+                    fragment_id: fragment.id.clone(),
+                    plan_node_id: sink.plan_node_id.as_deref().and_then(|id| id.parse().ok()),
+                    producer_pipeline_id: producer.id.clone(),
+                    consumer_pipeline_id: consumer.id.clone(),
+                    initial_dop,
+                    adapted_dop,
+                    initial_total_dop: Self::dop(producer, "TotalDegreeOfParallelism").unwrap_or(initial_dop),
+                    adapted_total_dop: Self::dop(consumer, "TotalDegreeOfParallelism").unwrap_or(adapted_dop),
+                    state: source.unique_metrics.get("State").cloned().unwrap_or_default(),
+                    input_rows: sink.common_metrics.get("PushRowNum")
+                        .and_then(|v| ValueParser::parse_number(v).ok())
+                        .unwrap_or(0),
+                    adapted_active_ms,
+                });
+            }
+        }
+
+        decisions
+    }
+
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let pipeline_dop = profile.summary.variables.get("pipeline_dop").cloned().unwrap_or_else(|| "0".to_string());
+        Self::report(profile).iter()
+            .filter_map(|decision| Self::analyze_decision(decision, &pipeline_dop))
+            .collect()
+    }
+
+    fn analyze_decision(decision: &AdaptiveDopDecision, pipeline_dop: &str) -> Option<HotSpot> {
+        if decision.adapted_dop >= decision.initial_dop || decision.adapted_active_ms < MIN_ADAPTED_ACTIVE_MS {
+            return None;
+        }
+        let rows_per_driver = decision.input_rows / decision.adapted_total_dop.max(1);
+        if rows_per_driver < MAX_ROWS_PER_DRIVER {
+            return None;
+        }
+
+        let backends = (decision.adapted_total_dop / decision.adapted_dop.max(1)).max(1);
+        let rows_per_backend = decision.input_rows / backends;
+        let recommended = rows_per_backend.div_ceil(TARGET_ROWS_PER_DRIVER)
+            .clamp(decision.adapted_dop + 1, decision.initial_dop);
+
+        Some(HotSpot {
+            node_path: format!("Fragment{}.Pipeline{}.COLLECT_STATS_SOURCE", decision.fragment_id, decision.consumer_pipeline_id),
+            node: NodeRef {
+                plan_node_id: decision.plan_node_id,
+                fragment_id: Some(decision.fragment_id.clone()),
+                pipeline_id: Some(decision.consumer_pipeline_id.clone()),
+                tree_node_id: None,
+                operator_name: Some("COLLECT_STATS_SOURCE".to_string()),
+            },
+            severity: if decision.adapted_active_ms >= 10_000.0 { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::AdaptiveDopTooLow,
+            confidence: Confidence::Medium,
+            description: tr!(
+                "adaptive_dop.description",
+                rows = decision.input_rows,
+                initial_dop = decision.initial_dop,
+                initial_total = decision.initial_total_dop,
+                adapted_dop = decision.adapted_dop,
+                adapted_total = decision.adapted_total_dop,
+                rows_per_driver = rows_per_driver,
+                secs = decision.adapted_active_ms / 1000.0,
+            ),
+            suggestions: tr_list!("adaptive_dop.suggestions", pipeline_dop = pipeline_dop, recommended = recommended),
+            evidence: vec![
+                Evidence::new("PushRowNum / TotalDegreeOfParallelism", rows_per_driver as f64, MetricUnit::Count).threshold(MAX_ROWS_PER_DRIVER as f64),
+                Evidence::new("ActiveTime", decision.adapted_active_ms, MetricUnit::Milliseconds).threshold(MIN_ADAPTED_ACTIVE_MS),
+            ],
+        })
+    }
+
+    fn dop(pipeline: &Pipeline, key: &str) -> Option<u64> {
+        pipeline.metrics.get(key).and_then(|v| ValueParser::parse_number(v).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, operator, pipeline, profile, with_pipelines};
+
+    fn adaptive_profile(rows: &str, consumer_active: &str) -> Profile {
+        profile(vec![with_pipelines("1", vec![
+            pipeline("0", &[("DegreeOfParallelism", "32"), ("TotalDegreeOfParallelism", "352"), ("ActiveTime", "2s")], vec![
+                operator("COLLECT_STATS_SINK", 3, &[("PushRowNum", rows)], &[]),
+                operator("EXCHANGE_SOURCE", 3, &[], &[]),
+            ]),
+            pipeline("1", &[("DegreeOfParallelism", "1"), ("TotalDegreeOfParallelism", "11"), ("ActiveTime", consumer_active)], vec![
+                operator("AGGREGATE_BLOCKING_SINK", 3, &[], &[]),
+                operator("COLLECT_STATS_SOURCE", 3, &[], &[("State", "RoundRobin")]),
+            ]),
+        ])])
+    }
+
+    #[test]
+    fn test_adaptive_dop_report() {
+        // profile1 fragment 1: 32 rows, DOP 32 -> 1 per backend on 11 backends
+        let report = AdaptiveDopAnalyzer::report(&adaptive_profile("32", "75.303us"));
+        assert_eq!(report.len(), 1);
+        assert_eq!((report[0].initial_dop, report[0].adapted_dop), (32, 1));
+        assert_eq!(report[0].adapted_total_dop, 11);
+        assert_eq!(report[0].state, "RoundRobin");
+        assert!(AdaptiveDopAnalyzer::analyze(&adaptive_profile("32", "75.303us")).is_empty());
+    }
+
+    #[test]
+    fn test_adapted_dop_too_low() {
+        let hotspots = AdaptiveDopAnalyzer::analyze(&adaptive_profile("440.000M (440000000)", "25s"));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].severity, HotSeverity::Moderate);
+        // 40M rows per backend at 1M rows per driver, capped by the initial DOP
+        assert!(hotspots[0].suggestions[1].ends_with("pipeline_dop = 32"));
+    }
+
+    #[test]
+    fn test_adaptive_dop_profile1() {
+        let profile = load("profile1.txt");
+        let report = AdaptiveDopAnalyzer::report(&profile);
+        assert!(report.iter().any(|d| (d.initial_dop, d.adapted_dop) == (32, 1) && d.state == "RoundRobin"));
+        assert!(report.iter().any(|d| d.state == "Passthrough"));
+        assert!(AdaptiveDopAnalyzer::analyze(&profile).is_empty());
+    }
+}
@@ -0,0 +1,390 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::parser::specialized::AggregateStrategy;
+use crate::{tr, tr_list};
+
+/// Aggregations over fewer input rows than this are cheap whatever their shape.
+const MIN_INPUT_ROWS: u64 = 1_000_000;
+/// Output/input share above which a pre-aggregation barely reduced anything.
+const USELESS_PREAGG_OUTPUT_RATIO: f64 = 0.8;
+/// Output/input share above which the first phase is considered weak.
+const WEAK_PREAGG_OUTPUT_RATIO: f64 = 0.5;
+/// Groups (summed over drivers) from which the group-by cardinality is exploding.
+const HIGH_CARDINALITY_GROUPS: u64 = 10_000_000;
+const SEVERE_CARDINALITY_GROUPS: u64 = 100_000_000;
+const LARGE_HASH_TABLE_BYTES: u64 = 1024 * 1024 * 1024;
+const SEVERE_HASH_TABLE_BYTES: u64 = 10 * 1024 * 1024 * 1024;
+
+/// One `AGGREGATE_*_SINK` / `_SOURCE` pair.
+struct AggregationPhase {
+    node_path: String,
+    node: NodeRef,
+    streaming: bool,
+    metrics: AggregateSpecializedMetrics,
+    output_rows: Option<u64>,
+    /// The sink pipeline starts with a native table scan, i.e. the phase aggregates data where it is stored.
+    over_native_scan: bool,
+    /// Plan node id of the exchange the sink pipeline reads, i.e. the phase merges shuffled data.
+    input_exchange: Option<String>,
+    /// Plan node id of the exchange the phase's output is sent through.
+    output_exchange: Option<String>,
+}
+
+impl AggregationPhase {
+    fn over_exchange(&self) -> bool {
+        self.input_exchange.is_some()
+    }
+
+    fn output_ratio(&self) -> Option<f64> {
+        let input = self.metrics.input_rows.filter(|r| *r > 0)?;
+        Some(self.output_rows? as f64 / input as f64)
+    }
+}
+
+/// Streaming / blocking aggregation analysis: per-phase reduction, useless pre-aggregation,
+/// exploding group-by cardinality and two-phase aggregations that could run colocated.
+pub struct AggregateAnalyzer;
+
+impl AggregateAnalyzer {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let phases = Self::collect_phases(profile);
+        let mut hotspots = Vec::new();
+
+        for phase in &phases {
+            let first_phase = Self::is_first_phase(phase, &phases);
+            if first_phase {
+                hotspots.extend(Self::analyze_preaggregation(phase));
+            } else {
+                hotspots.extend(Self::analyze_cardinality(phase));
+                hotspots.extend(Self::analyze_colocation(phase, &phases));
+            }
+        }
+
+        hotspots
+    }
+
+    fn collect_phases(profile: &Profile) -> Vec<AggregationPhase> {
+        let mut phases = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                let pipeline_source = Self::feeding_source(fragment, pipeline);
+                for operator in &pipeline.operators {
+                    let streaming = match operator.name.as_str() {
+                        "AGGREGATE_STREAMING_SINK" => true,
+                        "AGGREGATE_BLOCKING_SINK" => false,
+                        _ => continue,
+                    };
+                    let source_name = operator.name.replace("_SINK", "_SOURCE");
+                    let is_source = |op: &Operator| op.name == source_name && op.plan_node_id == operator.plan_node_id;
+                    let output_rows = fragment.pipelines.iter()
+                        .flat_map(|p| p.operators.iter())
+                        .find(|op| is_source(op))
+                        .and_then(|op| op.common_metrics.get("PullRowNum"))
+                        .and_then(|v| ValueParser::parse_number(v).ok());
+                    let output_exchange = fragment.pipelines.iter()
+                        .filter(|p| Self::feeding_source(fragment, p).map(is_source).unwrap_or(false))
+                        .filter_map(|p| p.operators.first())
+                        .find(|op| op.name == "EXCHANGE_SINK")
+                        .and_then(|op| op.plan_node_id.clone());
+
+                    phases.push(AggregationPhase {
+                        node_path: format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, operator.name),
+                        node: NodeRef::operator(&fragment.id, &pipeline.id, operator),
+                        streaming,
+                        metrics: AggregateStrategy::from_metrics(&operator.unique_metrics),
+                        output_rows,
+                        over_native_scan: pipeline_source
+                            .map(super::OlapScanAnalyzer::is_native_scan)
+                            .unwrap_or(false),
+                        input_exchange: pipeline_source
+                            .filter(|op| op.name == "EXCHANGE_SOURCE")
+                            .and_then(|op| op.plan_node_id.clone()),
+                        output_exchange,
+                    });
+                }
+            }
+        }
+
+        phases
+    }
+
+    /// The operator that produces a pipeline's data, looking through the local exchanges and
+    /// adaptive DOP stages that only hand chunks over from another pipeline of the fragment.
+    fn feeding_source<'a>(fragment: &'a Fragment, pipeline: &'a Pipeline) -> Option<&'a Operator> {
+        let mut source = pipeline.operators.last()?;
+        for _ in 0..fragment.pipelines.len() {
+            let sink_name = match source.name.as_str() {
+                "LOCAL_EXCHANGE_SOURCE" => "LOCAL_EXCHANGE_SINK",
+                "COLLECT_STATS_SOURCE" => "COLLECT_STATS_SINK",
+                _ => break,
+            };
+            let Some(upstream) = fragment.pipelines.iter().find(|p| {
+                p.operators.first().map(|op| op.name == sink_name && op.plan_node_id == source.plan_node_id).unwrap_or(false)
+            }) else {
+                break;
+            };
+            source = upstream.operators.last()?;
+        }
+        Some(source)
+    }
+
+    /// Streaming aggregations are always a pre-aggregation. A blocking one is the first phase when
+    /// another aggregation merges its output after an exchange.
+    fn is_first_phase(phase: &AggregationPhase, phases: &[AggregationPhase]) -> bool {
+        phase.streaming || (!phase.over_exchange() && Self::second_phase_of(phase, phases).is_some())
+    }
+
+    /// The aggregation reading the exchange this phase's output is sent through.
+    fn second_phase_of<'a>(phase: &AggregationPhase, phases: &'a [AggregationPhase]) -> Option<&'a AggregationPhase> {
+        let exchange = phase.output_exchange.as_ref()?;
+        phases.iter().find(|other| other.input_exchange.as_ref() == Some(exchange))
+    }
+
+    /// The aggregation whose output arrives through the exchange this phase reads.
+    fn first_phase_of<'a>(phase: &AggregationPhase, phases: &'a [AggregationPhase]) -> Option<&'a AggregationPhase> {
+        let exchange = phase.input_exchange.as_ref()?;
+        phases.iter().find(|other| other.output_exchange.as_ref() == Some(exchange))
+    }
+
+    fn analyze_preaggregation(phase: &AggregationPhase) -> Option<HotSpot> {
+        let input = phase.metrics.input_rows?;
+        let ratio = phase.output_ratio()?;
+        if input < MIN_INPUT_ROWS || ratio < USELESS_PREAGG_OUTPUT_RATIO {
+            return None;
+        }
+        let pass_through = phase.metrics.pass_through_rows.unwrap_or(0);
+        if pass_through * 2 >= input {
+            // the streaming aggregation already switched to pass-through by itself
+            return None;
+        }
+
+        let mut description = tr!(
+            "aggregate.preaggregation.description",
+            input = input,
+            output = phase.output_rows.unwrap_or(0),
+            reduction = 1.0 / ratio,
+            pass_through = pass_through,
+            keys = Self::or_dash(&phase.metrics.grouping_keys),
+            agg_time = Self::format_duration(phase.metrics.agg_compute_time),
+            expr_time = Self::format_duration(phase.metrics.expr_compute_time),
+        );
+        if let Some(size) = phase.metrics.hash_table_size {
+            description.push_str(&tr!("aggregate.preaggregation.hash_table", groups = size));
+        }
+
+        let mut suggestions = if phase.streaming {
+            vec![tr!("aggregate.preaggregation.force_streaming")]
+        } else {
+            vec![tr!("aggregate.preaggregation.one_stage")]
+        };
+        if phase.over_native_scan {
+            suggestions.push(tr!("aggregate.preaggregation.bucket_by_keys"));
+        }
+
+        Some(HotSpot {
+            node_path: phase.node_path.clone(),
+            node: phase.node.clone(),
+            severity: if input >= 100 * MIN_INPUT_ROWS { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::IneffectivePreAggregation,
+            confidence: Confidence::High,
+            description,
+            suggestions,
+            evidence: vec![
+                Evidence::new("PullRowNum / InputRowCount", ratio, MetricUnit::Ratio).threshold(USELESS_PREAGG_OUTPUT_RATIO),
+                Evidence::new("InputRowCount", input as f64, MetricUnit::Count).threshold(MIN_INPUT_ROWS as f64),
+            ],
+        })
+    }
+
+    fn analyze_cardinality(phase: &AggregationPhase) -> Option<HotSpot> {
+        let groups = phase.metrics.hash_table_size.unwrap_or(0);
+        let memory = phase.metrics.hash_table_memory.unwrap_or(0);
+        if groups < HIGH_CARDINALITY_GROUPS && memory < LARGE_HASH_TABLE_BYTES {
+            return None;
+        }
+
+        let mut description = tr!(
+            "aggregate.cardinality.description",
+            groups = groups,
+            memory = ValueParser::format_bytes(memory),
+            input = phase.metrics.input_rows.map(|r| r.to_string()).unwrap_or_else(|| "?".to_string()),
+            keys = Self::or_dash(&phase.metrics.grouping_keys),
+            functions = Self::or_dash(&phase.metrics.aggregate_functions),
+        );
+        if let Some(ratio) = phase.output_ratio() {
+            description.push_str(&tr!("aggregate.cardinality.reduction", reduction = 1.0 / ratio.max(f64::EPSILON)));
+        }
+        if let Some(spilled) = phase.metrics.spill_bytes.filter(|b| *b > 0) {
+            description.push_str(&tr!("aggregate.cardinality.spilled", bytes = ValueParser::format_bytes(spilled)));
+        }
+
+        Some(HotSpot {
+            node_path: phase.node_path.clone(),
+            node: phase.node.clone(),
+            severity: if groups >= SEVERE_CARDINALITY_GROUPS || memory >= SEVERE_HASH_TABLE_BYTES {
+                HotSeverity::Severe
+            } else {
+                HotSeverity::Moderate
+            },
+            issue_type: IssueCode::HighCardinalityAggregation,
+            confidence: Confidence::High,
+            description,
+            suggestions: tr_list!("aggregate.cardinality.suggestions"),
+            evidence: vec![
+                Evidence::new("HashTableSize", groups as f64, MetricUnit::Count).threshold(HIGH_CARDINALITY_GROUPS as f64),
+                Evidence::new("HashTableMemoryUsage", memory as f64, MetricUnit::Bytes).threshold(LARGE_HASH_TABLE_BYTES as f64),
+            ],
+        })
+    }
+
+    fn analyze_colocation(phase: &AggregationPhase, phases: &[AggregationPhase]) -> Option<HotSpot> {
+        let first = Self::first_phase_of(phase, phases)?;
+        if !first.over_native_scan || phase.metrics.grouping_keys.is_empty() {
+            return None;
+        }
+        let shuffled = phase.metrics.input_rows?;
+        let first_ratio = first.output_ratio()?;
+        if shuffled < MIN_INPUT_ROWS || first_ratio < WEAK_PREAGG_OUTPUT_RATIO {
+            return None;
+        }
+
+        Some(HotSpot {
+            node_path: phase.node_path.clone(),
+            node: phase.node.clone(),
+            severity: HotSeverity::Moderate,
+            issue_type: IssueCode::AggregationShouldColocate,
+            confidence: Confidence::Medium,
+            description: tr!(
+                "aggregate.colocation.description",
+                percent = first_ratio * 100.0,
+                rows = shuffled,
+                keys = phase.metrics.grouping_keys,
+            ),
+            suggestions: tr_list!("aggregate.colocation.suggestions"),
+            evidence: vec![
+                Evidence::new("first phase PullRowNum / InputRowCount", first_ratio, MetricUnit::Ratio).threshold(WEAK_PREAGG_OUTPUT_RATIO),
+                Evidence::new("InputRowCount", shuffled as f64, MetricUnit::Count).threshold(MIN_INPUT_ROWS as f64),
+            ],
+        })
+    }
+
+    fn or_dash(value: &str) -> &str {
+        if value.is_empty() { "-" } else { value }
+    }
+
+    fn format_duration(duration: Option<std::time::Duration>) -> String {
+        match duration {
+            Some(d) if d.as_secs_f64() >= 1.0 => format!("{:.2}s", d.as_secs_f64()),
+            Some(d) => format!("{:.2}ms", d.as_secs_f64() * 1000.0),
+            None => "-".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fragment, load, operator, profile};
+
+    fn two_phase(first_output: &str) -> Profile {
+        profile(vec![
+            fragment("0", vec![
+                vec![operator("AGGREGATE_BLOCKING_SOURCE", 4, &[("PullRowNum", "1.2M (1200000)")], &[])],
+                vec![
+                    operator("AGGREGATE_BLOCKING_SINK", 4, &[], &[
+                        ("GroupingKeys", "1: user_id"),
+                        ("InputRowCount", first_output),
+                        ("HashTableSize", "1.2M (1200000)"),
+                    ]),
+                    operator("EXCHANGE_SOURCE", 3, &[], &[]),
+                ],
+            ]),
+            fragment("1", vec![
+                vec![
+                    operator("EXCHANGE_SINK", 3, &[], &[]),
+                    operator("AGGREGATE_STREAMING_SOURCE", 2, &[("PullRowNum", first_output)], &[]),
+                ],
+                vec![
+                    operator("AGGREGATE_STREAMING_SINK", 2, &[], &[
+                        ("GroupingKeys", "1: user_id"),
+                        ("InputRowCount", "10.000M (10000000)"),
+                        ("PassThroughRowCount", "0"),
+                    ]),
+                    operator("OLAP_SCAN", 0, &[], &[]),
+                ],
+            ]),
+        ])
+    }
+
+    #[test]
+    fn test_useless_preaggregation() {
+        let hotspots = AggregateAnalyzer::analyze(&two_phase("9.500M (9500000)"));
+        let preagg = hotspots.iter().find(|h| h.issue_type == IssueCode::IneffectivePreAggregation).unwrap();
+        assert_eq!(preagg.node_path, "Fragment1.Pipeline1.AGGREGATE_STREAMING_SINK");
+        assert!(preagg.suggestions[0].contains("force_streaming"));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::AggregationShouldColocate));
+
+        // the first phase reduced the data tenfold
+        assert!(AggregateAnalyzer::analyze(&two_phase("1.000M (1000000)")).is_empty());
+    }
+
+    #[test]
+    fn test_high_cardinality() {
+        let profile = profile(vec![fragment("0", vec![vec![
+            operator("AGGREGATE_BLOCKING_SINK", 1, &[], &[
+                ("GroupingKeys", "2: order_id"),
+                ("InputRowCount", "300.000M (300000000)"),
+                ("HashTableSize", "250.000M (250000000)"),
+                ("HashTableMemoryUsage", "12.500 GB"),
+            ]),
+            operator("EXCHANGE_SOURCE", 0, &[], &[]),
+        ]])]);
+        let hotspots = AggregateAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::HighCardinalityAggregation);
+        assert_eq!(hotspots[0].severity, HotSeverity::Severe);
+    }
+
+    #[test]
+    fn test_aggregation_profile1() {
+        // streaming pre-aggregation behind a local shuffle, merged by a blocking aggregation
+        // behind an adaptive DOP stage
+        let profile = load("profile1.txt");
+        let phases = AggregateAnalyzer::collect_phases(&profile);
+        let preagg = phases.iter().find(|p| p.streaming).unwrap();
+        assert!(preagg.over_native_scan);
+        let merge = AggregateAnalyzer::second_phase_of(preagg, &phases).unwrap();
+        assert!(!merge.streaming);
+        assert_eq!(merge.input_exchange, preagg.output_exchange);
+        assert!(AggregateAnalyzer::analyze(&profile).is_empty());
+    }
+
+    #[test]
+    fn test_phases_paired_through_exchanges() {
+        // two scalar aggregations (no grouping keys), each merged after its own exchange
+        let scalar = |sink_id: i32, exchange: i32, first_id: i32, scan: i32| vec![
+            fragment(&sink_id.to_string(), vec![vec![
+                operator("AGGREGATE_BLOCKING_SINK", sink_id, &[], &[("InputRowCount", "32")]),
+                operator("EXCHANGE_SOURCE", exchange, &[], &[]),
+            ]]),
+            fragment(&first_id.to_string(), vec![
+                vec![operator("EXCHANGE_SINK", exchange, &[], &[]), operator("AGGREGATE_BLOCKING_SOURCE", first_id, &[], &[])],
+                vec![
+                    operator("AGGREGATE_BLOCKING_SINK", first_id, &[], &[("InputRowCount", "50.000M (50000000)")]),
+                    operator("OLAP_SCAN", scan, &[], &[]),
+                ],
+            ]),
+        ];
+        let profile = profile([scalar(4, 3, 2, 0), scalar(9, 8, 7, 5)].concat());
+        let phases = AggregateAnalyzer::collect_phases(&profile);
+        let phase = |id: i32| phases.iter().find(|p| p.node.plan_node_id == Some(id)).unwrap();
+        let id = |phase: Option<&AggregationPhase>| phase.and_then(|p| p.node.plan_node_id);
+
+        assert_eq!(id(AggregateAnalyzer::second_phase_of(phase(2), &phases)), Some(4));
+        assert_eq!(id(AggregateAnalyzer::second_phase_of(phase(7), &phases)), Some(9));
+        assert_eq!(id(AggregateAnalyzer::first_phase_of(phase(9), &phases)), Some(7));
+        assert!(AggregateAnalyzer::is_first_phase(phase(7), &phases));
+        assert!(!AggregateAnalyzer::is_first_phase(phase(9), &phases));
+    }
+}
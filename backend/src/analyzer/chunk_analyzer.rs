@@ -0,0 +1,202 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::tr;
+
+/// StarRocks default of the `chunk_size` session variable.
+const DEFAULT_CHUNK_SIZE: u64 = 4096;
+/// Edges carrying fewer rows are left out of the report.
+const MIN_REPORT_ROWS: u64 = 100_000;
+/// Under-filled edges are only worth flagging above this volume.
+const MIN_FLAG_ROWS: u64 = 1_000_000;
+/// Average fill below which per-chunk overhead dominates vectorized execution.
+const LOW_FILL_RATIO: f64 = 0.25;
+const VERY_LOW_FILL_RATIO: f64 = 0.05;
+
+/// Chunk fill per operator edge: how many rows each chunk carries relative to `chunk_size`,
+/// and where `CHUNK_ACCUMULATE` re-packs small chunks.
+pub struct ChunkAnalyzer;
+
+impl ChunkAnalyzer {
+    pub fn chunk_size(profile: &Profile) -> u64 {
+        profile.summary.variables.get("chunk_size")
+            .and_then(|v| v.parse::<u64>().ok())
+            .filter(|size| *size > 0)
+            .unwrap_or(DEFAULT_CHUNK_SIZE)
+    }
+
+    pub fn report(profile: &Profile) -> Vec<ChunkEdgeStats> {
+        let chunk_size = Self::chunk_size(profile) as f64;
+        let mut edges = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                // operators are listed sink first: operators[i] feeds operators[i - 1]
+                for i in 1..pipeline.operators.len() {
+                    let from = &pipeline.operators[i];
+                    let to = &pipeline.operators[i - 1];
+                    let count = |key: &str| from.common_metrics.get(key).and_then(|v| ValueParser::parse_number::<u64>(v).ok());
+                    let (Some(rows), Some(chunks)) = (count("PullRowNum"), count("PullChunkNum")) else {
+                        continue;
+                    };
+                    if rows < MIN_REPORT_ROWS || chunks == 0 {
+                        continue;
+                    }
+
+                    let avg = rows as f64 / chunks as f64;
+                    edges.push(ChunkEdgeStats {
+                        fragment_id: fragment.id.clone(),
+                        pipeline_id: pipeline.id.clone(),
+                        from_operator: from.name.clone(),
+                        to_operator: to.name.clone(),
+                        plan_node_id: from.plan_node_id.as_deref().and_then(|id| id.parse().ok()),
+                        rows,
+                        chunks,
+                        avg_rows_per_chunk: avg,
+                        fill_ratio: avg / chunk_size,
+                        accumulated: to.name == "CHUNK_ACCUMULATE",
+                    });
+                }
+            }
+        }
+
+        edges
+    }
+
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let chunk_size = Self::chunk_size(profile);
+
+        Self::report(profile).iter()
+            .filter(|edge| edge.rows >= MIN_FLAG_ROWS && edge.fill_ratio < LOW_FILL_RATIO && !edge.accumulated)
+            .filter(|edge| edge.from_operator != "CHUNK_ACCUMULATE")
+            .map(|edge| Self::build_hotspot(edge, chunk_size))
+            .collect()
+    }
+
+    fn build_hotspot(edge: &ChunkEdgeStats, chunk_size: u64) -> HotSpot {
+        let mut suggestions = Vec::new();
+        if edge.from_operator.contains("SCAN") {
+            suggestions.push(tr!("chunk.push_down_scan_filters"));
+        } else if edge.from_operator.contains("JOIN") {
+            suggestions.push(tr!("chunk.push_down_join_filters"));
+        }
+        suggestions.push(tr!("chunk.accumulate"));
+        if chunk_size < DEFAULT_CHUNK_SIZE {
+            suggestions.push(tr!("chunk.restore_chunk_size", chunk_size = chunk_size, default = DEFAULT_CHUNK_SIZE));
+        }
+
+        HotSpot {
+            node_path: format!("Fragment{}.Pipeline{}.{}", edge.fragment_id, edge.pipeline_id, edge.from_operator),
+            node: NodeRef {
+                plan_node_id: edge.plan_node_id,
+                fragment_id: Some(edge.fragment_id.clone()),
+                pipeline_id: Some(edge.pipeline_id.clone()),
+                tree_node_id: None,
+                operator_name: Some(edge.from_operator.clone()),
+            },
+            severity: if edge.fill_ratio < VERY_LOW_FILL_RATIO { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::UnderfilledChunks,
+            confidence: Confidence::High,
+            description: tr!(
+                "chunk.description",
+                from = edge.from_operator,
+                to = edge.to_operator,
+                rows = edge.rows,
+                chunks = edge.chunks,
+                avg_rows = edge.avg_rows_per_chunk,
+                chunk_size = chunk_size,
+                percent = edge.fill_ratio * 100.0,
+            ),
+            suggestions,
+            evidence: vec![
+                Evidence::new("PullRowNum / PullChunkNum / chunk_size", edge.fill_ratio, MetricUnit::Ratio).threshold(LOW_FILL_RATIO),
+                Evidence::new("PullRowNum", edge.rows as f64, MetricUnit::Count).threshold(MIN_FLAG_ROWS as f64),
+            ],
+        }
+    }
+
+    /// Sets each node's output chunk fill and whether a `CHUNK_ACCUMULATE` follows it.
+    pub fn mark_tree(tree: &mut ExecutionTree, profile: &Profile) {
+        let chunk_size = Self::chunk_size(profile) as f64;
+        let accumulated: Vec<(&str, i32)> = profile.fragments.iter()
+            .flat_map(|f| f.pipelines.iter().map(move |p| (f, p)))
+            .flat_map(|(f, p)| p.operators.iter().map(move |op| (f, op)))
+            .filter(|(_, op)| op.name == "CHUNK_ACCUMULATE")
+            .filter_map(|(f, op)| Some((f.id.as_str(), op.plan_node_id.as_deref()?.parse().ok()?)))
+            .collect();
+
+        let mark = |node: &mut ExecutionTreeNode| {
+            node.has_chunk_accumulate = match (node.fragment_id.as_deref(), node.plan_node_id) {
+                (Some(fragment), Some(id)) => accumulated.contains(&(fragment, id)),
+                _ => false,
+            };
+            node.chunk_fill = match (node.metrics.pull_row_num, node.metrics.pull_chunk_num) {
+                (Some(rows), Some(chunks)) if chunks > 0 => Some(rows as f64 / chunks as f64 / chunk_size),
+                _ => None,
+            };
+        };
+
+        for node in tree.nodes.iter_mut() {
+            mark(node);
+        }
+        mark(&mut tree.root);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fragment, load, metrics, operator, profile};
+
+    fn chunk_operator(name: &str, rows: &str, chunks: &str) -> Operator {
+        operator(name, 0, &[("PullRowNum", rows), ("PullChunkNum", chunks)], &[])
+    }
+
+    fn chunk_profile(operators: Vec<Operator>, variables: &[(&str, &str)]) -> Profile {
+        let mut profile = profile(vec![fragment("1", vec![operators])]);
+        profile.summary.variables = metrics(variables);
+        profile
+    }
+
+    #[test]
+    fn test_underfilled_chunks() {
+        // 2M rows in 20K chunks after a selective scan: 100 rows per chunk
+        let profile = chunk_profile(vec![
+            chunk_operator("AGGREGATE_BLOCKING_SINK", "0", "0"),
+            chunk_operator("PROJECT", "2.000M (2000000)", "20.000K (20000)"),
+            chunk_operator("CONNECTOR_SCAN", "2.000M (2000000)", "20.000K (20000)"),
+        ], &[("chunk_size", "4096")]);
+
+        let report = ChunkAnalyzer::report(&profile);
+        assert_eq!(report.len(), 2);
+        assert!((report[0].avg_rows_per_chunk - 100.0).abs() < 1e-9);
+
+        let hotspots = ChunkAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 2);
+        assert_eq!(hotspots[0].severity, HotSeverity::Moderate);
+        assert_eq!(hotspots[0].evidence[1].metric, "PullRowNum");
+        assert!(hotspots.iter().any(|h| h.suggestions[0].contains("存储层")));
+    }
+
+    #[test]
+    fn test_accumulated_chunks_not_flagged() {
+        let profile = chunk_profile(vec![
+            chunk_operator("PROJECT", "2.000M (2000000)", "500"),
+            chunk_operator("CHUNK_ACCUMULATE", "2.000M (2000000)", "500"),
+            chunk_operator("CONNECTOR_SCAN", "2.000M (2000000)", "20.000K (20000)"),
+        ], &[]);
+
+        let report = ChunkAnalyzer::report(&profile);
+        assert!(report.iter().any(|e| e.accumulated));
+        assert!(ChunkAnalyzer::analyze(&profile).is_empty());
+    }
+
+    #[test]
+    fn test_chunks_profile1() {
+        // the scan's small chunks are merged by CHUNK_ACCUMULATE before the next operator
+        let profile = load("profile1.txt");
+        let report = ChunkAnalyzer::report(&profile);
+        assert!(report.iter().any(|e| e.from_operator == "CONNECTOR_SCAN" && e.accumulated));
+        assert!(report.iter().all(|e| e.avg_rows_per_chunk > 0.0));
+        assert!(ChunkAnalyzer::analyze(&profile).is_empty());
+    }
+}
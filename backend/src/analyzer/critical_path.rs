@@ -0,0 +1,327 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use std::collections::{HashMap, HashSet};
+
+/// Operators that receive data from an `EXCHANGE_SINK` in another fragment.
+const EXCHANGE_RECEIVERS: &[&str] = &["EXCHANGE_SOURCE", "MERGE_EXCHANGE", "GLOBAL_PARALLEL_MERGE_SOURCE"];
+
+/// Sinks that write out of the query (client / table), used to locate the root pipeline.
+const FINAL_SINKS: &[&str] = &["RESULT_SINK", "OLAP_TABLE_SINK", "MYSQL_TABLE_SINK", "EXPORT_SINK", "ICEBERG_TABLE_SINK", "HIVE_TABLE_SINK", "TABLE_FUNCTION_TABLE_SINK", "BLACKHOLE_TABLE_SINK"];
+
+struct PipelineRef<'a> {
+    fragment_id: &'a str,
+    pipeline: &'a Pipeline,
+}
+
+impl PipelineRef<'_> {
+    fn sink(&self) -> Option<&Operator> {
+        self.pipeline.operators.first()
+    }
+
+    fn source(&self) -> Option<&Operator> {
+        self.pipeline.operators.last()
+    }
+}
+
+/// Critical-path computation over the fragment/pipeline dependency graph.
+///
+/// Each pipeline is weighted by the time its slowest driver spent working on its own
+/// (active time, plus IO wait for scan sources and sink wait for final sinks).
+/// Blocking edges (agg/sort sinks, join builds) serialize producer and consumer,
+/// streaming edges (exchanges, local exchanges) let them overlap.
+pub struct CriticalPathAnalyzer;
+
+impl CriticalPathAnalyzer {
+    pub fn analyze(profile: &Profile) -> Option<CriticalPath> {
+        let pipelines: Vec<PipelineRef> = profile.fragments.iter()
+            .flat_map(|f| f.pipelines.iter().map(move |p| PipelineRef { fragment_id: &f.id, pipeline: p }))
+            .filter(|p| !p.pipeline.operators.is_empty())
+            .collect();
+
+        if pipelines.is_empty() {
+            return None;
+        }
+
+        let producers: Vec<Vec<(usize, PipelineEdgeKind)>> = (0..pipelines.len())
+            .map(|idx| Self::find_producers(&pipelines, idx))
+            .collect();
+
+        let mut finish: HashMap<usize, (f64, Option<usize>)> = HashMap::new();
+        let mut visiting = HashSet::new();
+        for idx in 0..pipelines.len() {
+            Self::compute_finish(idx, &pipelines, &producers, &mut finish, &mut visiting);
+        }
+
+        let root_idx = pipelines.iter()
+            .position(|p| p.sink().map(Self::is_final_sink).unwrap_or(false))
+            .or_else(|| {
+                finish.iter()
+                    .max_by(|a, b| a.1.0.partial_cmp(&b.1.0).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(idx, _)| *idx)
+            })?;
+
+        let mut chain = vec![root_idx];
+        let mut cursor = root_idx;
+        while let Some((_, Some(prev))) = finish.get(&cursor) {
+            if chain.contains(prev) {
+                break;
+            }
+            chain.push(*prev);
+            cursor = *prev;
+        }
+        chain.reverse();
+
+        let mut stages = Vec::with_capacity(chain.len());
+        for (pos, &idx) in chain.iter().enumerate() {
+            let edge_to_next = chain.get(pos + 1).and_then(|next| {
+                producers[*next].iter().find(|(p, _)| *p == idx).map(|(_, kind)| *kind)
+            });
+            stages.push(Self::build_stage(&pipelines[idx], finish[&idx].0, edge_to_next));
+        }
+
+        let path_time_ms = finish[&root_idx].0;
+        let wall_time_ms = profile.summary.query_execution_wall_time_ms;
+        let coverage_percentage = wall_time_ms
+            .filter(|wall| *wall > 0.0)
+            .map(|wall| path_time_ms * 100.0 / wall);
+
+        Some(CriticalPath {
+            stages,
+            path_time_ms,
+            wall_time_ms,
+            coverage_percentage,
+        })
+    }
+
+    /// Flags execution tree nodes whose operators run in a critical-path pipeline.
+    pub fn mark_tree(tree: &mut ExecutionTree, path: &CriticalPath) {
+        let on_path = |node: &ExecutionTreeNode| {
+            path.stages.iter().any(|stage| match node.plan_node_id {
+                Some(id) if id >= 0 => stage.plan_node_ids.contains(&id),
+                _ => node.fragment_id.as_deref() == Some(stage.fragment_id.as_str())
+                    && stage.operators.contains(&node.operator_name),
+            })
+        };
+
+        for node in tree.nodes.iter_mut() {
+            node.is_on_critical_path = on_path(node);
+        }
+        tree.root.is_on_critical_path = on_path(&tree.root);
+    }
+
+    fn find_producers(pipelines: &[PipelineRef], consumer_idx: usize) -> Vec<(usize, PipelineEdgeKind)> {
+        let consumer = &pipelines[consumer_idx];
+        let mut result = Vec::new();
+
+        for op in &consumer.pipeline.operators {
+            let Some(plan_id) = Self::plan_node_id(op) else { continue };
+
+            for (idx, producer) in pipelines.iter().enumerate() {
+                if idx == consumer_idx {
+                    continue;
+                }
+                let Some(sink) = producer.sink() else { continue };
+                if Self::plan_node_id(sink) != Some(plan_id) {
+                    continue;
+                }
+
+                let same_fragment = producer.fragment_id == consumer.fragment_id;
+                let kind = if EXCHANGE_RECEIVERS.contains(&op.name.as_str()) {
+                    (sink.name == "EXCHANGE_SINK" && !same_fragment).then_some(PipelineEdgeKind::Exchange)
+                } else if same_fragment && Self::sink_feeds(&sink.name, &op.name) {
+                    Some(Self::edge_kind(&sink.name))
+                } else {
+                    None
+                };
+
+                if let Some(kind) = kind {
+                    result.push((idx, kind));
+                }
+            }
+        }
+
+        result
+    }
+
+    /// Whether a sink operator hands data to the given operator of another pipeline in the same fragment.
+    fn sink_feeds(sink_name: &str, op_name: &str) -> bool {
+        if let Some(family) = sink_name.strip_suffix("_BUILD") {
+            return op_name == format!("{}_PROBE", family);
+        }
+        let Some(family) = sink_name.strip_suffix("_SINK") else { return false };
+        if family.ends_with("SORT") {
+            return op_name.ends_with("MERGE_SOURCE");
+        }
+        op_name == format!("{}_SOURCE", family)
+    }
+
+    fn edge_kind(sink_name: &str) -> PipelineEdgeKind {
+        if sink_name == "LOCAL_EXCHANGE_SINK" || sink_name.starts_with("COLLECT_STATS") || sink_name.contains("STREAMING") {
+            PipelineEdgeKind::LocalExchange
+        } else {
+            PipelineEdgeKind::Blocking
+        }
+    }
+
+    fn compute_finish(
+        idx: usize,
+        pipelines: &[PipelineRef],
+        producers: &[Vec<(usize, PipelineEdgeKind)>],
+        finish: &mut HashMap<usize, (f64, Option<usize>)>,
+        visiting: &mut HashSet<usize>,
+    ) -> f64 {
+        if let Some((time, _)) = finish.get(&idx) {
+            return *time;
+        }
+        if !visiting.insert(idx) {
+            return 0.0;
+        }
+
+        let own = Self::own_time_ms(&pipelines[idx]);
+        let mut best = (own, None);
+        let mut best_upstream = 0.0;
+        for &(producer, kind) in &producers[idx] {
+            let upstream = Self::compute_finish(producer, pipelines, producers, finish, visiting);
+            let candidate = match kind {
+                PipelineEdgeKind::Blocking => upstream + own,
+                PipelineEdgeKind::Exchange | PipelineEdgeKind::LocalExchange => upstream.max(own),
+            };
+            // on ties keep following the slowest producer so the chain reaches the real source
+            if candidate > best.0 || (candidate == best.0 && upstream > best_upstream) {
+                best = (candidate, Some(producer));
+                best_upstream = upstream;
+            }
+        }
+
+        visiting.remove(&idx);
+        finish.insert(idx, best);
+        best.0
+    }
+
+    fn own_time_ms(pipeline: &PipelineRef) -> f64 {
+        let (active, input_wait, output_wait) = Self::time_components(pipeline);
+        active + input_wait + output_wait
+    }
+
+    /// Returns (active, input wait owned by this pipeline, output wait owned by this pipeline).
+    /// Input wait only counts for scan sources, where it is IO; otherwise it is upstream time.
+    /// Output wait only counts for final sinks, where it is client / load channel time.
+    fn time_components(pipeline: &PipelineRef) -> (f64, f64, f64) {
+        let metrics = &pipeline.pipeline.metrics;
+        let active = Self::max_time_ms(metrics, "ActiveTime");
+
+        let input_wait = if pipeline.source().map(|op| op.name.contains("SCAN")).unwrap_or(false) {
+            Self::max_time_ms(metrics, "InputEmptyTime")
+        } else {
+            0.0
+        };
+
+        let output_wait = if pipeline.sink().map(Self::is_final_sink).unwrap_or(false) {
+            Self::max_time_ms(metrics, "OutputFullTime") + Self::max_time_ms(metrics, "PendingFinishTime")
+        } else {
+            0.0
+        };
+
+        (active, input_wait, output_wait)
+    }
+
+    fn build_stage(pipeline: &PipelineRef, finish_time_ms: f64, edge_to_next: Option<PipelineEdgeKind>) -> CriticalPathStage {
+        let (active, input_wait, output_wait) = Self::time_components(pipeline);
+        let metrics = &pipeline.pipeline.metrics;
+
+        let mut plan_node_ids: Vec<i32> = pipeline.pipeline.operators.iter()
+            .filter_map(Self::plan_node_id)
+            .collect();
+        plan_node_ids.dedup();
+
+        CriticalPathStage {
+            fragment_id: pipeline.fragment_id.to_string(),
+            pipeline_id: pipeline.pipeline.id.clone(),
+            operators: pipeline.pipeline.operators.iter().map(|op| op.name.clone()).collect(),
+            plan_node_ids,
+            degree_of_parallelism: metrics.get("DegreeOfParallelism")
+                .and_then(|v| ValueParser::parse_number(v).ok()),
+            active_time_ms: active,
+            input_wait_ms: input_wait,
+            output_wait_ms: output_wait,
+            own_time_ms: active + input_wait + output_wait,
+            finish_time_ms,
+            driver_total_time_ms: Self::metric_ms(metrics, "__MAX_OF_DriverTotalTime")
+                .or_else(|| Self::metric_ms(metrics, "DriverTotalTime")),
+            edge_to_next,
+        }
+    }
+
+    fn is_final_sink(op: &Operator) -> bool {
+        op.common_metrics.contains_key("IsFinalSink") || FINAL_SINKS.contains(&op.name.as_str())
+    }
+
+    fn plan_node_id(op: &Operator) -> Option<i32> {
+        op.plan_node_id.as_ref().and_then(|id| id.parse::<i32>().ok())
+    }
+
+    fn max_time_ms(metrics: &HashMap<String, String>, name: &str) -> f64 {
+        Self::metric_ms(metrics, &format!("__MAX_OF_{}", name))
+            .or_else(|| Self::metric_ms(metrics, name))
+            .unwrap_or(0.0)
+    }
+
+    fn metric_ms(metrics: &HashMap<String, String>, name: &str) -> Option<f64> {
+        metrics.get(name).and_then(|v| ValueParser::parse_time_to_ms(v).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, operator, pipeline, profile, with_pipelines};
+
+    fn timed_pipeline(id: &str, active: &str, input_empty: &str, operators: Vec<Operator>) -> Pipeline {
+        pipeline(id, &[("ActiveTime", active), ("InputEmptyTime", input_empty)], operators)
+    }
+
+    #[test]
+    fn test_sink_feeds() {
+        assert!(CriticalPathAnalyzer::sink_feeds("LOCAL_EXCHANGE_SINK", "LOCAL_EXCHANGE_SOURCE"));
+        assert!(CriticalPathAnalyzer::sink_feeds("AGGREGATE_BLOCKING_SINK", "AGGREGATE_BLOCKING_SOURCE"));
+        assert!(CriticalPathAnalyzer::sink_feeds("LOCAL_SORT_SINK", "LOCAL_PARALLEL_MERGE_SOURCE"));
+        assert!(CriticalPathAnalyzer::sink_feeds("HASH_JOIN_BUILD", "HASH_JOIN_PROBE"));
+        assert!(!CriticalPathAnalyzer::sink_feeds("COLLECT_STATS_SINK", "LOCAL_EXCHANGE_SOURCE"));
+    }
+
+    #[test]
+    fn test_blocking_edge_serializes_pipelines() {
+        let fragment0 = with_pipelines("0", vec![
+            timed_pipeline("0", "1s", "0ns", vec![operator("RESULT_SINK", -1, &[], &[]), operator("AGGREGATE_BLOCKING_SOURCE", 2, &[], &[])]),
+            timed_pipeline("1", "2s", "0ns", vec![operator("AGGREGATE_BLOCKING_SINK", 2, &[], &[]), operator("EXCHANGE_SOURCE", 1, &[], &[])]),
+        ]);
+        let fragment1 = with_pipelines("1", vec![
+            timed_pipeline("0", "500ms", "5s", vec![operator("EXCHANGE_SINK", 1, &[], &[]), operator("OLAP_SCAN", 0, &[], &[])]),
+        ]);
+
+        let mut profile = profile(vec![fragment0, fragment1]);
+        profile.summary.query_execution_wall_time_ms = Some(10_000.0);
+        let path = CriticalPathAnalyzer::analyze(&profile).unwrap();
+
+        assert_eq!(path.stages.len(), 3);
+        assert_eq!(path.stages[0].fragment_id, "1");
+        assert_eq!(path.stages[0].own_time_ms, 5500.0);
+        assert_eq!(path.stages[0].edge_to_next, Some(PipelineEdgeKind::Exchange));
+        assert_eq!(path.stages[1].edge_to_next, Some(PipelineEdgeKind::Blocking));
+        // scan (5.5s) overlaps the exchange consumer (2s), then the blocking agg adds the final 1s
+        assert_eq!(path.path_time_ms, 6500.0);
+        assert_eq!(path.coverage_percentage, Some(65.0));
+    }
+
+    #[test]
+    fn test_critical_path_profile5() {
+        // INSERT ... SELECT: the scan feeds the table sink through a local exchange
+        let path = CriticalPathAnalyzer::analyze(&load("profile5.txt")).unwrap();
+        assert_eq!(path.stages.len(), 2);
+        assert!(path.stages[0].operators.iter().any(|op| op == "CONNECTOR_SCAN"));
+        assert_eq!(path.stages[0].edge_to_next, Some(PipelineEdgeKind::LocalExchange));
+        assert!(path.stages[1].operators.iter().any(|op| op == "OLAP_TABLE_SINK"));
+        // the sink mostly waits on its output, which counts towards its own time
+        assert_eq!(path.path_time_ms, 40798.0);
+    }
+}
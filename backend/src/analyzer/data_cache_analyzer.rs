@@ -0,0 +1,222 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::parser::specialized::ScanStrategy;
+use crate::{tr, tr_list};
+
+/// Local cache latency assumed when the scan did no local IO to measure it (NVMe/SSD block read).
+const ASSUMED_LOCAL_IO_LATENCY_MS: f64 = 1.0;
+/// Object storage latency per IO above which the storage side itself is worth checking.
+const HIGH_REMOTE_LATENCY_MS: f64 = 50.0;
+/// Below this expected gain a low hit ratio is not worth reporting.
+const MIN_SAVING_MS: f64 = 1000.0;
+const LOW_BYTE_HIT_PERCENTAGE: f64 = 50.0;
+/// Evidence holding the IO time a full cache hit would save, read by the speedup estimate.
+pub(crate) const IO_SAVING_EVIDENCE: &str = "EstimatedIoSaving";
+
+/// Data cache effectiveness per table for scans that read from remote storage
+/// (shared-data lake tables and external catalogs with data cache).
+pub struct DataCacheAnalyzer;
+
+impl DataCacheAnalyzer {
+    pub fn report(profile: &Profile) -> Vec<TableDataCacheStats> {
+        let mut tables: Vec<TableDataCacheStats> = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                // time counters are per-driver averages, counts and bytes are summed over all drivers
+                let drivers = pipeline.metrics.get("TotalDegreeOfParallelism")
+                    .or_else(|| pipeline.metrics.get("DegreeOfParallelism"))
+                    .and_then(|v| ValueParser::parse_number::<u64>(v).ok())
+                    .unwrap_or(1)
+                    .max(1) as f64;
+
+                for operator in pipeline.operators.iter().filter(|op| op.name.contains("SCAN")) {
+                    let scan = ScanStrategy::connector_from_metrics(&operator.unique_metrics);
+                    if scan.compressed_bytes_read_remote.is_none() && scan.io_count_remote.is_none() {
+                        continue;
+                    }
+
+                    let table = if scan.table.is_empty() { operator.name.clone() } else { scan.table.clone() };
+                    let idx = match tables.iter().position(|t| t.table == table) {
+                        Some(idx) => idx,
+                        None => {
+                            tables.push(Self::empty_stats(table));
+                            tables.len() - 1
+                        }
+                    };
+                    let stats = &mut tables[idx];
+
+                    let ms = |d: Option<std::time::Duration>| d.map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0);
+                    let local_ms = ms(scan.io_time_local_disk) * drivers;
+                    let remote_ms = ms(scan.io_time_remote) * drivers;
+                    let local_count = scan.io_count_local_disk.unwrap_or(0);
+                    let remote_count = scan.io_count_remote.unwrap_or(0);
+
+                    let local_latency = if local_count > 0 { local_ms / local_count as f64 } else { ASSUMED_LOCAL_IO_LATENCY_MS };
+                    let saving = (remote_ms - remote_count as f64 * local_latency).max(0.0) / drivers;
+
+                    stats.scan_nodes.push(format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, operator.name));
+                    stats.bytes_local += scan.compressed_bytes_read_local_disk.unwrap_or(0);
+                    stats.bytes_remote += scan.compressed_bytes_read_remote.unwrap_or(0);
+                    stats.io_count_local += local_count;
+                    stats.io_count_remote += remote_count;
+                    stats.io_time_local_ms += local_ms;
+                    stats.io_time_remote_ms += remote_ms;
+                    stats.estimated_saving_ms = Some(stats.estimated_saving_ms.unwrap_or(0.0) + saving);
+                }
+            }
+        }
+
+        for stats in &mut tables {
+            let percentage = |local: u64, remote: u64| {
+                (local + remote > 0).then(|| local as f64 / (local + remote) as f64 * 100.0)
+            };
+            stats.byte_hit_percentage = percentage(stats.bytes_local, stats.bytes_remote);
+            stats.io_hit_percentage = percentage(stats.io_count_local, stats.io_count_remote);
+            stats.local_latency_per_io_ms = (stats.io_count_local > 0)
+                .then(|| stats.io_time_local_ms / stats.io_count_local as f64);
+            stats.remote_latency_per_io_ms = (stats.io_count_remote > 0)
+                .then(|| stats.io_time_remote_ms / stats.io_count_remote as f64);
+        }
+
+        tables
+    }
+
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        Self::report(profile).iter().filter_map(Self::analyze_table).collect()
+    }
+
+    fn analyze_table(stats: &TableDataCacheStats) -> Option<HotSpot> {
+        let byte_hit = stats.byte_hit_percentage?;
+        let saving = stats.estimated_saving_ms.unwrap_or(0.0);
+        if byte_hit >= LOW_BYTE_HIT_PERCENTAGE || saving < MIN_SAVING_MS {
+            return None;
+        }
+
+        let severity = match saving {
+            s if s >= 60_000.0 => HotSeverity::Severe,
+            s if s >= 10_000.0 => HotSeverity::Moderate,
+            _ => HotSeverity::Mild,
+        };
+
+        let mut description = tr!(
+            "data_cache.description",
+            table = stats.table,
+            byte_hit = byte_hit,
+            bytes_local = ValueParser::format_bytes(stats.bytes_local),
+            bytes_remote = ValueParser::format_bytes(stats.bytes_remote),
+            io_hit = stats.io_hit_percentage.unwrap_or(0.0),
+            io_local = stats.io_count_local,
+            io_remote = stats.io_count_remote,
+        );
+        if let Some(latency) = stats.remote_latency_per_io_ms {
+            description.push_str(&tr!("data_cache.remote_latency", latency = latency));
+        }
+        description.push_str(&tr!("data_cache.saving", saving_secs = saving / 1000.0));
+
+        let mut suggestions = tr_list!("data_cache.suggestions", table = stats.table);
+        if stats.remote_latency_per_io_ms.map(|l| l > HIGH_REMOTE_LATENCY_MS).unwrap_or(false) {
+            suggestions.push(tr!("data_cache.high_remote_latency"));
+        }
+
+        Some(HotSpot {
+            node_path: stats.scan_nodes.join(", "),
+            // spans every scan of the table
+            node: NodeRef::default(),
+            severity,
+            issue_type: IssueCode::LowDataCacheHit,
+            confidence: Confidence::High,
+            description,
+            suggestions,
+            evidence: vec![
+                Evidence::new("BytesRead hit ratio", byte_hit / 100.0, MetricUnit::Ratio).threshold(LOW_BYTE_HIT_PERCENTAGE / 100.0),
+                Evidence::new(IO_SAVING_EVIDENCE, saving, MetricUnit::Milliseconds).threshold(MIN_SAVING_MS),
+            ],
+        })
+    }
+
+    fn empty_stats(table: String) -> TableDataCacheStats {
+        TableDataCacheStats {
+            table,
+            scan_nodes: Vec::new(),
+            bytes_local: 0,
+            bytes_remote: 0,
+            byte_hit_percentage: None,
+            io_count_local: 0,
+            io_count_remote: 0,
+            io_hit_percentage: None,
+            io_time_local_ms: 0.0,
+            io_time_remote_ms: 0.0,
+            local_latency_per_io_ms: None,
+            remote_latency_per_io_ms: None,
+            estimated_saving_ms: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fragment, load, operator, profile};
+
+    fn scan_profile(dop: &str, unique: &[(&str, &str)]) -> Profile {
+        let mut profile = profile(vec![fragment("2", vec![vec![operator("CONNECTOR_SCAN", 0, &[], unique)]])]);
+        profile.fragments[0].pipelines[0].metrics.insert("TotalDegreeOfParallelism".to_string(), dop.to_string());
+        profile
+    }
+
+    #[test]
+    fn test_cold_table_report() {
+        // counters from profile1: every byte read from object storage
+        let profile = scan_profile("32", &[
+            ("Table", "ads_user"),
+            ("CompressedBytesReadLocalDisk", "0.000 B"),
+            ("CompressedBytesReadRemote", "23.757 GB"),
+            ("IOCountLocalDisk", "0"),
+            ("IOCountRemote", "179.200K (179200)"),
+            ("IOTimeLocalDisk", "0ns"),
+            ("IOTimeRemote", "3m7s"),
+        ]);
+
+        let report = DataCacheAnalyzer::report(&profile);
+        assert_eq!(report.len(), 1);
+        let stats = &report[0];
+        assert_eq!(stats.byte_hit_percentage, Some(0.0));
+        assert_eq!(stats.io_count_remote, 179_200);
+        let latency = stats.remote_latency_per_io_ms.unwrap();
+        assert!((latency - 187_000.0 * 32.0 / 179_200.0).abs() < 1e-6);
+        // 5600 IOs per driver at 1ms locally instead of 33ms remotely
+        assert!((stats.estimated_saving_ms.unwrap() - (187_000.0 - 5_600.0)).abs() < 1e-6);
+
+        let hotspots = DataCacheAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].severity, HotSeverity::Severe);
+        assert!(hotspots[0].suggestions.iter().any(|s| s.contains("CACHE SELECT")));
+    }
+
+    #[test]
+    fn test_warm_table_not_reported() {
+        let profile = scan_profile("8", &[
+            ("Table", "t"),
+            ("CompressedBytesReadLocalDisk", "9.000 GB"),
+            ("CompressedBytesReadRemote", "1.000 GB"),
+            ("IOCountLocalDisk", "9000"),
+            ("IOCountRemote", "1000"),
+            ("IOTimeLocalDisk", "2s"),
+            ("IOTimeRemote", "4s"),
+        ]);
+        assert_eq!(DataCacheAnalyzer::report(&profile)[0].byte_hit_percentage, Some(90.0));
+        assert!(DataCacheAnalyzer::analyze(&profile).is_empty());
+    }
+
+    #[test]
+    fn test_cold_table_profile3() {
+        let profile = load("profile3.txt");
+        let report = DataCacheAnalyzer::report(&profile);
+        assert_eq!(report.len(), 1);
+        assert_eq!(report[0].byte_hit_percentage, Some(0.0));
+        let hotspots = DataCacheAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].severity, HotSeverity::Severe);
+    }
+}
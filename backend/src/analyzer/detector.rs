@@ -0,0 +1,274 @@
+use crate::models::*;
+use once_cell::sync::Lazy;
+use std::sync::{Arc, RwLock};
+
+use super::rule_engine::RuleEngine;
+use super::skew_detector::SkewDetector;
+use super::exchange_analyzer::ExchangeAnalyzer;
+use super::olap_scan_analyzer::OlapScanAnalyzer;
+use super::data_cache_analyzer::DataCacheAnalyzer;
+use super::external_scan_analyzer::ExternalScanAnalyzer;
+use super::aggregate_analyzer::AggregateAnalyzer;
+use super::spill_analyzer::SpillAnalyzer;
+use super::chunk_analyzer::ChunkAnalyzer;
+use super::local_exchange_analyzer::LocalExchangeAnalyzer;
+use super::adaptive_dop_analyzer::AdaptiveDopAnalyzer;
+use super::lifecycle_analyzer::LifecycleAnalyzer;
+use super::efficiency_analyzer::EfficiencyAnalyzer;
+
+/// A hotspot check. Implement it to ship checks of your own and add them with
+/// [`DetectorRegistry::register`].
+pub trait Detector: Send + Sync {
+    /// Unique name used to enable, disable or replace the detector.
+    fn name(&self) -> &str;
+
+    fn detect(&self, profile: &Profile, tree: Option<&ExecutionTree>) -> Vec<HotSpot>;
+}
+
+/// A detector backed by a function, for checks that need no state of their own.
+pub struct FnDetector<F> {
+    name: String,
+    detect: F,
+}
+
+impl<F> FnDetector<F>
+where
+    F: Fn(&Profile, Option<&ExecutionTree>) -> Vec<HotSpot> + Send + Sync,
+{
+    pub fn new(name: impl Into<String>, detect: F) -> Self {
+        Self { name: name.into(), detect }
+    }
+}
+
+impl<F> Detector for FnDetector<F>
+where
+    F: Fn(&Profile, Option<&ExecutionTree>) -> Vec<HotSpot> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn detect(&self, profile: &Profile, tree: Option<&ExecutionTree>) -> Vec<HotSpot> {
+        (self.detect)(profile, tree)
+    }
+}
+
+/// Hotspots of the declarative rules (`crate::rules`): the query, then each execution tree
+/// node, or each pipeline operator when the profile has no tree.
+pub struct RuleDetector;
+
+impl Detector for RuleDetector {
+    fn name(&self) -> &str {
+        "rules"
+    }
+
+    fn detect(&self, profile: &Profile, tree: Option<&ExecutionTree>) -> Vec<HotSpot> {
+        let rules = crate::rules::active();
+        let mut hotspots = RuleEngine::evaluate_query(&rules, profile);
+
+        if let Some(tree) = tree {
+            for node in &tree.nodes {
+                hotspots.extend(RuleEngine::evaluate_node(&rules, node, profile));
+            }
+        } else {
+            for fragment in &profile.fragments {
+                for pipeline in &fragment.pipelines {
+                    for operator in &pipeline.operators {
+                        hotspots.extend(RuleEngine::evaluate_operator(&rules, &fragment.id, &pipeline.id, operator, profile));
+                    }
+                }
+            }
+        }
+
+        hotspots
+    }
+}
+
+type AnalyzeFn = fn(&Profile) -> Vec<HotSpot>;
+
+/// Adapts the built-in analyzers, which only need the profile.
+struct AnalyzerDetector {
+    name: &'static str,
+    analyze: AnalyzeFn,
+}
+
+impl Detector for AnalyzerDetector {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn detect(&self, profile: &Profile, _tree: Option<&ExecutionTree>) -> Vec<HotSpot> {
+        (self.analyze)(profile)
+    }
+}
+
+/// Names of the built-in detectors, in the order they run.
+pub const BUILTIN_DETECTORS: &[&str] = &[
+    "rules", "skew", "exchange", "olap_scan", "data_cache", "external_scan", "aggregate",
+    "spill", "chunk", "local_exchange", "adaptive_dop", "lifecycle", "efficiency",
+];
+
+struct Registration {
+    detector: Arc<dyn Detector>,
+    enabled: bool,
+}
+
+/// The detectors an analysis runs, in registration order.
+#[derive(Default)]
+pub struct DetectorRegistry {
+    registrations: Vec<Registration>,
+}
+
+impl DetectorRegistry {
+    /// An empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in detectors, all enabled.
+    pub fn builtin() -> Self {
+        let analyzers: [(&'static str, AnalyzeFn); 12] = [
+            ("skew", SkewDetector::analyze),
+            ("exchange", ExchangeAnalyzer::analyze),
+            ("olap_scan", OlapScanAnalyzer::analyze),
+            ("data_cache", DataCacheAnalyzer::analyze),
+            ("external_scan", ExternalScanAnalyzer::analyze),
+            ("aggregate", AggregateAnalyzer::analyze),
+            ("spill", SpillAnalyzer::analyze),
+            ("chunk", ChunkAnalyzer::analyze),
+            ("local_exchange", LocalExchangeAnalyzer::analyze),
+            ("adaptive_dop", AdaptiveDopAnalyzer::analyze),
+            ("lifecycle", LifecycleAnalyzer::analyze),
+            ("efficiency", EfficiencyAnalyzer::analyze),
+        ];
+
+        let mut registry = Self::new();
+        registry.register(RuleDetector);
+        for (name, analyze) in analyzers {
+            registry.register(AnalyzerDetector { name, analyze });
+        }
+        registry
+    }
+
+    /// Adds an enabled detector, replacing a registered one of the same name in place.
+    pub fn register(&mut self, detector: impl Detector + 'static) -> &mut Self {
+        let detector: Arc<dyn Detector> = Arc::new(detector);
+        match self.registrations.iter_mut().find(|r| r.detector.name() == detector.name()) {
+            Some(existing) => *existing = Registration { detector, enabled: true },
+            None => self.registrations.push(Registration { detector, enabled: true }),
+        }
+        self
+    }
+
+    /// Removes a detector, returning whether it was registered.
+    pub fn unregister(&mut self, name: &str) -> bool {
+        let before = self.registrations.len();
+        self.registrations.retain(|r| r.detector.name() != name);
+        self.registrations.len() != before
+    }
+
+    /// Lets a registered detector run again after [`Self::disable`]; false if no detector has
+    /// that name.
+    pub fn enable(&mut self, name: &str) -> bool {
+        self.set_enabled(name, true)
+    }
+
+    /// Keeps a detector registered, in its place in the run order, but skips it until it is
+    /// enabled again; false if no detector has that name.
+    pub fn disable(&mut self, name: &str) -> bool {
+        self.set_enabled(name, false)
+    }
+
+    fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        self.registrations.iter_mut()
+            .find(|r| r.detector.name() == name)
+            .map(|r| r.enabled = enabled)
+            .is_some()
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.registrations.iter().any(|r| r.enabled && r.detector.name() == name)
+    }
+
+    /// Registered detector names with their enabled flag, in run order.
+    pub fn detectors(&self) -> Vec<(String, bool)> {
+        self.registrations.iter()
+            .map(|r| (r.detector.name().to_string(), r.enabled))
+            .collect()
+    }
+
+    /// Runs the enabled detectors and orders the hotspots by severity, most severe first.
+    pub fn detect(&self, profile: &Profile) -> Vec<HotSpot> {
+        let tree = profile.execution_tree.as_ref();
+        let mut hotspots: Vec<HotSpot> = self.registrations.iter()
+            .filter(|r| r.enabled)
+            .flat_map(|r| r.detector.detect(profile, tree))
+            .collect();
+
+        hotspots.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.confidence.cmp(&a.confidence)));
+
+        hotspots
+    }
+}
+
+static ACTIVE_REGISTRY: Lazy<RwLock<Arc<DetectorRegistry>>> =
+    Lazy::new(|| RwLock::new(Arc::new(DetectorRegistry::builtin())));
+
+/// The registry `HotSpotDetector::analyze` and `analyze_profile` run with.
+pub fn active() -> Arc<DetectorRegistry> {
+    ACTIVE_REGISTRY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn install(registry: DetectorRegistry) {
+    *ACTIVE_REGISTRY.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(registry);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{hotspot, load, profile};
+
+    fn long_running() -> Profile {
+        let mut profile = profile(Vec::new());
+        profile.summary.total_time_ms = Some(2.0 * 3_600_000.0);
+        profile
+    }
+
+    #[test]
+    fn test_builtin_registry() {
+        let registry = DetectorRegistry::builtin();
+        let names: Vec<String> = registry.detectors().into_iter().map(|(name, _)| name).collect();
+        assert_eq!(names, BUILTIN_DETECTORS);
+        assert!(registry.detect(&long_running()).iter().any(|h| h.issue_type == IssueCode::LongRunning));
+    }
+
+    #[test]
+    fn test_custom_and_disabled_detectors() {
+        let mut registry = DetectorRegistry::builtin();
+        assert!(registry.disable("rules"));
+        assert!(!registry.disable("missing"));
+        registry.register(FnDetector::new("company_check", |_: &Profile, _: Option<&ExecutionTree>| {
+            vec![hotspot(IssueCode::from("Mild"), HotSeverity::Mild), hotspot(IssueCode::from("Critical"), HotSeverity::Critical)]
+        }));
+
+        let hotspots = registry.detect(&long_running());
+        let types: Vec<&str> = hotspots.iter().map(|h| h.issue_type.as_str()).collect();
+        assert_eq!(types, ["Critical", "Mild"]);
+        assert!(matches!(hotspots[0].issue_type, IssueCode::Custom(_)));
+
+        registry.enable("rules");
+        assert!(registry.unregister("company_check"));
+        assert!(registry.is_enabled("rules"));
+        assert_eq!(registry.detect(&long_running())[0].issue_type, IssueCode::LongRunning);
+    }
+
+    #[test]
+    fn test_disabled_detector_profile3() {
+        let profile = load("profile3.txt");
+        let mut registry = DetectorRegistry::builtin();
+        assert!(registry.detect(&profile).iter().any(|h| h.issue_type == IssueCode::LowDataCacheHit));
+
+        assert!(registry.disable("data_cache"));
+        assert!(!registry.detect(&profile).iter().any(|h| h.issue_type == IssueCode::LowDataCacheHit));
+    }
+}
@@ -0,0 +1,264 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::{tr, tr_list};
+
+/// CPU efficiency from which the drivers are busy computing most of the time.
+const CPU_BOUND_EFFICIENCY: f64 = 0.5;
+/// Shares of the cumulative operator time that make scan or network the bottleneck.
+const IO_BOUND_SCAN_SHARE: f64 = 0.5;
+const NETWORK_BOUND_SHARE: f64 = 0.3;
+/// Result delivery share of the wall time beyond which execution itself is not the bottleneck.
+const DELIVERY_BOUND_SHARE: f64 = 0.5;
+/// A pipeline active for this share of the wall time at a fraction of the query's DOP serializes it.
+const SERIAL_PIPELINE_ACTIVE_SHARE: f64 = 0.5;
+const SERIAL_PIPELINE_DOP_FRACTION: u64 = 4;
+/// Classifying very short queries is noise.
+const MIN_WALL_TIME_MS: f64 = 5000.0;
+
+/// Pipeline that runs much narrower than the rest of the query while being busy most of the time.
+struct SerialPipeline {
+    fragment_id: String,
+    pipeline_id: String,
+    dop: u64,
+    max_dop: u64,
+    active_ms: f64,
+}
+
+/// CPU time versus wall time x DOP: classifies the query as CPU-, I/O-, network-bound,
+/// under-parallelized or waiting, and explains why.
+pub struct EfficiencyAnalyzer;
+
+impl EfficiencyAnalyzer {
+    pub fn report(profile: &Profile) -> Option<QueryEfficiency> {
+        let summary = &profile.summary;
+        let cpu_time_ms = summary.query_cumulative_cpu_time_ms?;
+        let wall_time_ms = summary.query_execution_wall_time_ms.filter(|w| *w > 0.0)?;
+        let total_dop = summary.total_degree_of_parallelism.filter(|d| *d > 0)?;
+        let cpu_efficiency = summary.cpu_efficiency.unwrap_or(cpu_time_ms / (wall_time_ms * total_dop as f64));
+
+        let fragments = profile.fragments.iter()
+            .filter_map(|fragment| Self::fragment_efficiency(fragment, wall_time_ms))
+            .collect();
+
+        let mut efficiency = QueryEfficiency {
+            cpu_time_ms,
+            wall_time_ms,
+            scan_time_ms: summary.query_cumulative_scan_time_ms.unwrap_or(0.0),
+            network_time_ms: summary.query_cumulative_network_time_ms.unwrap_or(0.0),
+            operator_time_ms: summary.query_cumulative_operator_time_ms.unwrap_or(0.0),
+            total_dop,
+            cpu_efficiency,
+            bound: ExecutionBound::Waiting,
+            explanation: String::new(),
+            fragments,
+        };
+        let (bound, explanation) = Self::classify(&efficiency, profile);
+        efficiency.bound = bound;
+        efficiency.explanation = explanation;
+        Some(efficiency)
+    }
+
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let Some(efficiency) = Self::report(profile) else {
+            return Vec::new();
+        };
+        if efficiency.wall_time_ms < MIN_WALL_TIME_MS || efficiency.bound == ExecutionBound::Waiting {
+            return Vec::new();
+        }
+
+        let suggestions = match efficiency.bound {
+            ExecutionBound::CpuBound => tr_list!("efficiency.cpu_bound.suggestions"),
+            ExecutionBound::IoBound => tr_list!("efficiency.io_bound.suggestions"),
+            ExecutionBound::NetworkBound => tr_list!("efficiency.network_bound.suggestions"),
+            ExecutionBound::UnderParallelized => tr_list!("efficiency.under_parallelized.suggestions"),
+            ExecutionBound::Waiting => Vec::new(),
+        };
+
+        let share = |part: f64| if efficiency.operator_time_ms > 0.0 { part / efficiency.operator_time_ms } else { 0.0 };
+        let cpu = Evidence::new("CpuEfficiency", efficiency.cpu_efficiency, MetricUnit::Ratio);
+        let (issue_type, evidence) = match efficiency.bound {
+            ExecutionBound::CpuBound => (IssueCode::CpuBound, vec![cpu.threshold(CPU_BOUND_EFFICIENCY)]),
+            ExecutionBound::IoBound => (IssueCode::IoBound, vec![
+                cpu,
+                Evidence::new("QueryCumulativeScanTime / QueryCumulativeOperatorTime", share(efficiency.scan_time_ms), MetricUnit::Ratio)
+                    .threshold(IO_BOUND_SCAN_SHARE),
+            ]),
+            ExecutionBound::NetworkBound => (IssueCode::NetworkBound, vec![
+                cpu,
+                Evidence::new("QueryCumulativeNetworkTime / QueryCumulativeOperatorTime", share(efficiency.network_time_ms), MetricUnit::Ratio)
+                    .threshold(NETWORK_BOUND_SHARE),
+            ]),
+            ExecutionBound::UnderParallelized | ExecutionBound::Waiting => (IssueCode::UnderParallelized, vec![cpu]),
+        };
+
+        vec![HotSpot {
+            node_path: "Execution.Overview".to_string(),
+            node: NodeRef::default(),
+            severity: if efficiency.bound == ExecutionBound::UnderParallelized { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type,
+            // the bound is inferred from query-wide totals
+            confidence: Confidence::Medium,
+            description: efficiency.explanation,
+            suggestions,
+            evidence,
+        }]
+    }
+
+    fn classify(efficiency: &QueryEfficiency, profile: &Profile) -> (ExecutionBound, String) {
+        let percent = |part: f64, whole: f64| if whole > 0.0 { part / whole * 100.0 } else { 0.0 };
+        let cpu_line = tr!(
+            "efficiency.cpu_line",
+            cpu_secs = efficiency.cpu_time_ms / 1000.0,
+            wall_secs = efficiency.wall_time_ms / 1000.0,
+            dop = efficiency.total_dop,
+            percent = efficiency.cpu_efficiency * 100.0,
+        );
+
+        let deliver_ms = profile.summary.result_deliver_time_ms.unwrap_or(0.0);
+        if deliver_ms >= efficiency.wall_time_ms * DELIVERY_BOUND_SHARE {
+            return (ExecutionBound::Waiting, tr!(
+                "efficiency.delivery_bound",
+                cpu_line = cpu_line,
+                percent = percent(deliver_ms, efficiency.wall_time_ms),
+            ));
+        }
+        if let Some(serial) = Self::serial_pipeline(profile, efficiency.wall_time_ms - deliver_ms) {
+            return (ExecutionBound::UnderParallelized, tr!(
+                "efficiency.under_parallelized.explanation",
+                cpu_line = cpu_line,
+                fragment = serial.fragment_id,
+                pipeline = serial.pipeline_id,
+                dop = serial.dop,
+                max_dop = serial.max_dop,
+                secs = serial.active_ms / 1000.0,
+            ));
+        }
+        if efficiency.cpu_efficiency >= CPU_BOUND_EFFICIENCY {
+            return (ExecutionBound::CpuBound, tr!("efficiency.cpu_bound.explanation", cpu_line = cpu_line));
+        }
+        if efficiency.scan_time_ms >= efficiency.operator_time_ms * IO_BOUND_SCAN_SHARE && efficiency.scan_time_ms > 0.0 {
+            return (ExecutionBound::IoBound, tr!(
+                "efficiency.io_bound.explanation",
+                cpu_line = cpu_line,
+                percent = percent(efficiency.scan_time_ms, efficiency.operator_time_ms),
+            ));
+        }
+        if efficiency.network_time_ms >= efficiency.operator_time_ms * NETWORK_BOUND_SHARE && efficiency.network_time_ms > 0.0 {
+            return (ExecutionBound::NetworkBound, tr!(
+                "efficiency.network_bound.explanation",
+                cpu_line = cpu_line,
+                percent = percent(efficiency.network_time_ms, efficiency.operator_time_ms),
+            ));
+        }
+        (ExecutionBound::Waiting, tr!("efficiency.waiting", cpu_line = cpu_line))
+    }
+
+    fn serial_pipeline(profile: &Profile, busy_wall_ms: f64) -> Option<SerialPipeline> {
+        let dop = |pipeline: &Pipeline| pipeline.metrics.get("DegreeOfParallelism").and_then(|v| ValueParser::parse_number::<u64>(v).ok());
+        let max_dop = profile.fragments.iter().flat_map(|f| f.pipelines.iter()).filter_map(dop).max()?;
+        if busy_wall_ms <= 0.0 || max_dop < SERIAL_PIPELINE_DOP_FRACTION {
+            return None;
+        }
+
+        profile.fragments.iter()
+            .flat_map(|f| f.pipelines.iter().map(move |p| (f, p)))
+            .filter_map(|(fragment, pipeline)| {
+                let dop = dop(pipeline)?;
+                let active_ms = Self::active_ms(pipeline)?;
+                (dop * SERIAL_PIPELINE_DOP_FRACTION <= max_dop && active_ms >= busy_wall_ms * SERIAL_PIPELINE_ACTIVE_SHARE)
+                    .then(|| SerialPipeline {
+                        fragment_id: fragment.id.clone(),
+                        pipeline_id: pipeline.id.clone(),
+                        dop,
+                        max_dop,
+                        active_ms,
+                    })
+            })
+            .max_by(|a, b| a.active_ms.total_cmp(&b.active_ms))
+    }
+
+    fn fragment_efficiency(fragment: &Fragment, wall_time_ms: f64) -> Option<FragmentEfficiency> {
+        let mut cpu_time_ms = 0.0;
+        let mut total_dop = 0;
+        for pipeline in &fragment.pipelines {
+            let dop = pipeline.metrics.get("TotalDegreeOfParallelism")
+                .or_else(|| pipeline.metrics.get("DegreeOfParallelism"))
+                .and_then(|v| ValueParser::parse_number::<u64>(v).ok())
+                .unwrap_or(1);
+            cpu_time_ms += Self::active_ms(pipeline).unwrap_or(0.0) * dop as f64;
+            total_dop = total_dop.max(dop);
+        }
+        if total_dop == 0 {
+            return None;
+        }
+
+        Some(FragmentEfficiency {
+            fragment_id: fragment.id.clone(),
+            cpu_time_ms,
+            total_dop,
+            cpu_efficiency: cpu_time_ms / (wall_time_ms * total_dop as f64),
+        })
+    }
+
+    fn active_ms(pipeline: &Pipeline) -> Option<f64> {
+        pipeline.metrics.get("ActiveTime").and_then(|v| ValueParser::parse_time_to_ms(v).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, pipeline, profile, with_pipelines};
+
+    fn dop_pipeline(id: &str, dop: &str, total: &str, active: &str) -> Pipeline {
+        pipeline(id, &[("DegreeOfParallelism", dop), ("TotalDegreeOfParallelism", total), ("ActiveTime", active)], Vec::new())
+    }
+
+    fn efficiency_profile(cpu_ms: f64, scan_ms: f64, pipelines: Vec<Pipeline>) -> Profile {
+        let mut profile = profile(vec![with_pipelines("1", pipelines)]);
+        profile.summary = ProfileSummary {
+            query_cumulative_cpu_time_ms: Some(cpu_ms),
+            query_execution_wall_time_ms: Some(60_000.0),
+            query_cumulative_operator_time_ms: Some(60_000.0),
+            query_cumulative_scan_time_ms: Some(scan_ms),
+            total_degree_of_parallelism: Some(32),
+            ..ProfileSummary::default()
+        };
+        profile
+    }
+
+    #[test]
+    fn test_io_bound_and_cpu_bound() {
+        // like profile1: 1.7s CPU over 60s x 32 drivers, scans take the whole time
+        let io = efficiency_profile(1_727.0, 60_000.0, vec![dop_pipeline("0", "32", "32", "538.833us")]);
+        let efficiency = EfficiencyAnalyzer::report(&io).unwrap();
+        assert_eq!(efficiency.bound, ExecutionBound::IoBound);
+        assert_eq!(EfficiencyAnalyzer::analyze(&io)[0].issue_type, IssueCode::IoBound);
+
+        let cpu = efficiency_profile(1_500_000.0, 0.0, vec![dop_pipeline("0", "32", "32", "50s")]);
+        let efficiency = EfficiencyAnalyzer::report(&cpu).unwrap();
+        assert_eq!(efficiency.bound, ExecutionBound::CpuBound);
+        assert!((efficiency.fragments[0].cpu_efficiency - 50.0 / 60.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_under_parallelized() {
+        let profile = efficiency_profile(50_000.0, 0.0, vec![
+            dop_pipeline("0", "32", "32", "100ms"),
+            dop_pipeline("1", "1", "1", "45s"),
+        ]);
+        let hotspots = EfficiencyAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::UnderParallelized);
+        assert!(hotspots[0].description.contains("Pipeline1"));
+    }
+
+    #[test]
+    fn test_io_bound_profile1() {
+        let profile = load("profile1.txt");
+        assert_eq!(EfficiencyAnalyzer::report(&profile).unwrap().bound, ExecutionBound::IoBound);
+        let hotspots = EfficiencyAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::IoBound);
+    }
+}
@@ -0,0 +1,468 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::parser::specialized::ExchangeSinkStrategy;
+use crate::{tr, tr_list};
+use std::collections::HashSet;
+
+const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+/// Below this volume an exchange is cheap whatever its settings.
+const SIGNIFICANT_EXCHANGE_BYTES: f64 = GB;
+/// Shuffles above this volume are reported on their own.
+const LARGE_EXCHANGE_BYTES: f64 = 10.0 * GB;
+/// Time-based rules only fire once the per-instance cost reaches this.
+const SIGNIFICANT_EXCHANGE_TIME_MS: f64 = 1000.0;
+/// Serialized / compressed below this means compression barely pays off.
+const LOW_COMPRESSION_RATIO: f64 = 1.3;
+/// Broadcasting a build side with more rows than this is usually slower than shuffling.
+const BROADCAST_MAX_BUILD_ROWS: u64 = 10_000_000;
+/// Build sides this small are cheap to broadcast.
+const BROADCAST_FRIENDLY_BUILD_ROWS: u64 = 1_000_000;
+
+const EXCHANGE_RECEIVERS: &[&str] = &["EXCHANGE_SOURCE", "MERGE_EXCHANGE"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum JoinSide {
+    Build,
+    Probe,
+}
+
+struct ExchangeRef<'a> {
+    fragment: &'a Fragment,
+    pipeline: &'a Pipeline,
+    operator: &'a Operator,
+    metrics: ExchangeSinkSpecializedMetrics,
+}
+
+/// A hash join whose build and/or probe input arrives through an exchange.
+struct JoinContext<'a> {
+    plan_node_id: String,
+    distribution_mode: String,
+    build_rows: Option<u64>,
+    probe_rows: Option<u64>,
+    build_exchange: Option<&'a ExchangeRef<'a>>,
+    probe_exchange: Option<&'a ExchangeRef<'a>>,
+}
+
+/// Exchange cost analysis (volume per destination, compression, serialization, pass-through)
+/// and join distribution advice (broadcast / shuffle / bucket shuffle / colocate) based on
+/// the exchanges that feed each hash join.
+pub struct ExchangeAnalyzer;
+
+impl ExchangeAnalyzer {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let exchanges: Vec<ExchangeRef> = profile.fragments.iter()
+            .flat_map(|fragment| fragment.pipelines.iter().map(move |pipeline| (fragment, pipeline)))
+            .flat_map(|(fragment, pipeline)| pipeline.operators.iter().map(move |op| (fragment, pipeline, op)))
+            .filter(|(_, _, op)| op.name == "EXCHANGE_SINK")
+            .map(|(fragment, pipeline, operator)| ExchangeRef {
+                fragment,
+                pipeline,
+                operator,
+                metrics: ExchangeSinkStrategy::from_metrics(&operator.unique_metrics),
+            })
+            .collect();
+
+        let mut hotspots = Vec::new();
+        for exchange in &exchanges {
+            hotspots.extend(Self::analyze_exchange(profile, exchange));
+        }
+        for join in Self::find_joins(profile, &exchanges) {
+            hotspots.extend(Self::analyze_join_distribution(&join));
+        }
+
+        hotspots
+    }
+
+    fn analyze_exchange(profile: &Profile, exchange: &ExchangeRef) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+        let m = &exchange.metrics;
+        let node_path = Self::node_path(exchange);
+        let node = NodeRef::operator(&exchange.fragment.id, &exchange.pipeline.id, exchange.operator);
+
+        let bytes_sent = m.bytes_sent.unwrap_or(0) as f64;
+        if bytes_sent < SIGNIFICANT_EXCHANGE_BYTES {
+            return hotspots;
+        }
+
+        let channels = m.channel_num.unwrap_or(1).max(1);
+        let per_destination = bytes_sent / channels as f64;
+        let volume = tr!(
+            "exchange.volume",
+            part_type = m.part_type,
+            bytes = ValueParser::format_bytes(bytes_sent as u64),
+            channels = channels,
+            per_channel = ValueParser::format_bytes(per_destination as u64),
+        );
+
+        if bytes_sent >= LARGE_EXCHANGE_BYTES {
+            hotspots.push(HotSpot {
+                node_path: node_path.clone(),
+                node: node.clone(),
+                severity: HotSeverity::Moderate,
+                issue_type: IssueCode::LargeExchange,
+                confidence: Confidence::High,
+                description: tr!("exchange.large.description", volume = volume),
+                suggestions: tr_list!("exchange.large.suggestions"),
+                evidence: vec![
+                    Evidence::new("BytesSent", bytes_sent, MetricUnit::Bytes).threshold(LARGE_EXCHANGE_BYTES),
+                    Evidence::new("ChannelNum", channels as f64, MetricUnit::Count),
+                ],
+            });
+        }
+
+        let ms = |d: Option<std::time::Duration>| d.map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0);
+        let compress_ms = ms(m.compress_time);
+        let network_ms = ms(m.network_time);
+        let overall_ms = ms(m.overall_time);
+
+        if let (Some(serialized), Some(compressed)) = (m.serialized_bytes, m.compressed_bytes) {
+            let ratio = serialized as f64 / compressed.max(1) as f64;
+            if compressed >= serialized && network_ms >= SIGNIFICANT_EXCHANGE_TIME_MS && network_ms > overall_ms * 0.5 {
+                hotspots.push(HotSpot {
+                    node_path: node_path.clone(),
+                    node: node.clone(),
+                    severity: HotSeverity::Mild,
+                    issue_type: IssueCode::UncompressedExchange,
+                    confidence: Confidence::High,
+                    description: tr!(
+                        "exchange.uncompressed.description",
+                        volume = volume,
+                        network_secs = network_ms / 1000.0,
+                        overall_secs = overall_ms / 1000.0,
+                    ),
+                    suggestions: tr_list!("exchange.uncompressed.suggestions"),
+                    evidence: vec![
+                        Evidence::new("NetworkTime", network_ms, MetricUnit::Milliseconds).threshold(SIGNIFICANT_EXCHANGE_TIME_MS),
+                        Evidence::new("NetworkTime / OverallTime", network_ms / overall_ms.max(1.0), MetricUnit::Ratio).threshold(0.5),
+                    ],
+                });
+            } else if ratio < LOW_COMPRESSION_RATIO && compress_ms >= SIGNIFICANT_EXCHANGE_TIME_MS && compress_ms > network_ms {
+                hotspots.push(HotSpot {
+                    node_path: node_path.clone(),
+                    node: node.clone(),
+                    severity: HotSeverity::Mild,
+                    issue_type: IssueCode::IneffectiveExchangeCompression,
+                    confidence: Confidence::High,
+                    description: tr!(
+                        "exchange.ineffective_compression.description",
+                        ratio = ratio,
+                        serialized = ValueParser::format_bytes(serialized),
+                        compressed = ValueParser::format_bytes(compressed),
+                        compress_secs = compress_ms / 1000.0,
+                        network_secs = network_ms / 1000.0,
+                    ),
+                    suggestions: tr_list!("exchange.ineffective_compression.suggestions"),
+                    evidence: vec![
+                        Evidence::new("SerializedBytes / CompressedBytes", ratio, MetricUnit::Ratio).threshold(LOW_COMPRESSION_RATIO),
+                        Evidence::new("CompressTime", compress_ms, MetricUnit::Milliseconds).threshold(SIGNIFICANT_EXCHANGE_TIME_MS),
+                    ],
+                });
+            }
+        }
+
+        let serialize_ms = ms(m.serialize_chunk_time) + ms(m.shuffle_hash_time);
+        let operator_ms = exchange.operator.common_metrics.get("OperatorTotalTime")
+            .and_then(|v| ValueParser::parse_time_to_ms(v).ok())
+            .unwrap_or(0.0);
+        if serialize_ms >= SIGNIFICANT_EXCHANGE_TIME_MS && operator_ms > 0.0 && serialize_ms > operator_ms * 0.5 {
+            hotspots.push(HotSpot {
+                node_path: node_path.clone(),
+                node: node.clone(),
+                severity: HotSeverity::Mild,
+                issue_type: IssueCode::ExchangeSerializationCost,
+                confidence: Confidence::High,
+                description: tr!(
+                    "exchange.serialization.description",
+                    serialize_secs = serialize_ms / 1000.0,
+                    percent = serialize_ms / operator_ms * 100.0,
+                    volume = volume,
+                ),
+                suggestions: tr_list!("exchange.serialization.suggestions"),
+                evidence: vec![
+                    Evidence::new("SerializeChunkTime + ShuffleHashTime", serialize_ms, MetricUnit::Milliseconds).threshold(SIGNIFICANT_EXCHANGE_TIME_MS),
+                    Evidence::new("(SerializeChunkTime + ShuffleHashTime) / OperatorTotalTime", serialize_ms / operator_ms, MetricUnit::Ratio).threshold(0.5),
+                ],
+            });
+        }
+
+        if m.bytes_pass_through == Some(0) && Self::shares_backends(profile, exchange) {
+            hotspots.push(HotSpot {
+                node_path,
+                node,
+                severity: HotSeverity::Mild,
+                issue_type: IssueCode::ExchangePassThroughDisabled,
+                confidence: Confidence::Medium,
+                description: tr!("exchange.pass_through.description", volume = volume),
+                suggestions: tr_list!("exchange.pass_through.suggestions"),
+                evidence: vec![
+                    Evidence::new("BytesPassThrough", 0.0, MetricUnit::Bytes),
+                    Evidence::new("BytesSent", bytes_sent, MetricUnit::Bytes).threshold(SIGNIFICANT_EXCHANGE_BYTES),
+                ],
+            });
+        }
+
+        hotspots
+    }
+
+    fn analyze_join_distribution(join: &JoinContext) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+        let (Some(build_rows), Some(probe_rows)) = (join.build_rows, join.probe_rows) else {
+            return hotspots;
+        };
+
+        let bytes_of = |ex: Option<&ExchangeRef>| ex.and_then(|e| e.metrics.bytes_sent).unwrap_or(0) as f64;
+        let build_bytes = bytes_of(join.build_exchange);
+        let probe_bytes = bytes_of(join.probe_exchange);
+        let channels = join.build_exchange.and_then(|e| e.metrics.channel_num).unwrap_or(1).max(1);
+        let node_path = join.build_exchange.or(join.probe_exchange)
+            .map(|e| format!("Fragment{}.HASH_JOIN (plan_node_id={})", e.fragment.id, join.plan_node_id))
+            .unwrap_or_else(|| format!("HASH_JOIN (plan_node_id={})", join.plan_node_id));
+        let node = NodeRef {
+            plan_node_id: join.plan_node_id.parse().ok(),
+            operator_name: Some("HASH_JOIN".to_string()),
+            ..NodeRef::default()
+        };
+        let rows = |metric: &str, rows: u64| Evidence::new(metric, rows as f64, MetricUnit::Count);
+        let bytes = |metric: &str, bytes: f64| Evidence::new(metric, bytes, MetricUnit::Bytes);
+        let numbers = tr!(
+            "join.numbers",
+            build_rows = build_rows,
+            build_bytes = ValueParser::format_bytes(build_bytes as u64),
+            probe_rows = probe_rows,
+            probe_bytes = ValueParser::format_bytes(probe_bytes as u64),
+        );
+
+        match join.distribution_mode.as_str() {
+            "BROADCAST" if build_rows > BROADCAST_MAX_BUILD_ROWS || build_bytes >= SIGNIFICANT_EXCHANGE_BYTES => {
+                hotspots.push(HotSpot {
+                    node_path,
+                    node,
+                    severity: if build_bytes >= LARGE_EXCHANGE_BYTES { HotSeverity::Severe } else { HotSeverity::Moderate },
+                    issue_type: IssueCode::BroadcastJoinTooLarge,
+                    confidence: Confidence::High,
+                    description: tr!("join.broadcast_too_large.description", channels = channels, numbers = numbers),
+                    suggestions: tr_list!("join.broadcast_too_large.suggestions"),
+                    evidence: vec![
+                        rows("BuildRows", build_rows).threshold(BROADCAST_MAX_BUILD_ROWS as f64),
+                        bytes("BuildBytesSent", build_bytes).threshold(SIGNIFICANT_EXCHANGE_BYTES),
+                    ],
+                });
+            }
+            "PARTITIONED" | "SHUFFLE_HASH_BUCKET" => {
+                let broadcast_cost = build_bytes * channels as f64;
+                let shuffle_cost = build_bytes + probe_bytes;
+                if build_rows <= BROADCAST_FRIENDLY_BUILD_ROWS
+                    && probe_bytes >= SIGNIFICANT_EXCHANGE_BYTES
+                    && broadcast_cost < shuffle_cost * 0.5
+                {
+                    hotspots.push(HotSpot {
+                        node_path,
+                        node,
+                        severity: HotSeverity::Moderate,
+                        issue_type: IssueCode::ShuffleJoinShouldBroadcast,
+                        confidence: Confidence::Medium,
+                        description: tr!(
+                            "join.should_broadcast.description",
+                            numbers = numbers,
+                            broadcast_bytes = ValueParser::format_bytes(broadcast_cost as u64),
+                            shuffle_bytes = ValueParser::format_bytes(shuffle_cost as u64),
+                        ),
+                        suggestions: tr_list!("join.should_broadcast.suggestions"),
+                        evidence: vec![
+                            rows("BuildRows", build_rows).threshold(BROADCAST_FRIENDLY_BUILD_ROWS as f64),
+                            bytes("ProbeBytesSent", probe_bytes).threshold(SIGNIFICANT_EXCHANGE_BYTES),
+                            bytes("EstimatedBroadcastBytes", broadcast_cost),
+                            bytes("ShuffleBytes", shuffle_cost),
+                        ],
+                    });
+                } else if build_bytes >= SIGNIFICANT_EXCHANGE_BYTES && probe_bytes >= SIGNIFICANT_EXCHANGE_BYTES {
+                    hotspots.push(HotSpot {
+                        node_path,
+                        node,
+                        severity: HotSeverity::Moderate,
+                        issue_type: IssueCode::ShuffleJoinBothSides,
+                        confidence: Confidence::Medium,
+                        description: tr!("join.shuffle_both_sides.description", numbers = numbers),
+                        suggestions: tr_list!("join.shuffle_both_sides.suggestions"),
+                        evidence: vec![
+                            bytes("BuildBytesSent", build_bytes).threshold(SIGNIFICANT_EXCHANGE_BYTES),
+                            bytes("ProbeBytesSent", probe_bytes).threshold(SIGNIFICANT_EXCHANGE_BYTES),
+                        ],
+                    });
+                }
+            }
+            "BUCKET_SHUFFLE" if build_bytes >= LARGE_EXCHANGE_BYTES => {
+                hotspots.push(HotSpot {
+                    node_path,
+                    node,
+                    severity: HotSeverity::Mild,
+                    issue_type: IssueCode::BucketShuffleJoinLargeBuild,
+                    confidence: Confidence::Medium,
+                    description: tr!("join.bucket_shuffle_large_build.description", numbers = numbers),
+                    suggestions: tr_list!("join.bucket_shuffle_large_build.suggestions"),
+                    evidence: vec![bytes("BuildBytesSent", build_bytes).threshold(LARGE_EXCHANGE_BYTES)],
+                });
+            }
+            _ => {}
+        }
+
+        hotspots
+    }
+
+    /// Pairs each hash join with the exchanges feeding its build and probe pipelines.
+    /// `DestID` of an exchange sink is the plan node id of its receiver.
+    fn find_joins<'a>(profile: &'a Profile, exchanges: &'a [ExchangeRef<'a>]) -> Vec<JoinContext<'a>> {
+        let mut joins: Vec<JoinContext> = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                let Some(source) = pipeline.operators.last() else { continue };
+                if !EXCHANGE_RECEIVERS.contains(&source.name.as_str()) {
+                    continue;
+                }
+                let exchange = exchanges.iter().find(|e| {
+                    e.metrics.dest_id.map(|id| id.to_string()) == source.plan_node_id
+                });
+
+                let side = pipeline.operators.iter().find_map(|op| match op.name.as_str() {
+                    "HASH_JOIN_BUILD" => Some((JoinSide::Build, op)),
+                    "HASH_JOIN_PROBE" => Some((JoinSide::Probe, op)),
+                    _ => None,
+                });
+                let Some((side, join_op)) = side else { continue };
+                let Some(plan_node_id) = join_op.plan_node_id.clone() else { continue };
+
+                let idx = match joins.iter().position(|j| j.plan_node_id == plan_node_id) {
+                    Some(idx) => idx,
+                    None => {
+                        joins.push(Self::join_context(fragment, plan_node_id));
+                        joins.len() - 1
+                    }
+                };
+                match side {
+                    JoinSide::Build => joins[idx].build_exchange = exchange,
+                    JoinSide::Probe => joins[idx].probe_exchange = exchange,
+                }
+            }
+        }
+
+        joins
+    }
+
+    fn join_context<'a>(fragment: &Fragment, plan_node_id: String) -> JoinContext<'a> {
+        let find = |name: &str| fragment.pipelines.iter()
+            .flat_map(|p| p.operators.iter())
+            .find(|op| op.name == name && op.plan_node_id.as_deref() == Some(plan_node_id.as_str()));
+        let rows = |op: Option<&Operator>| op
+            .and_then(|op| op.common_metrics.get("PushRowNum"))
+            .and_then(|v| ValueParser::parse_bytes(v).ok());
+
+        let build = find("HASH_JOIN_BUILD");
+        let probe = find("HASH_JOIN_PROBE");
+        let distribution_mode = build.or(probe)
+            .and_then(|op| op.unique_metrics.get("DistributionMode"))
+            .cloned()
+            .unwrap_or_default();
+
+        JoinContext {
+            distribution_mode,
+            build_rows: rows(build),
+            probe_rows: rows(probe),
+            build_exchange: None,
+            probe_exchange: None,
+            plan_node_id,
+        }
+    }
+
+    /// Whether sender and receiver fragments run on at least one common backend.
+    fn shares_backends(profile: &Profile, exchange: &ExchangeRef) -> bool {
+        let Some(dest_id) = exchange.metrics.dest_id.map(|id| id.to_string()) else {
+            return false;
+        };
+        let senders: HashSet<&String> = exchange.fragment.backend_addresses.iter().collect();
+
+        profile.fragments.iter()
+            .filter(|f| f.id != exchange.fragment.id)
+            .filter(|f| f.pipelines.iter().any(|p| p.operators.last().map(|op| {
+                EXCHANGE_RECEIVERS.contains(&op.name.as_str()) && op.plan_node_id.as_ref() == Some(&dest_id)
+            }).unwrap_or(false)))
+            .any(|f| f.backend_addresses.iter().any(|addr| senders.contains(addr)))
+    }
+
+    fn node_path(exchange: &ExchangeRef) -> String {
+        format!("Fragment{}.Pipeline{}.{}", exchange.fragment.id, exchange.pipeline.id, exchange.operator.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{fragment, load, operator, profile};
+
+    fn placed(id: &str, backend: &str, pipelines: Vec<Vec<Operator>>) -> Fragment {
+        Fragment { backend_addresses: vec![backend.to_string()], ..fragment(id, pipelines) }
+    }
+
+    fn shuffle_join_profile(build_rows: &str, build_bytes: &str, probe_bytes: &str) -> Profile {
+        let join = placed("0", "be1:9060", vec![
+            vec![
+                operator("HASH_JOIN_BUILD", 4, &[("PushRowNum", build_rows)], &[("DistributionMode", "PARTITIONED")]),
+                operator("EXCHANGE_SOURCE", 2, &[], &[]),
+            ],
+            vec![
+                operator("RESULT_SINK", -1, &[], &[]),
+                operator("HASH_JOIN_PROBE", 4, &[("PushRowNum", "500000000")], &[("DistributionMode", "PARTITIONED")]),
+                operator("EXCHANGE_SOURCE", 3, &[], &[]),
+            ],
+        ]);
+        let build = placed("1", "be2:9060", vec![vec![
+            operator("EXCHANGE_SINK", 2, &[], &[("DestID", "2"), ("PartType", "HASH_PARTITIONED"), ("ChannelNum", "8"), ("BytesSent", build_bytes), ("BytesPassThrough", "0.000 B")]),
+            operator("OLAP_SCAN", 0, &[], &[]),
+        ]]);
+        let probe = placed("2", "be3:9060", vec![vec![
+            operator("EXCHANGE_SINK", 3, &[], &[("DestID", "3"), ("PartType", "HASH_PARTITIONED"), ("ChannelNum", "8"), ("BytesSent", probe_bytes), ("BytesPassThrough", "0.000 B")]),
+            operator("OLAP_SCAN", 1, &[], &[]),
+        ]]);
+        profile(vec![join, build, probe])
+    }
+
+    #[test]
+    fn test_small_build_side_recommends_broadcast() {
+        let hotspots = ExchangeAnalyzer::analyze(&shuffle_join_profile("1000", "1.000 MB", "20.000 GB"));
+        let join = hotspots.iter().find(|h| h.issue_type == IssueCode::ShuffleJoinShouldBroadcast).unwrap();
+        assert!(join.node_path.contains("plan_node_id=4"));
+        assert!(join.suggestions.iter().any(|s| s.contains("[broadcast]")));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::LargeExchange));
+        // different backends, pass-through cannot apply
+        assert!(!hotspots.iter().any(|h| h.issue_type == IssueCode::ExchangePassThroughDisabled));
+    }
+
+    #[test]
+    fn test_two_large_sides_recommend_colocate() {
+        let hotspots = ExchangeAnalyzer::analyze(&shuffle_join_profile("400000000", "8.000 GB", "20.000 GB"));
+        let join = hotspots.iter().find(|h| h.issue_type == IssueCode::ShuffleJoinBothSides).unwrap();
+        assert!(join.suggestions.iter().any(|s| s.contains("colocate")));
+        assert!(join.suggestions.iter().any(|s| s.contains("[bucket]")));
+    }
+
+    #[test]
+    fn test_pass_through_on_shared_backend() {
+        let sender = placed("1", "be1:9060", vec![vec![
+            operator("EXCHANGE_SINK", 2, &[], &[("DestID", "2"), ("PartType", "HASH_PARTITIONED"), ("BytesSent", "2.000 GB"), ("BytesPassThrough", "0.000 B")]),
+            operator("OLAP_SCAN", 0, &[], &[]),
+        ]]);
+        let receiver = placed("0", "be1:9060", vec![vec![
+            operator("RESULT_SINK", -1, &[], &[]),
+            operator("EXCHANGE_SOURCE", 2, &[], &[]),
+        ]]);
+        let hotspots = ExchangeAnalyzer::analyze(&profile(vec![receiver, sender]));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::ExchangePassThroughDisabled));
+    }
+
+    #[test]
+    fn test_exchanges_profile1() {
+        // only a few hundred MB cross the network, all of it into aggregations
+        assert!(ExchangeAnalyzer::analyze(&load("profile1.txt")).is_empty());
+    }
+}
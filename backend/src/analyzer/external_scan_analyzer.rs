@@ -0,0 +1,353 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::parser::specialized::ScanStrategy;
+use crate::{tr, tr_list};
+
+/// Splits below this average size are considered small files.
+const SMALL_FILE_BYTES: f64 = 16.0 * 1024.0 * 1024.0;
+/// Fewer splits than this are cheap to open no matter their size.
+const MIN_SMALL_FILE_RANGES: u64 = 1000;
+const SEVERE_SMALL_FILE_RANGES: u64 = 100_000;
+/// Row groups / stripes below this count are not worth pruning.
+const MIN_PRUNABLE_UNITS: u64 = 100;
+/// Share of row groups / stripes skipped below which pruning is considered missing.
+const LOW_PRUNED_RATIO: f64 = 0.05;
+/// Share of read rows discarded by predicates that shows the filter was selective.
+const HIGH_PRED_FILTER_RATIO: f64 = 0.9;
+/// Metadata (metastore / manifest / file listing) time at planning worth reporting.
+const SLOW_METADATA_MS: f64 = 1000.0;
+/// Share of scan time spent building Iceberg delete files worth reporting.
+const HEAVY_DELETE_SHARE: f64 = 0.3;
+const MIN_DELETE_BUILD_MS: f64 = 1000.0;
+
+/// Planner timer name fragments of external catalog metadata access.
+const METADATA_TIMER_PREFIXES: &[&str] = &["HMS.", "HIVE.", "ICEBERG.", "HUDI.", "PAIMON.", "DELTA_LAKE.", "DELTALAKE.", "GLUE."];
+const METADATA_TIMER_KEYWORDS: &[&str] = &["getRemoteFiles", "RemoteFile", "ListPartition", "LIST_FS_PARTITIONS", "planFiles", "getTable"];
+
+/// Catalog-aware analysis of scans over external catalogs (Hive, Iceberg, Hudi, Paimon, Delta Lake):
+/// small files, missing row group / stripe pruning, slow metadata listing and Iceberg delete-file merging.
+pub struct ExternalScanAnalyzer;
+
+impl ExternalScanAnalyzer {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                for operator in pipeline.operators.iter().filter(|op| op.name == "CONNECTOR_SCAN") {
+                    let scan = ScanStrategy::connector_from_metrics(&operator.unique_metrics);
+                    let Some(external) = scan.external.as_ref() else {
+                        continue;
+                    };
+                    let node_path = format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, operator.name);
+                    let node = NodeRef::operator(&fragment.id, &pipeline.id, operator);
+                    hotspots.extend(Self::analyze_small_files(&node_path, &node, &scan, external));
+                    hotspots.extend(Self::analyze_pruning(&node_path, &node, &scan, external));
+                    hotspots.extend(Self::analyze_delete_files(&node_path, &node, &scan, external));
+                }
+            }
+        }
+
+        hotspots.extend(Self::analyze_metadata(&profile.planner));
+        hotspots
+    }
+
+    fn analyze_small_files(node_path: &str, node: &NodeRef, scan: &ConnectorScanSpecializedMetrics, external: &ExternalScanMetrics) -> Option<HotSpot> {
+        let ranges = external.scan_ranges?;
+        if ranges < MIN_SMALL_FILE_RANGES {
+            return None;
+        }
+        let total_bytes = external.scan_ranges_size.or(scan.bytes_read)?;
+        let avg_bytes = total_bytes as f64 / ranges as f64;
+        if avg_bytes >= SMALL_FILE_BYTES {
+            return None;
+        }
+
+        let secs = |d: Option<std::time::Duration>| d.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+        let open_cost = secs(external.open_file_time) + secs(external.reader_init_time);
+        let open_share = scan.scan_time.map(|t| t.as_secs_f64()).filter(|t| *t > 0.0).map(|t| open_cost / t);
+
+        let mut description = tr!(
+            "external_scan.small_files.description",
+            catalog = Self::catalog_name(external.catalog),
+            table = Self::table_name(scan),
+            ranges = ranges,
+            avg_size = ValueParser::format_bytes(avg_bytes as u64),
+        );
+        if let Some(share) = open_share {
+            description.push_str(&tr!("external_scan.small_files.open_share", percent = share * 100.0));
+        }
+
+        let mut suggestions = vec![Self::compaction_suggestion(external.catalog)];
+        suggestions.extend(tr_list!("external_scan.small_files.suggestions"));
+
+        Some(HotSpot {
+            node_path: node_path.to_string(),
+            node: node.clone(),
+            severity: if ranges >= SEVERE_SMALL_FILE_RANGES || open_share.map(|s| s >= 0.5).unwrap_or(false) {
+                HotSeverity::Severe
+            } else {
+                HotSeverity::Moderate
+            },
+            issue_type: IssueCode::SmallFileExplosion,
+            confidence: Confidence::High,
+            description,
+            suggestions,
+            evidence: [
+                Some(Evidence::new("ScanRanges", ranges as f64, MetricUnit::Count).threshold(MIN_SMALL_FILE_RANGES as f64)),
+                Some(Evidence::new("ScanRangesSize / ScanRanges", avg_bytes, MetricUnit::Bytes).threshold(SMALL_FILE_BYTES)),
+                open_share.map(|share| Evidence::new("(OpenFile + ReaderInit) / ScanTime", share, MetricUnit::Ratio)),
+            ].into_iter().flatten().collect(),
+        })
+    }
+
+    fn analyze_pruning(node_path: &str, node: &NodeRef, scan: &ConnectorScanSpecializedMetrics, external: &ExternalScanMetrics) -> Option<HotSpot> {
+        let (unit, total, filtered) = match (external.row_groups_total, external.stripes_total) {
+            (Some(total), _) => ("row group", total, external.row_groups_filtered.unwrap_or(0)),
+            (None, Some(total)) => ("stripe", total, external.stripes_filtered.unwrap_or(0)),
+            _ => return None,
+        };
+        if total < MIN_PRUNABLE_UNITS || filtered as f64 / total as f64 >= LOW_PRUNED_RATIO {
+            return None;
+        }
+
+        let (Some(raw), Some(returned)) = (scan.raw_rows_read, scan.rows_read) else {
+            return None;
+        };
+        if raw == 0 {
+            return None;
+        }
+        let pred_ratio = raw.saturating_sub(returned) as f64 / raw as f64;
+        if pred_ratio < HIGH_PRED_FILTER_RATIO {
+            return None;
+        }
+
+        let mut description = tr!(
+            "external_scan.row_group_pruning.description",
+            catalog = Self::catalog_name(external.catalog),
+            table = Self::table_name(scan),
+            total = total,
+            unit = unit,
+            filtered = filtered,
+            raw = raw,
+            percent = pred_ratio * 100.0,
+        );
+        if let Some(page_rows) = external.page_index_filtered_rows {
+            description.push_str(&tr!("external_scan.row_group_pruning.page_index", rows = page_rows));
+        }
+
+        Some(HotSpot {
+            node_path: node_path.to_string(),
+            node: node.clone(),
+            severity: if pred_ratio > 0.99 { HotSeverity::Severe } else { HotSeverity::Moderate },
+            issue_type: IssueCode::MissedRowGroupPruning,
+            confidence: Confidence::High,
+            description,
+            suggestions: tr_list!("external_scan.row_group_pruning.suggestions", unit = unit),
+            evidence: vec![
+                Evidence::new(format!("{}s filtered / total", unit), filtered as f64 / total as f64, MetricUnit::Ratio).threshold(LOW_PRUNED_RATIO),
+                Evidence::new("(RawRowsRead - RowsRead) / RawRowsRead", pred_ratio, MetricUnit::Ratio).threshold(HIGH_PRED_FILTER_RATIO),
+            ],
+        })
+    }
+
+    fn analyze_delete_files(node_path: &str, node: &NodeRef, scan: &ConnectorScanSpecializedMetrics, external: &ExternalScanMetrics) -> Option<HotSpot> {
+        if external.catalog != ExternalCatalog::Iceberg {
+            return None;
+        }
+        let ms = |d: Option<std::time::Duration>| d.map(|d| d.as_secs_f64() * 1000.0).unwrap_or(0.0);
+        let build_ms = ms(external.delete_file_build_time) + ms(external.delete_file_build_filter_time);
+        let scan_ms = ms(scan.scan_time);
+        if build_ms < MIN_DELETE_BUILD_MS || scan_ms <= 0.0 {
+            return None;
+        }
+        let share = build_ms / scan_ms;
+        if share < HEAVY_DELETE_SHARE {
+            return None;
+        }
+
+        Some(HotSpot {
+            node_path: node_path.to_string(),
+            node: node.clone(),
+            severity: if share >= 0.6 { HotSeverity::Severe } else { HotSeverity::Moderate },
+            issue_type: IssueCode::HeavyIcebergDeleteMerge,
+            confidence: Confidence::High,
+            description: tr!(
+                "external_scan.iceberg_deletes.description",
+                table = Self::table_name(scan),
+                build_secs = build_ms / 1000.0,
+                percent = share * 100.0,
+                delete_files = external.delete_files_per_scan.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string()),
+            ),
+            suggestions: tr_list!("external_scan.iceberg_deletes.suggestions"),
+            evidence: vec![
+                Evidence::new("DeleteFileBuildTime + DeleteFileBuildFilterTime", build_ms, MetricUnit::Milliseconds).threshold(MIN_DELETE_BUILD_MS),
+                Evidence::new("DeleteFileBuild / ScanTime", share, MetricUnit::Ratio).threshold(HEAVY_DELETE_SHARE),
+            ],
+        })
+    }
+
+    fn analyze_metadata(planner: &PlannerInfo) -> Option<HotSpot> {
+        // only count the outermost metadata timer of each subtree, nested ones are part of it
+        let mut ancestors: Vec<(usize, bool)> = Vec::new();
+        let mut timers: Vec<&PlannerTimer> = Vec::new();
+        for timer in &planner.timers {
+            while ancestors.last().map(|(depth, _)| *depth >= timer.depth).unwrap_or(false) {
+                ancestors.pop();
+            }
+            let covered = ancestors.iter().any(|(_, is_metadata)| *is_metadata);
+            let is_metadata = Self::is_metadata_timer(&timer.name);
+            if is_metadata && !covered {
+                timers.push(timer);
+            }
+            ancestors.push((timer.depth, is_metadata));
+        }
+
+        let total_ms: f64 = timers.iter().map(|t| t.time_ms).sum();
+        if total_ms < SLOW_METADATA_MS {
+            return None;
+        }
+        timers.sort_by(|a, b| b.time_ms.partial_cmp(&a.time_ms).unwrap_or(std::cmp::Ordering::Equal));
+        let top = timers.iter().take(3)
+            .map(|t| tr!("external_scan.metadata.timer", name = t.name, secs = t.time_ms / 1000.0, count = t.count))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Some(HotSpot {
+            node_path: "Planner".to_string(),
+            node: NodeRef::default(),
+            severity: match total_ms {
+                ms if ms >= 10_000.0 => HotSeverity::Severe,
+                ms if ms >= 3_000.0 => HotSeverity::Moderate,
+                _ => HotSeverity::Mild,
+            },
+            issue_type: IssueCode::SlowMetadataListing,
+            confidence: Confidence::High,
+            description: tr!("external_scan.metadata.description", secs = total_ms / 1000.0, timers = top),
+            suggestions: tr_list!("external_scan.metadata.suggestions"),
+            evidence: vec![Evidence::new("MetadataTime", total_ms, MetricUnit::Milliseconds).threshold(SLOW_METADATA_MS)],
+        })
+    }
+
+    fn is_metadata_timer(name: &str) -> bool {
+        let upper = name.to_uppercase();
+        METADATA_TIMER_PREFIXES.iter().any(|p| upper.starts_with(p))
+            || METADATA_TIMER_KEYWORDS.iter().any(|k| name.contains(k))
+    }
+
+    fn compaction_suggestion(catalog: ExternalCatalog) -> String {
+        match catalog {
+            ExternalCatalog::Iceberg => tr!("external_scan.compaction.iceberg"),
+            ExternalCatalog::Hudi => tr!("external_scan.compaction.hudi"),
+            ExternalCatalog::Paimon => tr!("external_scan.compaction.paimon"),
+            ExternalCatalog::DeltaLake => tr!("external_scan.compaction.delta_lake"),
+            _ => tr!("external_scan.compaction.other"),
+        }
+    }
+
+    fn catalog_name(catalog: ExternalCatalog) -> &'static str {
+        match catalog {
+            ExternalCatalog::Hive => "Hive",
+            ExternalCatalog::Iceberg => "Iceberg",
+            ExternalCatalog::Hudi => "Hudi",
+            ExternalCatalog::Paimon => "Paimon",
+            ExternalCatalog::DeltaLake => "Delta Lake",
+            ExternalCatalog::Jdbc => "JDBC",
+            ExternalCatalog::Elasticsearch => "Elasticsearch",
+            ExternalCatalog::File => "File",
+        }
+    }
+
+    fn table_name(scan: &ConnectorScanSpecializedMetrics) -> &str {
+        if scan.table.is_empty() { "<table>" } else { scan.table.as_str() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, metrics};
+    use std::collections::HashMap;
+
+    fn scan(unique: &[(&str, &str)]) -> ConnectorScanSpecializedMetrics {
+        ScanStrategy::connector_from_metrics(&metrics(unique))
+    }
+
+    #[test]
+    fn test_small_files_and_missed_pruning() {
+        let scan = scan(&[
+            ("DataSourceType", "HiveDataSource"),
+            ("Table", "events"),
+            ("ScanTime", "10s"),
+            ("OpenFile", "4s"),
+            ("ReaderInit", "2s"),
+            ("ScanRanges", "20.000K (20000)"),
+            ("ScanRangesSize", "10.000 GB"),
+            ("RawRowsRead", "100000000"),
+            ("RowsRead", "500000"),
+            ("TotalRowGroups", "20000"),
+            ("FilteredRowGroups", "0"),
+        ]);
+        let external = scan.external.as_ref().unwrap();
+        assert_eq!(external.catalog, ExternalCatalog::Hive);
+
+        let small = ExternalScanAnalyzer::analyze_small_files("scan", &NodeRef::default(), &scan, external).unwrap();
+        assert_eq!(small.severity, HotSeverity::Severe);
+        assert!(small.suggestions[0].contains("INSERT OVERWRITE"));
+
+        let pruning = ExternalScanAnalyzer::analyze_pruning("scan", &NodeRef::default(), &scan, external).unwrap();
+        assert_eq!(pruning.issue_type, IssueCode::MissedRowGroupPruning);
+        assert_eq!(pruning.severity, HotSeverity::Severe);
+    }
+
+    #[test]
+    fn test_heavy_iceberg_delete_merge() {
+        let scan = scan(&[
+            ("DataSourceType", "HiveDataSource"),
+            ("ScanTime", "5s"),
+            ("DeleteFileBuildTime", "3s"),
+            ("DeleteFilesPerScan", "24"),
+        ]);
+        let external = scan.external.as_ref().unwrap();
+        assert_eq!(external.catalog, ExternalCatalog::Iceberg);
+        let hotspot = ExternalScanAnalyzer::analyze_delete_files("scan", &NodeRef::default(), &scan, external).unwrap();
+        assert_eq!(hotspot.severity, HotSeverity::Severe);
+        assert!(ExternalScanAnalyzer::analyze_small_files("scan", &NodeRef::default(), &scan, external).is_none());
+    }
+
+    #[test]
+    fn test_slow_metadata_listing() {
+        let timer = |name: &str, depth: usize, time_ms: f64| PlannerTimer { name: name.to_string(), depth, count: 1, time_ms };
+        let planner = PlannerInfo {
+            details: HashMap::new(),
+            timers: vec![
+                timer("Total", 0, 9000.0),
+                timer("Analyzer", 1, 500.0),
+                timer("HMS.getTable", 2, 400.0),
+                timer("Optimizer", 1, 8000.0),
+                timer("HIVE.getRemoteFiles", 2, 7000.0),
+                timer("HMS.PARTITIONS.LIST_FS_PARTITIONS", 3, 6500.0),
+            ],
+        };
+        let hotspot = ExternalScanAnalyzer::analyze_metadata(&planner).unwrap();
+        // the nested partition listing is part of getRemoteFiles
+        assert!(hotspot.description.contains("7.4s"));
+        assert_eq!(hotspot.severity, HotSeverity::Moderate);
+
+        let planner = PlannerInfo { details: HashMap::new(), timers: vec![timer("Total", 0, 9000.0)] };
+        assert!(ExternalScanAnalyzer::analyze_metadata(&planner).is_none());
+    }
+
+    #[test]
+    fn test_lake_table_is_not_external_profile1() {
+        // a shared-data table read through CONNECTOR_SCAN (LakeDataSource)
+        let profile = load("profile1.txt");
+        let scan = profile.fragments.iter()
+            .flat_map(|f| &f.pipelines)
+            .flat_map(|p| &p.operators)
+            .find(|op| op.name == "CONNECTOR_SCAN")
+            .unwrap();
+        assert!(ScanStrategy::connector_from_metrics(&scan.unique_metrics).external.is_none());
+        assert!(ExternalScanAnalyzer::analyze(&profile).is_empty());
+    }
+}
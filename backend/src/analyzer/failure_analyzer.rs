@@ -0,0 +1,271 @@
+use crate::constants::starrocks::DEFAULT_QUERY_TIMEOUT_SECS;
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::{tr, tr_list};
+
+/// Share of `query_timeout` from which an elapsed time counts as having hit it.
+const TIMEOUT_REACHED_SHARE: f64 = 0.95;
+
+/// Reason keywords per failure kind, checked in order and case-insensitively. Keywords match
+/// whole words only, so "cast" does not fire on "broadcast".
+const FAILURE_PATTERNS: &[(FailureKind, &[&str])] = &[
+    (FailureKind::MemoryLimitExceeded, &["exceed limit", "mem_limit", "memory limit", "mem usage", "memory exceed", "allocate memory"]),
+    (FailureKind::Timeout, &["timeout", "timed out", "time limit"]),
+    (FailureKind::RpcFailure, &["rpc", "brpc", "thrift", "connection refused", "connection reset", "host is down", "backend not found", "node not alive"]),
+    // a cancel quoting the statement it interrupted may mention casts or invalid values
+    (FailureKind::UserCancelled, &["cancelled by user", "user cancel", "killed", "kill query"]),
+    (FailureKind::DataQuality, &["data quality", "filtered rows", "invalid", "overflow", "out of range", "cast"]),
+];
+
+/// Diagnosis of failed and cancelled queries from the `Reason:` line: what kind of failure it
+/// was and which operator is the likely culprit, the largest memory user or else the longest-running
+/// one. Profiles carry no driver state, so the latter is not necessarily what was running when the
+/// query stopped.
+pub struct FailureAnalyzer;
+
+impl FailureAnalyzer {
+    pub fn is_failed(profile: &Profile) -> bool {
+        matches!(profile.summary.query_state.as_str(), "Error" | "Failed" | "Cancelled" | "Canceled")
+    }
+
+    pub fn diagnose(profile: &Profile) -> Option<FailureDiagnosis> {
+        if !Self::is_failed(profile) {
+            return None;
+        }
+        let summary = &profile.summary;
+        let reason = summary.failure_reason.clone().unwrap_or_default();
+        let query_timeout_secs = summary.variables.get("query_timeout")
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_QUERY_TIMEOUT_SECS);
+        let elapsed_ms = summary.total_time_ms;
+
+        let kind = Self::classify(&reason, &summary.query_state, elapsed_ms, query_timeout_secs);
+        let suspect = if kind == FailureKind::MemoryLimitExceeded {
+            Self::memory_suspect(profile)
+        } else {
+            Self::longest_running_suspect(profile)
+        };
+        let explanation = Self::explain(kind, &reason, elapsed_ms, query_timeout_secs, suspect.as_ref());
+        let suggestions = Self::suggestions(kind, profile, elapsed_ms, suspect.as_ref());
+
+        Some(FailureDiagnosis {
+            query_state: summary.query_state.clone(),
+            reason,
+            kind,
+            explanation,
+            elapsed_ms,
+            query_timeout_secs,
+            suspect,
+            suggestions,
+        })
+    }
+
+    fn classify(reason: &str, state: &str, elapsed_ms: Option<f64>, query_timeout_secs: u64) -> FailureKind {
+        let lower = reason.to_lowercase();
+        if let Some((kind, _)) = FAILURE_PATTERNS.iter().find(|(_, keywords)| keywords.iter().any(|k| Self::contains_word(&lower, k))) {
+            return *kind;
+        }
+        // an unexplained stop right at query_timeout is the timeout itself
+        if elapsed_ms.is_some_and(|ms| ms >= query_timeout_secs as f64 * 1000.0 * TIMEOUT_REACHED_SHARE) {
+            return FailureKind::Timeout;
+        }
+        if state.starts_with("Cancel") && lower.is_empty() {
+            return FailureKind::UserCancelled;
+        }
+        FailureKind::Unknown
+    }
+
+    /// Whether `keyword` occurs in `text` without a letter or digit directly before or after it.
+    fn contains_word(text: &str, keyword: &str) -> bool {
+        text.match_indices(keyword).any(|(start, _)| {
+            let before = text[..start].chars().next_back();
+            let after = text[start + keyword.len()..].chars().next();
+            !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+        })
+    }
+
+    fn explain(kind: FailureKind, reason: &str, elapsed_ms: Option<f64>, query_timeout_secs: u64, suspect: Option<&FailureSuspect>) -> String {
+        let elapsed = elapsed_ms.map(|ms| format!("{:.1}s", ms / 1000.0)).unwrap_or_else(|| "?".to_string());
+        let mut explanation = match kind {
+            FailureKind::MemoryLimitExceeded => tr!("failure.memory_limit.explanation", elapsed = elapsed),
+            FailureKind::Timeout => tr!("failure.timeout.explanation", elapsed = elapsed, timeout_secs = query_timeout_secs),
+            FailureKind::RpcFailure => tr!("failure.rpc.explanation", elapsed = elapsed),
+            FailureKind::DataQuality => tr!("failure.data_quality.explanation", elapsed = elapsed),
+            FailureKind::UserCancelled => tr!("failure.cancelled.explanation", elapsed = elapsed),
+            FailureKind::Unknown => tr!("failure.unknown.explanation", elapsed = elapsed),
+        };
+        if !reason.is_empty() {
+            explanation.push_str(&format!(": {}", reason));
+        }
+        if let Some(suspect) = suspect {
+            let key = if kind == FailureKind::MemoryLimitExceeded { "failure.suspect.memory" } else { "failure.suspect.longest_running" };
+            explanation.push_str(&tr!(
+                key,
+                fragment = suspect.fragment_id,
+                pipeline = suspect.pipeline_id,
+                operator = suspect.operator_name,
+            ));
+            if let Some(bytes) = suspect.peak_memory_bytes {
+                explanation.push_str(&format!(" ({})", ValueParser::format_bytes(bytes)));
+            }
+        }
+        explanation
+    }
+
+    fn suggestions(kind: FailureKind, profile: &Profile, elapsed_ms: Option<f64>, suspect: Option<&FailureSuspect>) -> Vec<String> {
+        match kind {
+            FailureKind::MemoryLimitExceeded => {
+                let mut suggestions = tr_list!("failure.memory_limit.suggestions");
+                match suspect.map(|s| s.operator_name.as_str()) {
+                    Some(name) if name.contains("HASH_JOIN") => suggestions.push(tr!("failure.memory_limit.join")),
+                    Some(name) if name.contains("AGGREGATE") => suggestions.push(tr!("failure.memory_limit.aggregate")),
+                    _ => {}
+                }
+                suggestions.push(tr!("failure.memory_limit.lower_dop"));
+                suggestions
+            }
+            FailureKind::Timeout => {
+                let needed_secs = elapsed_ms.map(|ms| (ms / 1000.0 * 2.0).ceil() as u64).unwrap_or(DEFAULT_QUERY_TIMEOUT_SECS * 2);
+                let mut suggestions = vec![tr!("failure.timeout.raise", secs = needed_secs)];
+                if profile.summary.sql_statement.trim_start().to_uppercase().starts_with("INSERT") {
+                    suggestions.push(tr!("failure.timeout.insert", secs = needed_secs));
+                }
+                suggestions.push(tr!("failure.timeout.tree"));
+                suggestions
+            }
+            FailureKind::RpcFailure => tr_list!("failure.rpc.suggestions"),
+            FailureKind::DataQuality => tr_list!("failure.data_quality.suggestions"),
+            FailureKind::UserCancelled => tr_list!("failure.cancelled.suggestions"),
+            FailureKind::Unknown => tr_list!("failure.unknown.suggestions", query_id = profile.summary.query_id),
+        }
+    }
+
+    fn memory_suspect(profile: &Profile) -> Option<FailureSuspect> {
+        let mut best: Option<FailureSuspect> = None;
+        for fragment in &profile.fragments {
+            let fragment_peak = Self::fragment_peak_memory(fragment);
+            for pipeline in &fragment.pipelines {
+                for operator in &pipeline.operators {
+                    let peak = operator.unique_metrics.iter()
+                        .filter(|(key, _)| key.contains("Memory") && !key.starts_with("__MIN_OF_"))
+                        .filter_map(|(_, v)| ValueParser::parse_bytes(v).ok())
+                        .max();
+                    let Some(peak) = peak.filter(|p| *p > 0) else {
+                        continue;
+                    };
+                    if best.as_ref().is_none_or(|b| b.peak_memory_bytes < Some(peak)) {
+                        best = Some(Self::suspect(fragment, pipeline, operator, Some(peak), fragment_peak));
+                    }
+                }
+            }
+        }
+        // operators without memory counters: fall back to the fragment with the highest instance peak
+        best.or_else(|| {
+            let fragment = profile.fragments.iter().max_by_key(|f| Self::fragment_peak_memory(f).unwrap_or(0))?;
+            let pipeline = fragment.pipelines.first()?;
+            let operator = pipeline.operators.first()?;
+            Some(Self::suspect(fragment, pipeline, operator, None, Self::fragment_peak_memory(fragment)))
+        })
+    }
+
+    fn longest_running_suspect(profile: &Profile) -> Option<FailureSuspect> {
+        profile.fragments.iter()
+            .flat_map(|f| f.pipelines.iter().map(move |p| (f, p)))
+            .flat_map(|(f, p)| p.operators.iter().map(move |op| (f, p, op)))
+            .filter_map(|(f, p, op)| {
+                let time = op.common_metrics.get("__MAX_OF_OperatorTotalTime")
+                    .or_else(|| op.common_metrics.get("OperatorTotalTime"))
+                    .and_then(|v| ValueParser::parse_time_to_ms(v).ok())?;
+                Some((f, p, op, time))
+            })
+            .max_by(|a, b| a.3.total_cmp(&b.3))
+            .map(|(f, p, op, time)| {
+                let mut suspect = Self::suspect(f, p, op, None, Self::fragment_peak_memory(f));
+                suspect.operator_time_ms = Some(time);
+                suspect
+            })
+    }
+
+    fn suspect(fragment: &Fragment, pipeline: &Pipeline, operator: &Operator, peak: Option<u64>, fragment_peak: Option<u64>) -> FailureSuspect {
+        FailureSuspect {
+            fragment_id: fragment.id.clone(),
+            pipeline_id: pipeline.id.clone(),
+            operator_name: operator.name.clone(),
+            plan_node_id: operator.plan_node_id.as_deref().and_then(|id| id.parse().ok()),
+            peak_memory_bytes: peak,
+            operator_time_ms: None,
+            fragment_peak_memory_bytes: fragment_peak,
+        }
+    }
+
+    fn fragment_peak_memory(fragment: &Fragment) -> Option<u64> {
+        fragment.metrics.get("__MAX_OF_InstancePeakMemoryUsage")
+            .or_else(|| fragment.metrics.get("InstancePeakMemoryUsage"))
+            .and_then(|v| ValueParser::parse_bytes(v).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, metrics, operator, pipeline, profile, with_pipelines};
+
+    fn failed_profile(state: &str, reason: Option<&str>, total_ms: f64) -> Profile {
+        let mut profile = profile(vec![with_pipelines("2", vec![pipeline("1", &[], vec![
+            operator("HASH_JOIN_BUILD", 4, &[("OperatorTotalTime", "3s")], &[("HashTableMemoryUsage", "12.500 GB")]),
+            operator("EXCHANGE_SOURCE", 4, &[("OperatorTotalTime", "40s")], &[("PeakBufferMemoryBytes", "64.000 MB")]),
+        ])])]);
+        profile.summary = ProfileSummary {
+            query_state: state.to_string(),
+            failure_reason: reason.map(str::to_string),
+            total_time_ms: Some(total_ms),
+            variables: metrics(&[("query_timeout", "600")]),
+            ..ProfileSummary::default()
+        };
+        profile
+    }
+
+    #[test]
+    fn test_memory_limit_exceeded() {
+        let profile = failed_profile("Error", Some("Memory of query_pool exceed limit. Used: 13421772800"), 75_000.0);
+        let diagnosis = FailureAnalyzer::diagnose(&profile).unwrap();
+        assert_eq!(diagnosis.kind, FailureKind::MemoryLimitExceeded);
+        let suspect = diagnosis.suspect.unwrap();
+        assert_eq!(suspect.operator_name, "HASH_JOIN_BUILD");
+        assert_eq!(suspect.plan_node_id, Some(4));
+        assert!(diagnosis.suggestions.iter().any(|s| s.contains("build侧")));
+    }
+
+    #[test]
+    fn test_timeout_and_cancel() {
+        // no reason text, but the query stopped at its 600s query_timeout
+        let diagnosis = FailureAnalyzer::diagnose(&failed_profile("Error", None, 600_000.0)).unwrap();
+        assert_eq!(diagnosis.kind, FailureKind::Timeout);
+        assert_eq!(diagnosis.suspect.unwrap().operator_name, "EXCHANGE_SOURCE");
+        assert!(diagnosis.explanation.contains("运行时间最长的算子是 Fragment2 Pipeline1 的 EXCHANGE_SOURCE"));
+        assert!(diagnosis.suggestions[0].ends_with("query_timeout = 1200"));
+
+        let diagnosis = FailureAnalyzer::diagnose(&failed_profile("Cancelled", None, 5_000.0)).unwrap();
+        assert_eq!(diagnosis.kind, FailureKind::UserCancelled);
+
+        assert!(FailureAnalyzer::diagnose(&failed_profile("Finished", None, 5_000.0)).is_none());
+    }
+
+    #[test]
+    fn test_reason_keywords_match_whole_words() {
+        let kind = |reason| FailureAnalyzer::diagnose(&failed_profile("Error", Some(reason), 5_000.0)).unwrap().kind;
+        assert_eq!(kind("Cast to DECIMAL overflow in column price"), FailureKind::DataQuality);
+        // "broadcast" contains "cast", "invalidated" contains "invalid"
+        assert_eq!(kind("Fragment instance invalidated during broadcast"), FailureKind::Unknown);
+        assert_eq!(kind("brpc call failed: host is unreachable"), FailureKind::RpcFailure);
+        assert_eq!(kind("Query killed: kill query issued while casting an invalid value"), FailureKind::UserCancelled);
+    }
+
+    #[test]
+    fn test_no_failure_profile4() {
+        // still running when the profile was taken
+        let profile = load("profile4.txt");
+        assert!(!FailureAnalyzer::is_failed(&profile));
+        assert!(FailureAnalyzer::diagnose(&profile).is_none());
+    }
+}
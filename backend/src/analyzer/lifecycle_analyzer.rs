@@ -0,0 +1,228 @@
+use crate::models::*;
+use crate::{tr, tr_list};
+
+/// Time spent in the FE query queue (`Pending`) from which waiting is worth reporting.
+const QUEUE_WAIT_MS: f64 = 1000.0;
+const LONG_QUEUE_WAIT_MS: f64 = 10_000.0;
+/// Deploying fragment instances normally takes tens of milliseconds.
+const SLOW_DEPLOY_MS: f64 = 1000.0;
+const VERY_SLOW_DEPLOY_MS: f64 = 5000.0;
+/// Result delivery is only a problem when it is both long and a large share of the execution.
+const SLOW_DELIVERY_MS: f64 = 1000.0;
+const SLOW_DELIVERY_SHARE: f64 = 0.3;
+const DOMINANT_DELIVERY_SHARE: f64 = 0.5;
+
+/// Query lifecycle: plan, queue, prepare, deploy, execute, deliver results and collect the
+/// profile, reconciled against the summary `Total`.
+pub struct LifecycleAnalyzer;
+
+impl LifecycleAnalyzer {
+    pub fn report(profile: &Profile) -> Option<QueryLifecycle> {
+        let summary = &profile.summary;
+        let total_ms = summary.total_time_ms?;
+        let timer = |name: &str| Self::planner_timer(&profile.planner, name);
+
+        let wall_ms = summary.query_execution_wall_time_ms.unwrap_or(0.0);
+        let deliver_ms = summary.result_deliver_time_ms.unwrap_or(0.0).min(wall_ms);
+        let durations = [
+            ("Plan", timer("Parser") + timer("Total")),
+            ("Queue", timer("Pending")),
+            ("Prepare", timer("Prepare")),
+            ("Deploy", timer("Deploy")),
+            ("Execute", wall_ms - deliver_ms),
+            ("ResultDeliver", deliver_ms),
+            ("CollectProfile", summary.collect_profile_time_ms.unwrap_or(0.0)),
+        ];
+
+        let accounted: f64 = durations.iter().map(|(_, ms)| ms).sum();
+        let phases = durations.iter()
+            .map(|(name, ms)| LifecyclePhase {
+                name: name.to_string(),
+                time_ms: *ms,
+                percentage: if total_ms > 0.0 { ms / total_ms * 100.0 } else { 0.0 },
+            })
+            .collect();
+
+        Some(QueryLifecycle {
+            total_ms,
+            phases,
+            unaccounted_ms: total_ms - accounted,
+            peak_schedule_ms: summary.query_peak_schedule_time_ms,
+            running: summary.query_state == "Running",
+        })
+    }
+
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let Some(lifecycle) = Self::report(profile) else {
+            return Vec::new();
+        };
+        let phase = |name: &str| lifecycle.phases.iter().find(|p| p.name == name).map(|p| p.time_ms).unwrap_or(0.0);
+
+        let mut hotspots = Vec::new();
+        hotspots.extend(Self::analyze_queue(phase("Queue"), &lifecycle, profile));
+        hotspots.extend(Self::analyze_deploy(phase("Deploy"), profile));
+        hotspots.extend(Self::analyze_delivery(phase("ResultDeliver"), phase("Execute"), &lifecycle, profile));
+        hotspots
+    }
+
+    fn analyze_queue(queue_ms: f64, lifecycle: &QueryLifecycle, profile: &Profile) -> Option<HotSpot> {
+        if queue_ms < QUEUE_WAIT_MS {
+            return None;
+        }
+        let resource_group = profile.summary.variables.get("resource_group").map(String::as_str).unwrap_or("default_wg");
+
+        Some(HotSpot {
+            node_path: "Planner.Pending".to_string(),
+            node: NodeRef::default(),
+            severity: if queue_ms >= LONG_QUEUE_WAIT_MS { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::QueryQueueWait,
+            confidence: Confidence::High,
+            description: tr!(
+                "lifecycle.queue.description",
+                secs = queue_ms / 1000.0,
+                percent = queue_ms / lifecycle.total_ms.max(1.0) * 100.0,
+            ),
+            suggestions: tr_list!("lifecycle.queue.suggestions", resource_group = resource_group),
+            evidence: vec![Evidence::new("Pending", queue_ms, MetricUnit::Milliseconds).threshold(QUEUE_WAIT_MS)],
+        })
+    }
+
+    fn analyze_deploy(deploy_ms: f64, profile: &Profile) -> Option<HotSpot> {
+        if deploy_ms < SLOW_DEPLOY_MS {
+            return None;
+        }
+        let wait_ms = Self::planner_timer(&profile.planner, "DeployWaitTime");
+        let instances: usize = profile.fragments.iter().map(|f| f.instance_ids.len()).sum();
+        let data_size = profile.planner.details.get("DeployDataSize").cloned().unwrap_or_else(|| "?".to_string());
+
+        let mut suggestions = Vec::new();
+        if wait_ms >= deploy_ms * 0.5 {
+            suggestions.push(tr!("lifecycle.deploy.backend_wait"));
+        }
+        suggestions.push(tr!("lifecycle.deploy.instances", instances = instances, bytes = data_size));
+
+        Some(HotSpot {
+            node_path: "Planner.Deploy".to_string(),
+            node: NodeRef::default(),
+            severity: if deploy_ms >= VERY_SLOW_DEPLOY_MS { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::SlowDeploy,
+            confidence: Confidence::High,
+            description: tr!("lifecycle.deploy.description", secs = deploy_ms / 1000.0, wait_secs = wait_ms / 1000.0),
+            suggestions,
+            evidence: vec![
+                Evidence::new("Deploy", deploy_ms, MetricUnit::Milliseconds).threshold(SLOW_DEPLOY_MS),
+                Evidence::new("DeployWaitTime", wait_ms, MetricUnit::Milliseconds),
+            ],
+        })
+    }
+
+    fn analyze_delivery(deliver_ms: f64, execute_ms: f64, lifecycle: &QueryLifecycle, profile: &Profile) -> Option<HotSpot> {
+        let wall_ms = deliver_ms + execute_ms;
+        if deliver_ms < SLOW_DELIVERY_MS || deliver_ms < wall_ms * SLOW_DELIVERY_SHARE {
+            return None;
+        }
+        let share = deliver_ms / wall_ms;
+
+        let mut description = tr!(
+            "lifecycle.delivery.description",
+            secs = deliver_ms / 1000.0,
+            percent = share * 100.0,
+            execute_secs = execute_ms / 1000.0,
+        );
+        if lifecycle.running {
+            description.push_str(&tr!("lifecycle.delivery.running"));
+        }
+
+        let is_insert = profile.summary.sql_statement.trim_start().to_uppercase().starts_with("INSERT");
+        let suggestions = if is_insert {
+            tr_list!("lifecycle.delivery.insert_suggestions")
+        } else {
+            tr_list!("lifecycle.delivery.client_suggestions")
+        };
+
+        Some(HotSpot {
+            node_path: "Execution.ResultDeliver".to_string(),
+            node: NodeRef::default(),
+            severity: if share >= DOMINANT_DELIVERY_SHARE { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::SlowResultDelivery,
+            confidence: Confidence::High,
+            description,
+            suggestions,
+            evidence: vec![
+                Evidence::new("ResultDeliverTime", deliver_ms, MetricUnit::Milliseconds).threshold(SLOW_DELIVERY_MS),
+                Evidence::new("ResultDeliverTime / QueryExecutionWallTime", share, MetricUnit::Ratio).threshold(SLOW_DELIVERY_SHARE),
+            ],
+        })
+    }
+
+    fn planner_timer(planner: &PlannerInfo, name: &str) -> f64 {
+        planner.timers.iter().find(|t| t.name == name).map(|t| t.time_ms).unwrap_or(0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, profile};
+
+    fn timer(name: &str, depth: usize, time_ms: f64) -> PlannerTimer {
+        PlannerTimer { name: name.to_string(), depth, count: 1, time_ms }
+    }
+
+    fn lifecycle_profile(sql: &str, pending_ms: f64, wall_ms: f64, deliver_ms: f64) -> Profile {
+        let mut profile = profile(Vec::new());
+        profile.summary = ProfileSummary {
+            sql_statement: sql.to_string(),
+            query_state: "Finished".to_string(),
+            total_time_ms: Some(45_045.0),
+            query_execution_wall_time_ms: Some(wall_ms),
+            result_deliver_time_ms: Some(deliver_ms),
+            ..ProfileSummary::default()
+        };
+        profile.planner.timers = vec![
+            timer("Parser", 0, 0.0),
+            timer("Total", 0, 7.0),
+            timer("Optimizer", 2, 1.0),
+            timer("Pending", 0, pending_ms),
+            timer("Prepare", 0, 0.0),
+            timer("Deploy", 0, 27.0),
+            timer("DeployWaitTime", 2, 25.0),
+        ];
+        profile
+    }
+
+    #[test]
+    fn test_lifecycle_report() {
+        // profile5: 45s45ms total, 44s875ms wall of which 36s677ms result delivery
+        let lifecycle = LifecycleAnalyzer::report(&lifecycle_profile("INSERT INTO t SELECT 1", 0.0, 44_875.0, 36_677.0)).unwrap();
+        let phase = |name: &str| lifecycle.phases.iter().find(|p| p.name == name).unwrap().time_ms;
+        assert_eq!(phase("Plan"), 7.0);
+        assert_eq!(phase("Execute"), 8198.0);
+        assert!((lifecycle.unaccounted_ms - 136.0).abs() < 1e-6);
+
+        let hotspots = LifecycleAnalyzer::analyze(&lifecycle_profile("INSERT INTO t SELECT 1", 0.0, 44_875.0, 36_677.0));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::SlowResultDelivery);
+        assert_eq!(hotspots[0].severity, HotSeverity::Moderate);
+        assert!(hotspots[0].suggestions[0].contains("OLAP_TABLE_SINK"));
+    }
+
+    #[test]
+    fn test_queue_wait() {
+        let hotspots = LifecycleAnalyzer::analyze(&lifecycle_profile("SELECT 1", 30_000.0, 15_000.0, 0.0));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::QueryQueueWait);
+        assert_eq!(hotspots[0].severity, HotSeverity::Moderate);
+    }
+
+    #[test]
+    fn test_slow_result_delivery_profile5() {
+        let profile = load("profile5.txt");
+        let lifecycle = LifecycleAnalyzer::report(&profile).unwrap();
+        assert!(lifecycle.phases.iter().any(|p| p.name == "Execute"));
+        let hotspots = LifecycleAnalyzer::analyze(&profile);
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::SlowResultDelivery);
+        assert!(hotspots[0].suggestions[0].contains("OLAP_TABLE_SINK"));
+    }
+}
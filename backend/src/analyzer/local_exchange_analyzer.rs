@@ -0,0 +1,316 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::parser::specialized::LocalExchangeStrategy;
+use crate::{tr, tr_list};
+
+/// Local exchange buffers below this size are never worth reporting.
+const MIN_BUFFER_BYTES: u64 = 128 * 1024 * 1024;
+const LARGE_BUFFER_BYTES: u64 = 1024 * 1024 * 1024;
+const SEVERE_BUFFER_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+/// Share of the per-node query peak memory held by one local exchange buffer.
+const HIGH_PEAK_SHARE: f64 = 0.3;
+const SEVERE_PEAK_SHARE: f64 = 0.5;
+/// A consuming pipeline running this many times fewer drivers than its producers caps the query's parallelism.
+const DOP_DROP_FACTOR: u64 = 4;
+/// Consuming pipelines busy for less than this (per driver) do not matter.
+const MIN_CONSUMER_ACTIVE_SECS: f64 = 1.0;
+
+/// One `LOCAL_EXCHANGE_SINK` with the pipelines on both sides of it.
+struct LocalExchange<'a> {
+    node_path: String,
+    node: NodeRef,
+    metrics: LocalExchangeSpecializedMetrics,
+    producer: &'a Pipeline,
+    consumer: Option<&'a Pipeline>,
+}
+
+/// Local exchange (`LOCAL_EXCHANGE_SINK/SOURCE`) analysis: buffer memory blowups, shuffles that do
+/// not split anything or collapse the DOP, and the plan shape change of `enable_local_shuffle_agg = false`.
+pub struct LocalExchangeAnalyzer;
+
+impl LocalExchangeAnalyzer {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let peak_per_node = profile.execution.metrics.get("QueryPeakMemoryUsagePerNode")
+            .and_then(|v| ValueParser::parse_bytes(v).ok());
+        let mut hotspots = Vec::new();
+
+        for fragment in &profile.fragments {
+            for exchange in Self::collect_exchanges(fragment) {
+                hotspots.extend(Self::analyze_memory(&exchange, peak_per_node));
+                hotspots.extend(Self::analyze_shuffle(&exchange));
+                hotspots.extend(Self::analyze_dop_drop(&exchange));
+            }
+        }
+
+        hotspots.extend(Self::analyze_local_shuffle_agg(profile));
+        hotspots
+    }
+
+    fn collect_exchanges(fragment: &Fragment) -> Vec<LocalExchange<'_>> {
+        let mut exchanges = Vec::new();
+
+        for pipeline in &fragment.pipelines {
+            for operator in pipeline.operators.iter().filter(|op| op.name == "LOCAL_EXCHANGE_SINK") {
+                let consumer = fragment.pipelines.iter().find(|p| {
+                    p.operators.last().map(|source| {
+                        source.name == "LOCAL_EXCHANGE_SOURCE" && source.plan_node_id == operator.plan_node_id
+                    }).unwrap_or(false)
+                });
+                exchanges.push(LocalExchange {
+                    node_path: format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, operator.name),
+                    node: NodeRef::operator(&fragment.id, &pipeline.id, operator),
+                    metrics: LocalExchangeStrategy::from_metrics(&operator.unique_metrics),
+                    producer: pipeline,
+                    consumer,
+                });
+            }
+        }
+
+        exchanges
+    }
+
+    fn analyze_memory(exchange: &LocalExchange, peak_per_node: Option<u64>) -> Option<HotSpot> {
+        let metrics = &exchange.metrics;
+        // a passthrough buffer only fills up while its consumer stops pulling: the memory is the
+        // backlog of a slow consumer (e.g. a client fetching results), reported where that shows
+        if metrics.exchange_type.eq_ignore_ascii_case("Passthrough") {
+            return None;
+        }
+        let buffered = metrics.peak_memory.unwrap_or(0).max(metrics.peak_buffer_memory.unwrap_or(0));
+        if buffered < MIN_BUFFER_BYTES {
+            return None;
+        }
+        let share = peak_per_node.filter(|p| *p > 0).map(|p| buffered as f64 / p as f64);
+        if buffered < LARGE_BUFFER_BYTES && share.map(|s| s < HIGH_PEAK_SHARE).unwrap_or(true) {
+            return None;
+        }
+
+        let mut description = tr!(
+            "local_exchange.memory.description",
+            exchange_type = Self::or_dash(&metrics.exchange_type),
+            partitions = metrics.shuffle_num.map(|n| n.to_string()).unwrap_or_else(|| "?".to_string()),
+            bytes = ValueParser::format_bytes(buffered),
+        );
+        if let Some(share) = share {
+            description.push_str(&tr!("local_exchange.memory.peak_share", percent = share * 100.0));
+        }
+        if let Some(unplugs) = metrics.buffer_unplug_count.filter(|c| *c > 0) {
+            description.push_str(&tr!("local_exchange.memory.unplugs", count = unplugs));
+        }
+        let consumer_ops = exchange.consumer.map(Self::operator_chain);
+        if let Some(ops) = &consumer_ops {
+            description.push_str(&tr!("local_exchange.memory.consumer", operators = ops));
+        }
+
+        Some(HotSpot {
+            node_path: exchange.node_path.clone(),
+            node: exchange.node.clone(),
+            severity: if buffered >= SEVERE_BUFFER_BYTES {
+                HotSeverity::Severe
+            } else if buffered >= LARGE_BUFFER_BYTES || share.map(|s| s >= SEVERE_PEAK_SHARE).unwrap_or(false) {
+                HotSeverity::Moderate
+            } else {
+                HotSeverity::Mild
+            },
+            issue_type: IssueCode::LocalExchangeMemory,
+            confidence: Confidence::High,
+            description,
+            suggestions: tr_list!("local_exchange.memory.suggestions"),
+            evidence: [
+                Some(Evidence::new("PeakMemoryUsage", buffered as f64, MetricUnit::Bytes).threshold(LARGE_BUFFER_BYTES as f64)),
+                share.map(|s| Evidence::new("PeakMemoryUsage / QueryPeakMemoryUsagePerNode", s, MetricUnit::Ratio).threshold(SEVERE_PEAK_SHARE)),
+            ].into_iter().flatten().collect(),
+        })
+    }
+
+    fn analyze_shuffle(exchange: &LocalExchange) -> Option<HotSpot> {
+        let metrics = &exchange.metrics;
+        if !metrics.exchange_type.to_lowercase().contains("shuffle") || metrics.shuffle_num? > 1 {
+            return None;
+        }
+
+        Some(HotSpot {
+            node_path: exchange.node_path.clone(),
+            node: exchange.node.clone(),
+            severity: HotSeverity::Mild,
+            issue_type: IssueCode::UnnecessaryLocalShuffle,
+            confidence: Confidence::High,
+            description: tr!(
+                "local_exchange.shuffle.description",
+                exchange_type = metrics.exchange_type,
+                partitions = metrics.shuffle_num.unwrap_or(0),
+            ),
+            suggestions: tr_list!("local_exchange.shuffle.suggestions"),
+            evidence: vec![Evidence::new("ShuffleNum", metrics.shuffle_num.unwrap_or(0) as f64, MetricUnit::Count)],
+        })
+    }
+
+    fn analyze_dop_drop(exchange: &LocalExchange) -> Option<HotSpot> {
+        let consumer = exchange.consumer?;
+        let producer_dop = Self::total_dop(exchange.producer)?;
+        let consumer_dop = Self::total_dop(consumer)?;
+        if consumer_dop * DOP_DROP_FACTOR > producer_dop {
+            return None;
+        }
+        // a global merge is single-driver by design
+        if consumer.operators.iter().any(|op| op.name.contains("MERGE")) {
+            return None;
+        }
+        let active_secs = consumer.metrics.get("ActiveTime")
+            .and_then(|v| ValueParser::parse_duration(v).ok())
+            .map(|d| d.as_secs_f64())?;
+        if active_secs < MIN_CONSUMER_ACTIVE_SECS {
+            return None;
+        }
+
+        Some(HotSpot {
+            node_path: exchange.node_path.clone(),
+            node: exchange.node.clone(),
+            severity: if active_secs >= 10.0 { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::LocalExchangeDopDrop,
+            confidence: Confidence::Medium,
+            description: tr!(
+                "local_exchange.dop_drop.description",
+                producer_dop = producer_dop,
+                consumer_dop = consumer_dop,
+                consumer = Self::operator_chain(consumer),
+                secs = active_secs,
+            ),
+            suggestions: tr_list!("local_exchange.dop_drop.suggestions"),
+            evidence: vec![
+                Evidence::new("TotalDegreeOfParallelism (producer / consumer)", producer_dop as f64 / consumer_dop.max(1) as f64, MetricUnit::Ratio)
+                    .threshold(DOP_DROP_FACTOR as f64),
+                Evidence::new("ActiveTime (consumer)", active_secs * 1000.0, MetricUnit::Milliseconds).threshold(MIN_CONSUMER_ACTIVE_SECS * 1000.0),
+            ],
+        })
+    }
+
+    /// With `enable_local_shuffle_agg = false` the optimizer cannot replace the network shuffle of an
+    /// aggregation with a local shuffle, so single-backend queries keep an extra exchange and second phase.
+    fn analyze_local_shuffle_agg(profile: &Profile) -> Option<HotSpot> {
+        let disabled = profile.summary.variables.get("enable_local_shuffle_agg")
+            .map(|v| v.eq_ignore_ascii_case("false"))
+            .unwrap_or(false);
+        if !disabled {
+            return None;
+        }
+
+        let mut backends: Vec<&String> = profile.fragments.iter().flat_map(|f| f.backend_addresses.iter()).collect();
+        backends.sort();
+        backends.dedup();
+        if backends.len() > 1 {
+            return None;
+        }
+
+        let (node_path, node) = profile.fragments.iter().find_map(|fragment| {
+            fragment.pipelines.iter().find_map(|pipeline| {
+                let sink = pipeline.operators.first()?;
+                let source = pipeline.operators.last()?;
+                (sink.name.starts_with("AGGREGATE_") && source.name == "EXCHANGE_SOURCE")
+                    .then(|| (format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, sink.name), NodeRef::operator(&fragment.id, &pipeline.id, sink)))
+            })
+        })?;
+
+        Some(HotSpot {
+            node_path,
+            node,
+            severity: HotSeverity::Mild,
+            issue_type: IssueCode::LocalShuffleAggDisabled,
+            confidence: Confidence::Medium,
+            description: tr!("local_exchange.shuffle_agg_disabled.description"),
+            suggestions: tr_list!("local_exchange.shuffle_agg_disabled.suggestions"),
+            evidence: Vec::new(),
+        })
+    }
+
+    fn total_dop(pipeline: &Pipeline) -> Option<u64> {
+        pipeline.metrics.get("TotalDegreeOfParallelism")
+            .or_else(|| pipeline.metrics.get("DegreeOfParallelism"))
+            .and_then(|v| ValueParser::parse_number(v).ok())
+    }
+
+    fn operator_chain(pipeline: &Pipeline) -> String {
+        pipeline.operators.iter().rev().map(|op| op.name.as_str()).collect::<Vec<_>>().join(" -> ")
+    }
+
+    fn or_dash(value: &str) -> &str {
+        if value.is_empty() { "-" } else { value }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, metrics, operator, pipeline, profile, with_pipelines};
+
+    fn exchange_profile(pipelines: Vec<Pipeline>, peak: &str, variables: &[(&str, &str)]) -> Profile {
+        let fragment = Fragment { backend_addresses: vec!["10.0.0.1:9060".to_string()], ..with_pipelines("0", pipelines) };
+        let mut profile = profile(vec![fragment]);
+        profile.summary.variables = metrics(variables);
+        profile.execution.metrics = metrics(&[("QueryPeakMemoryUsagePerNode", peak)]);
+        profile
+    }
+
+    #[test]
+    fn test_local_exchange_memory_and_dop_drop() {
+        // a partitioning buffer holding most of the node's peak memory
+        let exchange = |exchange_type: &str| exchange_profile(vec![
+            pipeline("1", &[("TotalDegreeOfParallelism", "32")], vec![
+                operator("LOCAL_EXCHANGE_SINK", 5, &[], &[
+                    ("Type", exchange_type),
+                    ("ShuffleNum", "8"),
+                    ("LocalExchangePeakMemoryUsage", "355.685 MB"),
+                ]),
+                operator("OLAP_SCAN", 0, &[], &[]),
+            ]),
+            pipeline("2", &[("TotalDegreeOfParallelism", "1"), ("ActiveTime", "12s")], vec![
+                operator("AGGREGATE_BLOCKING_SINK", 4, &[], &[]),
+                operator("LOCAL_EXCHANGE_SOURCE", 5, &[], &[]),
+            ]),
+        ], "440.237 MB", &[]);
+
+        let hotspots = LocalExchangeAnalyzer::analyze(&exchange("Partition"));
+        let memory = hotspots.iter().find(|h| h.issue_type == IssueCode::LocalExchangeMemory).unwrap();
+        assert_eq!(memory.severity, HotSeverity::Moderate);
+        assert!(memory.description.contains("LOCAL_EXCHANGE_SOURCE -> AGGREGATE_BLOCKING_SINK"));
+        let share = &memory.evidence[1];
+        assert_eq!(share.threshold, Some(SEVERE_PEAK_SHARE));
+        assert!((share.value - 355.685 / 440.237).abs() < 1e-6);
+
+        // a passthrough buffer only holds what the consumer has not pulled yet
+        let hotspots = LocalExchangeAnalyzer::analyze(&exchange("Passthrough"));
+        assert!(!hotspots.iter().any(|h| h.issue_type == IssueCode::LocalExchangeMemory));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::LocalExchangeDopDrop));
+        let dop = hotspots.iter().find(|h| h.issue_type == IssueCode::LocalExchangeDopDrop).unwrap();
+        assert!(dop.description.contains("从 32 降到 1"));
+        assert!(!hotspots.iter().any(|h| h.issue_type == IssueCode::UnnecessaryLocalShuffle));
+    }
+
+    #[test]
+    fn test_local_shuffle_agg_disabled() {
+        let pipelines = || vec![
+            pipeline("0", &[], vec![
+                operator("AGGREGATE_BLOCKING_SINK", 4, &[], &[]),
+                operator("EXCHANGE_SOURCE", 3, &[], &[]),
+            ]),
+        ];
+        let hotspots = LocalExchangeAnalyzer::analyze(&exchange_profile(pipelines(), "1.000 GB", &[("enable_local_shuffle_agg", "false")]));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::LocalShuffleAggDisabled);
+
+        assert!(LocalExchangeAnalyzer::analyze(&exchange_profile(pipelines(), "1.000 GB", &[])).is_empty());
+    }
+
+    #[test]
+    fn test_local_exchanges_profile1() {
+        // the local shuffles in front of the aggregations hold little memory and keep the DOP
+        assert!(LocalExchangeAnalyzer::analyze(&load("profile1.txt")).is_empty());
+    }
+
+    #[test]
+    fn test_passthrough_backlog_profile4() {
+        // 355MB buffered in front of a RESULT_SINK waiting on the client
+        let hotspots = LocalExchangeAnalyzer::analyze(&load("profile4.txt"));
+        assert!(!hotspots.iter().any(|h| h.issue_type == IssueCode::LocalExchangeMemory));
+    }
+}
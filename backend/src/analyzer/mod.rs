@@ -1,5 +1,45 @@
 pub mod hotspot_detector;
 pub mod suggestion_engine;
+pub mod critical_path;
+pub mod skew_detector;
+pub mod exchange_analyzer;
+pub mod olap_scan_analyzer;
+pub mod data_cache_analyzer;
+pub mod external_scan_analyzer;
+pub mod aggregate_analyzer;
+pub mod spill_analyzer;
+pub mod chunk_analyzer;
+pub mod local_exchange_analyzer;
+pub mod adaptive_dop_analyzer;
+pub mod lifecycle_analyzer;
+pub mod efficiency_analyzer;
+pub mod failure_analyzer;
+pub mod risk_analyzer;
+pub mod speedup_estimator;
+pub mod root_cause;
+pub mod node_explainer;
+pub mod rule_engine;
+pub mod detector;
 
 pub use hotspot_detector::*;
 pub use suggestion_engine::*;
+pub use critical_path::*;
+pub use skew_detector::*;
+pub use exchange_analyzer::*;
+pub use olap_scan_analyzer::*;
+pub use data_cache_analyzer::*;
+pub use external_scan_analyzer::*;
+pub use aggregate_analyzer::*;
+pub use spill_analyzer::*;
+pub use chunk_analyzer::*;
+pub use local_exchange_analyzer::*;
+pub use adaptive_dop_analyzer::*;
+pub use lifecycle_analyzer::*;
+pub use efficiency_analyzer::*;
+pub use failure_analyzer::*;
+pub use risk_analyzer::*;
+pub use speedup_estimator::*;
+pub use root_cause::*;
+pub use node_explainer::*;
+pub use rule_engine::*;
+pub use detector::{Detector, DetectorRegistry, FnDetector, RuleDetector, BUILTIN_DETECTORS};
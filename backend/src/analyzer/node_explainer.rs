@@ -0,0 +1,367 @@
+use crate::analyzer::skew_detector::{SkewDetector, MILD_SKEW_RATIO};
+use crate::constants::starrocks::{MERGED_INFO_PREFIX_MAX, MERGED_INFO_PREFIX_MIN};
+use crate::glossary;
+use crate::i18n;
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::tr;
+use std::collections::HashMap;
+
+/// How many counters an explanation spells out.
+const TOP_METRICS: usize = 3;
+/// How many skew indicators an explanation lists.
+const MAX_SKEW_INDICATORS: usize = 3;
+/// Share of the node's time from which one category is called out as where the time goes.
+const DOMINANT_SHARE: f64 = 0.5;
+/// Output/input ratio above which a node is said to expand its input rather than pass it on.
+const EXPANSION_RATIO: f64 = 1.5;
+/// Output/input ratio below which a node is said to filter its input.
+const FILTER_RATIO: f64 = 0.9;
+
+/// Explains an execution tree node: where its time goes, what it does to the rows of its
+/// children, how evenly its instances are loaded, and which of its counters matter most.
+///
+/// A plan node runs as several operators, possibly in several pipelines and fragments (a hash
+/// join's build and probe, an exchange's sink and source); all of them are taken into account.
+/// The time categories are per-driver averages and overlap in wall time (IO threads run
+/// alongside the drivers): they show where the node's time goes, not a partition of it.
+pub struct NodeExplainer;
+
+impl NodeExplainer {
+    /// `hotspots` are those of the whole query; the ones located at the node are cited and
+    /// their evidence ranks the node's counters.
+    pub fn explain(profile: &Profile, plan_node_id: i32, hotspots: &[HotSpot]) -> Option<NodeExplanation> {
+        let tree = profile.execution_tree.as_ref()?;
+        let node = tree.nodes.iter().find(|n| n.plan_node_id == Some(plan_node_id))?;
+        let operators = Self::operators(profile, plan_node_id);
+
+        let node_hotspots: Vec<(usize, &HotSpot)> = hotspots.iter().enumerate()
+            .filter(|(_, hotspot)| tree.locate(profile, hotspot).is_some_and(|n| n.id == node.id))
+            .collect();
+
+        let time_breakdown = Self::time_breakdown(&operators);
+        let data_flow = Self::data_flow(profile, tree, node, &operators);
+
+        let mut skew: Vec<SkewIndicator> = operators.iter()
+            .flat_map(|(pipeline, index)| SkewDetector::operator_skews(pipeline, &pipeline.operators[*index]))
+            .collect();
+        skew.sort_by(|a, b| b.significant.cmp(&a.significant)
+            .then(b.ratio.partial_cmp(&a.ratio).unwrap_or(std::cmp::Ordering::Equal)));
+        skew.truncate(MAX_SKEW_INDICATORS);
+
+        let top_metrics = Self::top_metrics(&operators, &node_hotspots);
+
+        let mut explanation = NodeExplanation {
+            plan_node_id,
+            operator_name: node.operator_name.clone(),
+            time_percentage: node.time_percentage,
+            time_breakdown,
+            data_flow,
+            skew,
+            top_metrics,
+            hotspots: node_hotspots.iter()
+                .map(|(index, hotspot)| HotspotRef {
+                    index: *index,
+                    issue_type: hotspot.issue_type.clone(),
+                    node_path: hotspot.node_path.clone(),
+                })
+                .collect(),
+            narrative: Vec::new(),
+        };
+        explanation.narrative = Self::narrative(&explanation);
+        Some(explanation)
+    }
+
+    /// The node's operators as (pipeline, index into its operators).
+    fn operators(profile: &Profile, plan_node_id: i32) -> Vec<(&Pipeline, usize)> {
+        let id = plan_node_id.to_string();
+        profile.fragments.iter()
+            .flat_map(|f| &f.pipelines)
+            .flat_map(|pipeline| {
+                pipeline.operators.iter().enumerate()
+                    .filter(|(_, op)| op.plan_node_id.as_deref() == Some(id.as_str()))
+                    .map(move |(index, _)| (pipeline, index))
+            })
+            .collect()
+    }
+
+    fn time_breakdown(operators: &[(&Pipeline, usize)]) -> TimeBreakdown {
+        let mut breakdown = TimeBreakdown::default();
+        for (pipeline, index) in operators {
+            let operator = &pipeline.operators[*index];
+            let unique = &operator.unique_metrics;
+            let io_ms = Self::time_ms(unique, "ScanTime").or_else(|| Self::time_ms(unique, "IOTaskExecTime")).unwrap_or(0.0);
+
+            breakdown.cpu_ms += Self::time_ms(&operator.common_metrics, "OperatorTotalTime").unwrap_or(0.0);
+            breakdown.io_ms += io_ms;
+            breakdown.network_ms += Self::time_ms(unique, "NetworkTime").unwrap_or(0.0);
+            breakdown.wait_ms += Self::time_ms(unique, "IOTaskWaitTime").unwrap_or(0.0)
+                + Self::time_ms(unique, "WaitTime").unwrap_or(0.0);
+
+            // operators are listed sink first: the source waits for upstream pipelines (a scan
+            // source waits for its own IO, counted above), the sink for downstream ones
+            if *index + 1 == pipeline.operators.len() && io_ms == 0.0 {
+                breakdown.wait_ms += Self::time_ms(&pipeline.metrics, "InputEmptyTime").unwrap_or(0.0);
+            }
+            if *index == 0 {
+                breakdown.wait_ms += Self::time_ms(&pipeline.metrics, "OutputFullTime").unwrap_or(0.0);
+            }
+        }
+        breakdown
+    }
+
+    fn data_flow(profile: &Profile, tree: &ExecutionTree, node: &ExecutionTreeNode, operators: &[(&Pipeline, usize)]) -> NodeDataFlow {
+        let children: Vec<ChildRows> = node.children.iter()
+            .filter_map(|id| tree.nodes.iter().find(|n| &n.id == id))
+            .filter_map(|child| {
+                let plan_node_id = child.plan_node_id?;
+                Some(ChildRows {
+                    plan_node_id,
+                    operator_name: child.operator_name.clone(),
+                    rows: Self::output_rows(profile, plan_node_id),
+                })
+            })
+            .collect();
+
+        // a leaf scan's input is what it read from storage
+        let input_rows = if children.is_empty() {
+            operators.iter()
+                .filter_map(|(pipeline, index)| pipeline.operators[*index].unique_metrics.get("RawRowsRead"))
+                .filter_map(|v| ValueParser::parse_number::<u64>(v).ok())
+                .reduce(|a, b| a + b)
+        } else {
+            children.iter().filter_map(|c| c.rows).reduce(|a, b| a + b)
+        };
+        let output_rows = node.plan_node_id.and_then(|id| Self::output_rows(profile, id));
+        let ratio = match (input_rows, output_rows) {
+            (Some(input), Some(output)) if input > 0 => Some(output as f64 / input as f64),
+            _ => None,
+        };
+
+        NodeDataFlow { input_rows, output_rows, ratio, children }
+    }
+
+    /// Rows the plan node hands on: in every pipeline it runs in, its outermost operator that
+    /// produces rows (not a sink); the smallest count wins, as the node's local exchanges and
+    /// inner operators still see its input.
+    fn output_rows(profile: &Profile, plan_node_id: i32) -> Option<u64> {
+        let id = plan_node_id.to_string();
+        profile.fragments.iter()
+            .flat_map(|f| &f.pipelines)
+            .filter_map(|pipeline| {
+                pipeline.operators.iter()
+                    .find(|op| op.plan_node_id.as_deref() == Some(id.as_str()) && !op.name.ends_with("_SINK"))
+            })
+            .filter_map(|op| op.common_metrics.get("PullRowNum"))
+            .filter_map(|v| ValueParser::parse_number::<u64>(v).ok())
+            .min()
+    }
+
+    /// Glossary counters of the node: those cited by its hotspots' evidence first, then time
+    /// counters by duration, then the others.
+    fn top_metrics(operators: &[(&Pipeline, usize)], hotspots: &[(usize, &HotSpot)]) -> Vec<ExplainedMetric> {
+        let cited = |metric: &str| hotspots.iter()
+            .flat_map(|(_, hotspot)| &hotspot.evidence)
+            .any(|e| e.glossary.iter().any(|name| name == metric));
+
+        let mut candidates: Vec<(bool, f64, ExplainedMetric)> = operators.iter()
+            .flat_map(|(pipeline, index)| {
+                let operator = &pipeline.operators[*index];
+                operator.common_metrics.iter().chain(&operator.unique_metrics)
+                    .map(move |(metric, value)| (operator, metric, value))
+            })
+            .filter(|(_, metric, _)| !metric.starts_with(MERGED_INFO_PREFIX_MAX) && !metric.starts_with(MERGED_INFO_PREFIX_MIN))
+            .filter_map(|(operator, metric, value)| {
+                let entry = glossary::lookup(metric)?;
+                let time_ms = Self::parse_time(value);
+                if time_ms == Some(0.0) {
+                    return None;
+                }
+                Some((cited(metric), time_ms.unwrap_or(-1.0), ExplainedMetric {
+                    metric: metric.clone(),
+                    operator: operator.name.clone(),
+                    value: value.clone(),
+                    meaning: entry.meaning.current().clone(),
+                }))
+            })
+            .collect();
+
+        candidates.sort_by(|a, b| b.0.cmp(&a.0)
+            .then(b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal))
+            .then_with(|| a.2.metric.cmp(&b.2.metric)));
+
+        let mut top: Vec<ExplainedMetric> = Vec::new();
+        for (_, _, metric) in candidates {
+            if top.len() == TOP_METRICS {
+                break;
+            }
+            if !top.iter().any(|m| m.metric == metric.metric) {
+                top.push(metric);
+            }
+        }
+        top
+    }
+
+    fn narrative(explanation: &NodeExplanation) -> Vec<String> {
+        let mut lines = Vec::new();
+        let operator = &explanation.operator_name;
+
+        lines.push(match explanation.time_percentage {
+            Some(percent) => tr!("explain.summary", operator = operator, id = explanation.plan_node_id, percent = percent),
+            None => tr!("explain.summary_no_share", operator = operator, id = explanation.plan_node_id),
+        });
+
+        let time = &explanation.time_breakdown;
+        let total_ms = time.total_ms();
+        if total_ms > 0.0 {
+            let share = |ms: f64| ms * 100.0 / total_ms;
+            lines.push(tr!(
+                "explain.time",
+                total_ms = total_ms,
+                cpu = share(time.cpu_ms),
+                io = share(time.io_ms),
+                network = share(time.network_ms),
+                wait = share(time.wait_ms),
+            ));
+            let categories = [
+                (time.cpu_ms, "explain.dominant.cpu"),
+                (time.io_ms, "explain.dominant.io"),
+                (time.network_ms, "explain.dominant.network"),
+                (time.wait_ms, "explain.dominant.wait"),
+            ];
+            if let Some((_, key)) = categories.iter().find(|(ms, _)| *ms / total_ms >= DOMINANT_SHARE) {
+                lines.push(tr!(key));
+            }
+        }
+
+        let flow = &explanation.data_flow;
+        if let (Some(input), Some(output), Some(ratio)) = (flow.input_rows, flow.output_rows, flow.ratio) {
+            lines.push(match ratio {
+                r if r < FILTER_RATIO => tr!("explain.rows.filters", input = input, output = output, percent = (1.0 - r) * 100.0),
+                r if r > EXPANSION_RATIO => tr!("explain.rows.expands", input = input, output = output, ratio = r),
+                _ => tr!("explain.rows.passes", input = input, output = output),
+            });
+        }
+
+        match explanation.skew.first() {
+            Some(worst) if worst.significant => lines.push(tr!(
+                "explain.skew.skewed",
+                operator = worst.operator,
+                metric = worst.metric,
+                ratio = worst.ratio,
+            )),
+            Some(worst) if worst.ratio >= MILD_SKEW_RATIO => lines.push(tr!(
+                "explain.skew.minor",
+                metric = worst.metric,
+                ratio = worst.ratio,
+            )),
+            Some(worst) => lines.push(tr!("explain.skew.balanced", ratio = worst.ratio)),
+            None => {}
+        }
+
+        if !explanation.hotspots.is_empty() {
+            let mut issues: Vec<String> = Vec::new();
+            for hotspot in &explanation.hotspots {
+                let title = i18n::issue_title(&hotspot.issue_type);
+                if !issues.contains(&title) {
+                    issues.push(title);
+                }
+            }
+            lines.push(tr!("explain.hotspots", issues = issues.join(", ")));
+        }
+
+        lines.extend(explanation.top_metrics.iter().map(|m| tr!(
+            "explain.metric",
+            metric = m.metric,
+            value = m.value,
+            meaning = m.meaning,
+        )));
+        lines
+    }
+
+    fn time_ms(metrics: &HashMap<String, String>, key: &str) -> Option<f64> {
+        metrics.get(key).and_then(|v| Self::parse_time(v))
+    }
+
+    /// Durations are written like `1s234ms` or `517.418us`; counts and sizes are not parsed.
+    fn parse_time(value: &str) -> Option<f64> {
+        let value = value.trim();
+        let starts_numeric = value.starts_with(|c: char| c.is_ascii_digit());
+        (starts_numeric && !value.contains(' ') && value.ends_with(['s', 'm', 'h']))
+            .then(|| ValueParser::parse_time_to_ms(value).ok())
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{hotspot, load, operator, pipeline, profile, tree, tree_node, with_pipelines};
+
+    /// A scan reading 10M rows of which a project keeps 1M, in one pipeline of 4 drivers.
+    fn scan_profile() -> Profile {
+        let mut profile = profile(vec![with_pipelines("0", vec![pipeline("0", &[("DegreeOfParallelism", "4"), ("OutputFullTime", "100ms")], vec![
+            operator("PROJECT", 1, &[("OperatorTotalTime", "50ms"), ("PullRowNum", "1.000M (1000000)")], &[]),
+            operator("OLAP_SCAN", 0, &[("OperatorTotalTime", "200ms"), ("PullRowNum", "1.000M (1000000)")], &[
+                ("ScanTime", "8s"),
+                ("__MAX_OF_ScanTime", "20s"),
+                ("__MIN_OF_ScanTime", "1s"),
+                ("IOTaskWaitTime", "1s"),
+                ("RawRowsRead", "10.000M (10000000)"),
+                ("PredFilterRows", "9.000M (9000000)"),
+                ("Table", "lineorder"),
+            ]),
+        ])])]);
+        let mut nodes = vec![tree_node(1, "PROJECT", &[0]), tree_node(0, "OLAP_SCAN", &[])];
+        nodes.iter_mut().for_each(|node| node.time_percentage = Some(80.0));
+        profile.execution_tree = Some(tree(nodes));
+        profile
+    }
+
+    #[test]
+    fn test_explain_scan() {
+        let profile = scan_profile();
+        let hotspot = HotSpot {
+            node_path: "Fragment0.Pipeline0.OLAP_SCAN".to_string(),
+            node: NodeRef { plan_node_id: Some(0), ..NodeRef::default() },
+            evidence: vec![Evidence::new("PredFilterRows / RawRowsRead", 0.9, MetricUnit::Ratio)],
+            ..hotspot(IssueCode::MissingPredicatePushdown, HotSeverity::Moderate)
+        };
+        let explanation = i18n::with_lang(i18n::Lang::En, || NodeExplainer::explain(&profile, 0, &[hotspot]))
+            .expect("scan is in the tree");
+
+        assert_eq!(explanation.time_breakdown, TimeBreakdown { cpu_ms: 200.0, io_ms: 8_000.0, network_ms: 0.0, wait_ms: 1_000.0 });
+        assert_eq!((explanation.data_flow.input_rows, explanation.data_flow.output_rows), (Some(10_000_000), Some(1_000_000)));
+        assert_eq!(explanation.skew[0].metric, "ScanTime");
+        assert!(explanation.skew[0].significant);
+        assert_eq!(explanation.hotspots.len(), 1);
+
+        // the cited counters come first, then the longest time counters
+        let metrics: Vec<&str> = explanation.top_metrics.iter().map(|m| m.metric.as_str()).collect();
+        assert_eq!(metrics, ["PredFilterRows", "RawRowsRead", "ScanTime"]);
+        assert!(explanation.narrative.iter().any(|line| line.contains("90.0% fewer")), "{:?}", explanation.narrative);
+    }
+
+    #[test]
+    fn test_explain_parent_and_missing_node() {
+        let profile = scan_profile();
+        let explanation = NodeExplainer::explain(&profile, 1, &[]).expect("project is in the tree");
+        assert_eq!(explanation.data_flow.children[0].rows, Some(1_000_000));
+        assert_eq!(explanation.data_flow.ratio, Some(1.0));
+        // the pipeline's sink waits for the downstream
+        assert_eq!(explanation.time_breakdown.wait_ms, 100.0);
+
+        assert!(NodeExplainer::explain(&profile, 7, &[]).is_none());
+    }
+
+    #[test]
+    fn test_explain_scan_profile1() {
+        let profile = load("profile1.txt");
+        let explanation = NodeExplainer::explain(&profile, 0, &[]).expect("scan is in the tree");
+        assert_eq!(explanation.operator_name, "OLAP_SCAN");
+        assert_eq!((explanation.data_flow.input_rows, explanation.data_flow.output_rows), (Some(94_286_609), Some(2_250_692)));
+        // a cold remote read: IO dominates, CPU is negligible
+        assert!(explanation.time_breakdown.io_ms > explanation.time_breakdown.total_ms() / 2.0);
+        assert!(explanation.time_breakdown.cpu_ms < 1.0);
+    }
+}
@@ -0,0 +1,289 @@
+use crate::models::*;
+use crate::parser::specialized::ScanStrategy;
+use crate::{tr, tr_list};
+
+/// Rules on fewer raw rows than this are not worth reporting.
+const MIN_RAW_ROWS: u64 = 10_000_000;
+/// Share of read rows discarded by predicate evaluation that means they should have been filtered before reading.
+const HIGH_PRED_FILTER_RATIO: f64 = 0.9;
+/// Index filters removing less than this share of candidate rows are considered ineffective.
+const LOW_INDEX_FILTER_RATIO: f64 = 0.1;
+/// Share of read rows discarded by predicates from which an index that removed nothing is reported.
+const INDEXABLE_PRED_FILTER_RATIO: f64 = 0.5;
+/// Rowsets read per tablet from which compaction is lagging.
+const FRAGMENTED_ROWSETS_PER_TABLET: f64 = 50.0;
+const SEVERE_ROWSETS_PER_TABLET: f64 = 500.0;
+/// Evidence holding the share of read rows that predicates discarded, read by the speedup estimate.
+pub(crate) const PRED_FILTER_RATIO_EVIDENCE: &str = "PredFilterRows / RawRowsRead";
+
+/// Deep analysis of native-table scans: the storage row-filtering funnel, rows that are only
+/// filtered after being read, index effectiveness and rowset fragmentation.
+///
+/// Covers `OLAP_SCAN` and, in shared-data clusters, `CONNECTOR_SCAN` over `LakeDataSource`.
+pub struct OlapScanAnalyzer;
+
+impl OlapScanAnalyzer {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                for operator in pipeline.operators.iter().filter(|op| Self::is_native_scan(op)) {
+                    let metrics = ScanStrategy::olap_from_metrics(&operator.unique_metrics);
+                    let node_path = format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, operator.name);
+                    let node = NodeRef::operator(&fragment.id, &pipeline.id, operator);
+                    hotspots.extend(Self::analyze_funnel(&node_path, &node, &metrics));
+                    hotspots.extend(Self::analyze_rowsets(&node_path, &node, &metrics));
+                }
+            }
+        }
+
+        hotspots
+    }
+
+    pub fn is_native_scan(operator: &Operator) -> bool {
+        operator.name == "OLAP_SCAN"
+            || (operator.name == "CONNECTOR_SCAN"
+                && operator.unique_metrics.get("DataSourceType").map(|t| t == "LakeDataSource").unwrap_or(false))
+    }
+
+    fn analyze_funnel(node_path: &str, node: &NodeRef, metrics: &OlapScanSpecializedMetrics) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+        let funnel = &metrics.funnel;
+
+        let (Some(raw), Some(candidates)) = (funnel.raw_rows_read, funnel.candidate_rows()) else {
+            return hotspots;
+        };
+        if raw < MIN_RAW_ROWS {
+            return hotspots;
+        }
+
+        let pred_filtered = funnel.pred_filter_rows.unwrap_or(0);
+        let returned = funnel.rows_read.unwrap_or(raw.saturating_sub(pred_filtered));
+        let pred_ratio = pred_filtered as f64 / raw as f64;
+        let index_ratio = funnel.index_filtered_rows() as f64 / candidates.max(1) as f64;
+        let table = if metrics.table.is_empty() { "<table>" } else { metrics.table.as_str() };
+        let predicates = if metrics.predicates.is_empty() { "-" } else { metrics.predicates.as_str() };
+
+        let summary = tr!(
+            "olap_scan.funnel",
+            candidates = candidates,
+            index_filtered = funnel.index_filtered_rows(),
+            short_key = funnel.short_key_filter_rows.unwrap_or(0),
+            zone_map = funnel.zone_map_filter_rows.unwrap_or(0),
+            bloom_filter = funnel.bloom_filter_rows.unwrap_or(0),
+            bitmap = funnel.bitmap_index_filter_rows.unwrap_or(0),
+            del_vec = funnel.del_vec_filter_rows.unwrap_or(0),
+            read = raw,
+            pred_filtered = pred_filtered,
+            returned = returned,
+        );
+
+        if pred_ratio >= HIGH_PRED_FILTER_RATIO && index_ratio < LOW_INDEX_FILTER_RATIO {
+            hotspots.push(HotSpot {
+                node_path: node_path.to_string(),
+                node: node.clone(),
+                severity: if pred_ratio > 0.99 { HotSeverity::Severe } else { HotSeverity::Moderate },
+                issue_type: IssueCode::LateRowFiltering,
+                confidence: Confidence::High,
+                description: tr!(
+                    "olap_scan.late_filtering.description",
+                    table = table,
+                    tablets = metrics.tablet_count.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string()),
+                    percent = pred_ratio * 100.0,
+                    predicates = predicates,
+                    funnel = summary,
+                ),
+                suggestions: tr_list!("olap_scan.late_filtering.suggestions"),
+                evidence: vec![
+                    Evidence::new(PRED_FILTER_RATIO_EVIDENCE, pred_ratio, MetricUnit::Ratio).threshold(HIGH_PRED_FILTER_RATIO),
+                    Evidence::new("IndexFilteredRows / CandidateRows", index_ratio, MetricUnit::Ratio),
+                    Evidence::new("RawRowsRead", raw as f64, MetricUnit::Count).threshold(MIN_RAW_ROWS as f64),
+                ],
+            });
+        }
+
+        let indexable = pred_ratio >= INDEXABLE_PRED_FILTER_RATIO;
+        if indexable && metrics.pushdown_predicates.unwrap_or(0) > 0 && funnel.zone_map_filter_rows.unwrap_or(0) == 0 {
+            hotspots.push(HotSpot {
+                node_path: node_path.to_string(),
+                node: node.clone(),
+                severity: HotSeverity::Mild,
+                issue_type: IssueCode::IneffectiveZoneMap,
+                confidence: Confidence::Medium,
+                description: tr!(
+                    "olap_scan.zone_map.description",
+                    table = table,
+                    predicates = metrics.pushdown_predicates.unwrap_or(0),
+                    pred_filtered = pred_filtered,
+                ),
+                suggestions: tr_list!("olap_scan.zone_map.suggestions"),
+                evidence: vec![
+                    Evidence::new("PushdownPredicates", metrics.pushdown_predicates.unwrap_or(0) as f64, MetricUnit::Count),
+                    Evidence::new("ZoneMapIndexFilterRows", 0.0, MetricUnit::Count),
+                    Evidence::new(PRED_FILTER_RATIO_EVIDENCE, pred_ratio, MetricUnit::Ratio).threshold(INDEXABLE_PRED_FILTER_RATIO),
+                ],
+            });
+        }
+
+        if indexable && funnel.bloom_filter_rows == Some(0) && Self::has_bloom_filter_evaluation(metrics) {
+            hotspots.push(HotSpot {
+                node_path: node_path.to_string(),
+                node: node.clone(),
+                severity: HotSeverity::Mild,
+                issue_type: IssueCode::IneffectiveBloomFilter,
+                confidence: Confidence::Medium,
+                description: tr!("olap_scan.bloom_filter.description", table = table, percent = pred_ratio * 100.0),
+                suggestions: tr_list!("olap_scan.bloom_filter.suggestions"),
+                evidence: vec![
+                    Evidence::new("BloomFilterFilterRows", 0.0, MetricUnit::Count),
+                    Evidence::new(PRED_FILTER_RATIO_EVIDENCE, pred_ratio, MetricUnit::Ratio).threshold(INDEXABLE_PRED_FILTER_RATIO),
+                ],
+            });
+        }
+
+        hotspots
+    }
+
+    /// The BloomFilterFilter timer only advances when a bloom index on a predicate column was evaluated.
+    fn has_bloom_filter_evaluation(metrics: &OlapScanSpecializedMetrics) -> bool {
+        metrics.bloom_filter_time.map(|t| !t.is_zero()).unwrap_or(false)
+    }
+
+    fn analyze_rowsets(node_path: &str, node: &NodeRef, metrics: &OlapScanSpecializedMetrics) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+
+        let (Some(rowsets), Some(tablets)) = (metrics.rowsets_read_count, metrics.tablet_count) else {
+            return hotspots;
+        };
+        if tablets == 0 {
+            return hotspots;
+        }
+
+        let per_tablet = rowsets as f64 / tablets as f64;
+        if per_tablet < FRAGMENTED_ROWSETS_PER_TABLET {
+            return hotspots;
+        }
+
+        let table = if metrics.table.is_empty() { "<table>" } else { metrics.table.as_str() };
+        let init_cost = match (metrics.segment_init, metrics.create_segment_iter) {
+            (Some(init), Some(iter)) => format!(", SegmentInit {:.1}s / CreateSegmentIter {:.1}s", init.as_secs_f64(), iter.as_secs_f64()),
+            (Some(init), None) => format!(", SegmentInit {:.1}s", init.as_secs_f64()),
+            _ => String::new(),
+        };
+
+        hotspots.push(HotSpot {
+            node_path: node_path.to_string(),
+            node: node.clone(),
+            severity: if per_tablet >= SEVERE_ROWSETS_PER_TABLET { HotSeverity::Severe } else { HotSeverity::Moderate },
+            issue_type: IssueCode::RowsetFragmentation,
+            confidence: Confidence::High,
+            description: tr!(
+                "olap_scan.rowsets.description",
+                table = table,
+                tablets = tablets,
+                rowsets = rowsets,
+                segments = metrics.segments_read_count.map(|c| c.to_string()).unwrap_or_else(|| "?".to_string()),
+                per_tablet = per_tablet,
+                init_cost = init_cost,
+            ),
+            suggestions: tr_list!("olap_scan.rowsets.suggestions", table = table),
+            evidence: vec![
+                Evidence::new("RowsetsReadCount / TabletCount", per_tablet, MetricUnit::Ratio).threshold(FRAGMENTED_ROWSETS_PER_TABLET),
+                Evidence::new("RowsetsReadCount", rowsets as f64, MetricUnit::Count),
+            ],
+        });
+
+        hotspots
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::load;
+
+    fn scan_metrics(raw: u64, pred_filtered: u64, zone_map: u64, rowsets: u64, tablets: u64) -> OlapScanSpecializedMetrics {
+        OlapScanSpecializedMetrics {
+            table: "t".to_string(),
+            rollup: "t".to_string(),
+            shared_scan: false,
+            scan_time: None,
+            io_time: None,
+            bytes_read: None,
+            rows_read: Some(raw - pred_filtered),
+            raw_rows_read: Some(raw),
+            tablet_count: Some(tablets),
+            pushdown_predicates: Some(1),
+            predicates: "2: model = 'PKB110'".to_string(),
+            segments_read_count: Some(rowsets),
+            rowsets_read_count: Some(rowsets),
+            segment_init: None,
+            create_segment_iter: None,
+            bloom_filter_time: Some(std::time::Duration::from_micros(1314)),
+            funnel: ScanRowFunnel {
+                zone_map_filter_rows: Some(zone_map),
+                raw_rows_read: Some(raw),
+                pred_filter_rows: Some(pred_filtered),
+                rows_read: Some(raw - pred_filtered),
+                bloom_filter_rows: Some(0),
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_late_row_filtering() {
+        let hotspots = OlapScanAnalyzer::analyze_funnel("scan", &NodeRef::default(), &scan_metrics(94_286_609, 92_035_917, 0, 32, 32));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::LateRowFiltering));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::IneffectiveZoneMap));
+        assert!(hotspots.iter().any(|h| h.issue_type == IssueCode::IneffectiveBloomFilter));
+
+        // zonemap already removed most candidates: predicate filtering of the rest is not late filtering
+        let mut metrics = scan_metrics(20_000_000, 19_000_000, 500_000_000, 32, 32);
+        metrics.bloom_filter_time = None;
+        assert!(OlapScanAnalyzer::analyze_funnel("scan", &NodeRef::default(), &metrics).is_empty());
+    }
+
+    #[test]
+    fn test_rowset_fragmentation() {
+        let hotspots = OlapScanAnalyzer::analyze_rowsets("scan", &NodeRef::default(), &scan_metrics(1, 0, 0, 89_600, 32));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].severity, HotSeverity::Severe);
+        assert_eq!(hotspots[0].evidence[0].value, 2800.0);
+        assert!(hotspots[0].suggestions[0].contains("COMPACT"));
+
+        assert!(OlapScanAnalyzer::analyze_rowsets("scan", &NodeRef::default(), &scan_metrics(1, 0, 0, 64, 32)).is_empty());
+    }
+
+    #[test]
+    fn test_rowset_fragmentation_profile3() {
+        let hotspots = OlapScanAnalyzer::analyze(&load("profile3.txt"));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::RowsetFragmentation);
+        assert_eq!(hotspots[0].severity, HotSeverity::Severe);
+        // 89632 rowsets over 32 tablets
+        assert_eq!(hotspots[0].evidence[0].value.round(), 2801.0);
+        assert_eq!(hotspots[0].evidence[0].unit, MetricUnit::Ratio);
+    }
+
+    #[test]
+    fn test_index_findings_without_late_filtering() {
+        let issues = |metrics: &OlapScanSpecializedMetrics| -> Vec<IssueCode> {
+            OlapScanAnalyzer::analyze_funnel("scan", &NodeRef::default(), metrics).into_iter().map(|h| h.issue_type).collect()
+        };
+
+        // the short key index pruned well, yet predicates still discard 60% of what is read
+        let mut metrics = scan_metrics(20_000_000, 12_000_000, 0, 32, 32);
+        metrics.funnel.short_key_filter_rows = Some(200_000_000);
+        assert_eq!(issues(&metrics), [IssueCode::IneffectiveZoneMap, IssueCode::IneffectiveBloomFilter]);
+
+        metrics.bloom_filter_time = None;
+        assert_eq!(issues(&metrics), [IssueCode::IneffectiveZoneMap]);
+
+        // predicates keep almost every row: no index could have skipped anything
+        let mut metrics = scan_metrics(20_000_000, 1_000_000, 0, 32, 32);
+        metrics.funnel.short_key_filter_rows = Some(200_000_000);
+        assert!(issues(&metrics).is_empty());
+    }
+}
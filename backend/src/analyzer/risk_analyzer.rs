@@ -0,0 +1,181 @@
+use crate::constants::starrocks::DEFAULT_QUERY_TIMEOUT_SECS;
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::{tr, tr_list};
+
+/// Usage of a limit from which the query is at risk of failing on the next run.
+const WARNING_USAGE: f64 = 0.7;
+const CRITICAL_USAGE: f64 = 0.9;
+
+/// Proactive timeout and memory-limit risk for queries that finished: how much of
+/// `query_timeout` and `QueryMemoryLimit` they used and how much headroom is left.
+pub struct RiskAnalyzer;
+
+impl RiskAnalyzer {
+    pub fn report(profile: &Profile) -> Vec<RiskIndicator> {
+        [Self::timeout_risk(profile), Self::memory_risk(profile)]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn timeout_risk(profile: &Profile) -> Option<RiskIndicator> {
+        let summary = &profile.summary;
+        let elapsed_ms = summary.total_time_ms.filter(|ms| *ms > 0.0)?;
+        // INSERT is bounded by insert_timeout where the version reports it
+        let is_insert = summary.sql_statement.trim_start().to_uppercase().starts_with("INSERT");
+        let (source, timeout_secs) = ["insert_timeout", "query_timeout"].iter()
+            .filter(|name| is_insert || **name == "query_timeout")
+            .find_map(|name| Some((name.to_string(), summary.variables.get(*name)?.parse::<f64>().ok()?)))
+            .unwrap_or_else(|| ("query_timeout".to_string(), DEFAULT_QUERY_TIMEOUT_SECS as f64));
+        let limit_ms = timeout_secs * 1000.0;
+        if limit_ms <= 0.0 {
+            return None;
+        }
+
+        let usage = elapsed_ms / limit_ms;
+        let level = Self::level(usage);
+        let mut message = tr!(
+            "risk.timeout.message",
+            secs = elapsed_ms / 1000.0,
+            source = source,
+            timeout_secs = timeout_secs,
+            percent = usage * 100.0,
+            headroom = (1.0 - usage).max(0.0) * 100.0,
+        );
+        if summary.query_state == "Running" {
+            message.push_str(&tr!("risk.timeout.running"));
+        }
+        let suggestions = if level == RiskLevel::Safe {
+            Vec::new()
+        } else {
+            tr_list!("risk.timeout.suggestions", source = source, secs = (elapsed_ms / 1000.0 * 3.0).ceil())
+        };
+
+        Some(RiskIndicator {
+            kind: RiskKind::Timeout,
+            level,
+            limit_source: source,
+            used: elapsed_ms,
+            limit: limit_ms,
+            usage_percentage: usage * 100.0,
+            headroom_percentage: (1.0 - usage).max(0.0) * 100.0,
+            message,
+            suggestions,
+        })
+    }
+
+    fn memory_risk(profile: &Profile) -> Option<RiskIndicator> {
+        let (source, limit) = Self::memory_limit(profile)?;
+        let peak = profile.execution.metrics.get("QueryPeakMemoryUsagePerNode")
+            .and_then(|v| ValueParser::parse_bytes(v).ok())
+            .or_else(|| Self::max_instance_peak(profile))?;
+
+        let usage = peak as f64 / limit as f64;
+        let level = Self::level(usage);
+        let suggestions = if level == RiskLevel::Safe {
+            Vec::new()
+        } else {
+            tr_list!("risk.memory.suggestions", bytes = (peak as f64 * 1.5) as u64)
+        };
+
+        Some(RiskIndicator {
+            kind: RiskKind::MemoryLimit,
+            level,
+            limit_source: source,
+            used: peak as f64,
+            limit: limit as f64,
+            usage_percentage: usage * 100.0,
+            headroom_percentage: (1.0 - usage).max(0.0) * 100.0,
+            message: tr!(
+                "risk.memory.message",
+                peak = ValueParser::format_bytes(peak),
+                limit = ValueParser::format_bytes(limit),
+                percent = usage * 100.0,
+                headroom = (1.0 - usage).max(0.0) * 100.0,
+            ),
+            suggestions,
+        })
+    }
+
+    /// Per-node limit: `QueryMemoryLimit` of the fragments, else a positive `query_mem_limit`.
+    fn memory_limit(profile: &Profile) -> Option<(String, u64)> {
+        profile.fragments.iter()
+            .filter_map(|f| f.metrics.get("QueryMemoryLimit"))
+            .filter_map(|v| ValueParser::parse_bytes(v).ok())
+            .find(|limit| *limit > 0)
+            .map(|limit| ("QueryMemoryLimit".to_string(), limit))
+            .or_else(|| {
+                let limit = profile.summary.variables.get("query_mem_limit")?.parse::<u64>().ok()?;
+                (limit > 0).then(|| ("query_mem_limit".to_string(), limit))
+            })
+    }
+
+    fn max_instance_peak(profile: &Profile) -> Option<u64> {
+        profile.fragments.iter()
+            .filter_map(|f| f.metrics.get("__MAX_OF_InstancePeakMemoryUsage").or_else(|| f.metrics.get("InstancePeakMemoryUsage")))
+            .filter_map(|v| ValueParser::parse_bytes(v).ok())
+            .max()
+    }
+
+    fn level(usage: f64) -> RiskLevel {
+        match usage {
+            u if u >= CRITICAL_USAGE => RiskLevel::Critical,
+            u if u >= WARNING_USAGE => RiskLevel::Warning,
+            _ => RiskLevel::Safe,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, metrics, profile, with_pipelines};
+
+    fn risk_profile(total_ms: f64, variables: &[(&str, &str)], limit: &str, peak: &str) -> Profile {
+        let mut profile = profile(vec![with_pipelines("0", Vec::new())]);
+        profile.summary = ProfileSummary {
+            sql_statement: "SELECT 1".to_string(),
+            query_state: "Finished".to_string(),
+            total_time_ms: Some(total_ms),
+            variables: metrics(variables),
+            ..ProfileSummary::default()
+        };
+        profile.execution.metrics = metrics(&[("QueryPeakMemoryUsagePerNode", peak)]);
+        profile.fragments[0].metrics = metrics(&[("QueryMemoryLimit", limit)]);
+        profile
+    }
+
+    #[test]
+    fn test_timeout_risk() {
+        // 280s against the default 300s
+        let risks = RiskAnalyzer::report(&risk_profile(280_000.0, &[], "-1.000 B", "2.000 GB"));
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].level, RiskLevel::Critical);
+        assert!((risks[0].headroom_percentage - 6.666).abs() < 0.01);
+
+        // 44s against query_timeout=90000
+        let risks = RiskAnalyzer::report(&risk_profile(44_875.0, &[("query_timeout", "90000")], "-1.000 B", "2.000 GB"));
+        assert_eq!(risks[0].level, RiskLevel::Safe);
+        assert!(risks[0].suggestions.is_empty());
+    }
+
+    #[test]
+    fn test_memory_risk() {
+        let risks = RiskAnalyzer::report(&risk_profile(1_000.0, &[], "10.000 GB", "9.500 GB"));
+        let memory = risks.iter().find(|r| r.kind == RiskKind::MemoryLimit).unwrap();
+        assert_eq!(memory.level, RiskLevel::Critical);
+        assert_eq!(memory.limit_source, "QueryMemoryLimit");
+        assert!((memory.usage_percentage - 95.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_running_query_profile4() {
+        // 189s into a 90000s query_timeout, no memory limit set
+        let risks = RiskAnalyzer::report(&load("profile4.txt"));
+        assert_eq!(risks.len(), 1);
+        assert_eq!(risks[0].kind, RiskKind::Timeout);
+        assert_eq!(risks[0].level, RiskLevel::Safe);
+        assert_eq!(risks[0].used, 189_000.0);
+    }
+}
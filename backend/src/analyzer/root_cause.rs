@@ -0,0 +1,441 @@
+use std::collections::HashMap;
+
+use crate::i18n;
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::tr;
+
+/// At most this many root causes are reported.
+const MAX_ROOT_CAUSES: usize = 5;
+/// A slow scan only counts as a root cause when it explains this many hotspots.
+const MIN_SLOW_SCAN_HOTSPOTS: usize = 2;
+
+/// Hotspots that follow from memory pressure and the spilling it forces.
+const MEMORY_PRESSURE_ISSUES: &[IssueCode] = &[
+    IssueCode::OperatorSpill, IssueCode::DiskSpill, IssueCode::MemoryUsage, IssueCode::HighMemoryUsage,
+    IssueCode::HighJoinMemory, IssueCode::HighCardinalityAggregation, IssueCode::LocalExchangeMemory,
+];
+/// Probe-side scan hotspots caused by reading rows a runtime filter would have dropped.
+const UNFILTERED_SCAN_ISSUES: &[IssueCode] = &[
+    IssueCode::HighLatency, IssueCode::HighTimeCost, IssueCode::HighScanTime, IssueCode::ExcessiveScanTime,
+    IssueCode::HighIoTime, IssueCode::IoBottleneck, IssueCode::MissingPredicatePushdown,
+    IssueCode::LargeDataOutput, IssueCode::HighDataOutput, IssueCode::LargeExchange,
+];
+/// Hotspots of the single-input operators above a scan, such as a blocked exchange, that only
+/// inherit its slowness.
+const UPSTREAM_ISSUES: &[IssueCode] = &[IssueCode::HighLatency, IssueCode::HighTimeCost, IssueCode::LargeExchange];
+/// Query-level hotspots that a slow scan explains.
+const QUERY_IO_ISSUES: &[IssueCode] = &[IssueCode::IoBound, IssueCode::IoBottleneck];
+/// Joins whose probe side never receives runtime filters.
+const NO_RUNTIME_FILTER_JOINS: &[&str] = &["LEFT OUTER", "FULL OUTER", "LEFT ANTI", "NULL AWARE"];
+
+/// Execution tree lookups and the tree node each hotspot is located at.
+struct TreeIndex<'a> {
+    nodes: HashMap<&'a str, &'a ExecutionTreeNode>,
+    parents: HashMap<&'a str, &'a str>,
+    /// Tree node id per hotspot index, `None` for query-level hotspots.
+    located: Vec<Option<&'a str>>,
+}
+
+impl<'a> TreeIndex<'a> {
+    fn new(tree: &'a ExecutionTree, profile: &Profile, hotspots: &[HotSpot]) -> Self {
+        let nodes: HashMap<&str, &ExecutionTreeNode> = tree.nodes.iter().map(|n| (n.id.as_str(), n)).collect();
+        let parents = tree.nodes.iter()
+            .flat_map(|n| n.children.iter().map(move |child| (child.as_str(), n.id.as_str())))
+            .collect();
+        let located = hotspots.iter()
+            .map(|h| tree.locate(profile, h).map(|n| n.id.as_str()))
+            .collect();
+
+        TreeIndex { nodes, parents, located }
+    }
+
+    fn at(&self, index: usize, node_id: &str) -> bool {
+        self.located[index] == Some(node_id)
+    }
+
+    /// The hotspot is located at `node_id` or somewhere below it.
+    fn within(&self, index: usize, node_id: &str) -> bool {
+        let mut current = self.located[index];
+        while let Some(id) = current {
+            if id == node_id {
+                return true;
+            }
+            current = self.parents.get(id).copied();
+        }
+        false
+    }
+}
+
+/// Links related hotspots to the problem they most likely stem from: spilling under memory
+/// pressure, a hash join whose probe side got no runtime filter, or a slow scan that the
+/// exchanges above it and the query-level IO symptoms only reflect.
+pub struct RootCauseAnalyzer;
+
+impl RootCauseAnalyzer {
+    /// Each hotspot supports at most one root cause, claimed in the order spill, runtime filter,
+    /// slow scan.
+    pub fn analyze(profile: &Profile, hotspots: &[HotSpot]) -> Vec<RootCause> {
+        let mut claimed = vec![false; hotspots.len()];
+        let tree = profile.execution_tree.as_ref().map(|tree| (tree, TreeIndex::new(tree, profile, hotspots)));
+        let mut causes: Vec<RootCause> =
+            Self::spill(tree.as_ref().map(|(_, index)| index), hotspots, &mut claimed).into_iter().collect();
+
+        if let Some((tree, index)) = &tree {
+            causes.extend(Self::missing_runtime_filters(profile, tree, index, hotspots, &mut claimed));
+            causes.extend(Self::slow_scans(tree, index, hotspots, &mut claimed));
+        }
+
+        causes.sort_by(|a, b| b.severity.cmp(&a.severity).then(b.hotspots.len().cmp(&a.hotspots.len())));
+        causes.truncate(MAX_ROOT_CAUSES);
+        causes
+    }
+
+    /// The memory hotspots of the worst spilling operator: its own, those of its plan node, those
+    /// below it in the execution tree, and the query-level ones.
+    fn spill(index: Option<&TreeIndex>, hotspots: &[HotSpot], claimed: &mut [bool]) -> Option<RootCause> {
+        let (root_index, root) = hotspots.iter().enumerate()
+            .filter(|(_, h)| matches!(h.issue_type, IssueCode::OperatorSpill | IssueCode::DiskSpill))
+            .max_by_key(|(_, h)| (h.severity.clone(), h.node.plan_node_id.is_some()))?;
+        let root_node = index.and_then(|index| index.located[root_index]);
+        let members = Self::claim(hotspots, claimed, |i, h| {
+            let query_level = h.node.plan_node_id.is_none() && h.node.tree_node_id.is_none() && h.node.operator_name.is_none();
+            let related = i == root_index
+                || query_level
+                || (h.node.plan_node_id.is_some() && h.node.plan_node_id == root.node.plan_node_id)
+                || index.zip(root_node).is_some_and(|(index, node_id)| index.within(i, node_id));
+            related && MEMORY_PRESSURE_ISSUES.contains(&h.issue_type)
+        });
+        let operator = root.node.operator_name.clone().unwrap_or_else(|| root.node_path.clone());
+
+        Some(Self::cause(
+            RootCauseKind::SpillMemoryPressure,
+            root.node_path.clone(),
+            root.node.clone(),
+            tr!("root_cause.spill", operator = operator, count = members.len()),
+            hotspots,
+            members,
+        ))
+    }
+
+    fn missing_runtime_filters(
+        profile: &Profile,
+        tree: &ExecutionTree,
+        index: &TreeIndex,
+        hotspots: &[HotSpot],
+        claimed: &mut [bool],
+    ) -> Vec<RootCause> {
+        let mut causes = Vec::new();
+        for join in tree.nodes.iter().filter(|n| n.operator_name.contains("HASH_JOIN")) {
+            let Some(plan_node_id) = join.plan_node_id else {
+                continue;
+            };
+            if Self::join_type(profile, plan_node_id).is_some_and(|t| NO_RUNTIME_FILTER_JOINS.iter().any(|j| t.contains(j))) {
+                continue;
+            }
+            // the parser lists the probe (left) input of a join first
+            let Some(scan) = join.children.first().and_then(|child| Self::first_scan(index, child)) else {
+                continue;
+            };
+            if !scan.plan_node_id.is_some_and(|id| Self::received_no_runtime_filter(profile, id)) {
+                continue;
+            }
+
+            let members = Self::claim(hotspots, claimed, |i, h| {
+                index.at(i, &join.id) || (index.at(i, &scan.id) && UNFILTERED_SCAN_ISSUES.contains(&h.issue_type))
+            });
+            if members.is_empty() {
+                continue;
+            }
+            causes.push(Self::cause(
+                RootCauseKind::MissingRuntimeFilter,
+                Self::node_path(join),
+                NodeRef::tree_node(join),
+                tr!("root_cause.missing_runtime_filter", join = Self::label(join), scan = Self::label(scan), count = members.len()),
+                hotspots,
+                members,
+            ));
+        }
+        causes
+    }
+
+    fn slow_scans(tree: &ExecutionTree, index: &TreeIndex, hotspots: &[HotSpot], claimed: &mut [bool]) -> Vec<RootCause> {
+        let mut candidates: Vec<(&ExecutionTreeNode, Vec<usize>)> = tree.nodes.iter()
+            .filter(|n| Self::is_scan(n))
+            .map(|scan| {
+                let upstream = Self::single_input_ancestors(index, &scan.id);
+                let members = (0..hotspots.len())
+                    .filter(|&i| !claimed[i])
+                    .filter(|&i| {
+                        index.at(i, &scan.id)
+                            || (upstream.iter().any(|id| index.at(i, id)) && UPSTREAM_ISSUES.contains(&hotspots[i].issue_type))
+                    })
+                    .collect();
+                (scan, members)
+            })
+            .filter(|(scan, members): &(&ExecutionTreeNode, Vec<usize>)| members.iter().any(|&i| index.at(i, &scan.id)))
+            .collect();
+        candidates.sort_by_key(|(_, members)| std::cmp::Reverse(Self::max_severity(hotspots, members)));
+
+        // the query-level IO symptoms go to the worst scan
+        if let Some((_, members)) = candidates.first_mut() {
+            members.extend((0..hotspots.len()).filter(|&i| {
+                !claimed[i] && index.located[i].is_none() && QUERY_IO_ISSUES.contains(&hotspots[i].issue_type)
+            }));
+        }
+
+        candidates.into_iter()
+            .filter(|(_, members)| members.len() >= MIN_SLOW_SCAN_HOTSPOTS)
+            .map(|(scan, members)| {
+                for &i in &members {
+                    claimed[i] = true;
+                }
+                let mut issues: Vec<String> = Vec::new();
+                for &i in members.iter().filter(|&&i| index.at(i, &scan.id)) {
+                    let title = i18n::issue_title(&hotspots[i].issue_type);
+                    if !issues.contains(&title) {
+                        issues.push(title);
+                    }
+                }
+                Self::cause(
+                    RootCauseKind::SlowScan,
+                    Self::node_path(scan),
+                    NodeRef::tree_node(scan),
+                    tr!("root_cause.slow_scan", scan = Self::label(scan), issues = issues.join(", "), count = members.len()),
+                    hotspots,
+                    members,
+                )
+            })
+            .collect()
+    }
+
+    /// Marks and returns the unclaimed hotspots matching `supports`.
+    fn claim(hotspots: &[HotSpot], claimed: &mut [bool], supports: impl Fn(usize, &HotSpot) -> bool) -> Vec<usize> {
+        let members: Vec<usize> = hotspots.iter().enumerate()
+            .filter(|(i, h)| !claimed[*i] && supports(*i, h))
+            .map(|(i, _)| i)
+            .collect();
+        for &i in &members {
+            claimed[i] = true;
+        }
+        members
+    }
+
+    fn cause(
+        kind: RootCauseKind,
+        node_path: String,
+        node: NodeRef,
+        description: String,
+        hotspots: &[HotSpot],
+        members: Vec<usize>,
+    ) -> RootCause {
+        RootCause {
+            kind,
+            node_path,
+            node,
+            severity: Self::max_severity(hotspots, &members),
+            description,
+            hotspots: members.into_iter()
+                .map(|index| HotspotRef {
+                    index,
+                    issue_type: hotspots[index].issue_type.clone(),
+                    node_path: hotspots[index].node_path.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    fn max_severity(hotspots: &[HotSpot], members: &[usize]) -> HotSeverity {
+        members.iter().map(|&i| hotspots[i].severity.clone()).max().unwrap_or(HotSeverity::Normal)
+    }
+
+    /// The ancestors whose only input is the scan's subtree, up to the first join or union.
+    fn single_input_ancestors<'a>(index: &TreeIndex<'a>, node_id: &str) -> Vec<&'a str> {
+        let mut ancestors = Vec::new();
+        let mut current = node_id;
+        while let Some(&parent) = index.parents.get(current) {
+            if index.nodes.get(parent).is_none_or(|p| p.children.len() > 1) {
+                break;
+            }
+            ancestors.push(parent);
+            current = parent;
+        }
+        ancestors
+    }
+
+    /// The scan feeding a join input through exchanges and other single-input operators.
+    fn first_scan<'a>(index: &TreeIndex<'a>, node_id: &str) -> Option<&'a ExecutionTreeNode> {
+        let node = *index.nodes.get(node_id)?;
+        if Self::is_scan(node) {
+            return Some(node);
+        }
+        match node.children.as_slice() {
+            [child] => Self::first_scan(index, child),
+            _ => None,
+        }
+    }
+
+    fn is_scan(node: &ExecutionTreeNode) -> bool {
+        matches!(node.node_type, NodeType::OlapScan | NodeType::ConnectorScan) || node.operator_name.ends_with("_SCAN")
+    }
+
+    /// `JoinType` of the join's probe or build operator, e.g. `LEFT OUTER JOIN`.
+    fn join_type(profile: &Profile, plan_node_id: i32) -> Option<String> {
+        Self::operators(profile, plan_node_id)
+            .filter(|op| op.name.starts_with("HASH_JOIN"))
+            .find_map(|op| op.unique_metrics.get("JoinType"))
+            .map(|t| t.replace('_', " ").to_ascii_uppercase())
+    }
+
+    /// Every scan operator of the plan node reports `RuntimeFilterNum: 0`.
+    fn received_no_runtime_filter(profile: &Profile, plan_node_id: i32) -> bool {
+        let counts: Vec<u64> = Self::operators(profile, plan_node_id)
+            .filter(|op| op.name.contains("SCAN"))
+            .filter_map(|op| op.common_metrics.get("RuntimeFilterNum"))
+            .filter_map(|v| ValueParser::parse_number(v).ok())
+            .collect();
+        !counts.is_empty() && counts.iter().all(|&n| n == 0)
+    }
+
+    fn operators(profile: &Profile, plan_node_id: i32) -> impl Iterator<Item = &Operator> {
+        let plan_node_id = plan_node_id.to_string();
+        profile.fragments.iter()
+            .flat_map(|f| &f.pipelines)
+            .flat_map(|p| &p.operators)
+            .filter(move |op| op.plan_node_id.as_deref() == Some(plan_node_id.as_str()))
+    }
+
+    fn node_path(node: &ExecutionTreeNode) -> String {
+        format!("{} ({})", node.operator_name, node.id)
+    }
+
+    fn label(node: &ExecutionTreeNode) -> String {
+        match node.plan_node_id {
+            Some(id) => format!("{} (plan_node_id={})", node.operator_name, id),
+            None => node.operator_name.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{hotspot, load, operator, pipeline, profile, tree, tree_node, with_pipelines};
+
+    /// `RESULT_SINK <- EXCHANGE <- HASH_JOIN(probe: OLAP_SCAN 3, build: OLAP_SCAN 4)`
+    fn join_profile(join_type: &str, probe_filters: &str) -> Profile {
+        let mut profile = profile(vec![with_pipelines("0", vec![pipeline("0", &[], vec![
+            operator("HASH_JOIN_PROBE", 2, &[], &[("JoinType", join_type)]),
+            operator("OLAP_SCAN", 3, &[("RuntimeFilterNum", probe_filters)], &[]),
+            operator("OLAP_SCAN", 4, &[("RuntimeFilterNum", "0")], &[]),
+        ])])]);
+        profile.execution_tree = Some(tree(vec![
+            tree_node(0, "RESULT_SINK", &[1]),
+            tree_node(1, "EXCHANGE", &[2]),
+            tree_node(2, "HASH_JOIN", &[3, 4]),
+            tree_node(3, "OLAP_SCAN", &[]),
+            tree_node(4, "OLAP_SCAN", &[]),
+        ]));
+        profile
+    }
+
+    fn hotspot_at(issue_type: IssueCode, severity: HotSeverity, plan_node_id: Option<i32>) -> HotSpot {
+        HotSpot {
+            node_path: plan_node_id.map(|id| format!("node_{}", id)).unwrap_or_else(|| "Query".to_string()),
+            node: NodeRef { plan_node_id, ..NodeRef::default() },
+            ..hotspot(issue_type, severity)
+        }
+    }
+
+    fn hotspots() -> Vec<HotSpot> {
+        vec![
+            hotspot_at(IssueCode::HighTimeCost, HotSeverity::Severe, Some(2)),
+            hotspot_at(IssueCode::HighScanTime, HotSeverity::Moderate, Some(3)),
+            hotspot_at(IssueCode::RowsetFragmentation, HotSeverity::Moderate, Some(3)),
+            hotspot_at(IssueCode::HighLatency, HotSeverity::Mild, Some(1)),
+            hotspot_at(IssueCode::IoBound, HotSeverity::Mild, None),
+        ]
+    }
+
+    fn indexes(cause: &RootCause) -> Vec<usize> {
+        cause.hotspots.iter().map(|h| h.index).collect()
+    }
+
+    #[test]
+    fn test_missing_runtime_filter() {
+        let causes = RootCauseAnalyzer::analyze(&join_profile("INNER JOIN", "0"), &hotspots());
+        assert_eq!(causes.len(), 2);
+        assert_eq!(causes[0].kind, RootCauseKind::MissingRuntimeFilter);
+        assert_eq!(causes[0].node.tree_node_id.as_deref(), Some("node_2"));
+        assert_eq!(causes[0].severity, HotSeverity::Severe);
+        assert_eq!(indexes(&causes[0]), [0, 1]);
+        // the fragmentation is the scan's own problem and no runtime filter would fix it
+        assert_eq!(causes[1].kind, RootCauseKind::SlowScan);
+        assert_eq!(indexes(&causes[1]), [2, 4]);
+    }
+
+    #[test]
+    fn test_slow_scan_behind_exchange() {
+        let mut hotspots = hotspots();
+        hotspots.remove(0);
+        let causes = RootCauseAnalyzer::analyze(&join_profile("LEFT OUTER JOIN", "0"), &hotspots);
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].kind, RootCauseKind::SlowScan);
+        assert_eq!(causes[0].node.plan_node_id, Some(3));
+        // the exchange is separated from the scan by the join
+        assert_eq!(indexes(&causes[0]), [0, 1, 3]);
+
+        let mut tree_profile = join_profile("INNER JOIN", "1");
+        let tree = tree_profile.execution_tree.as_mut().unwrap();
+        tree.nodes[2].children = vec!["node_3".to_string()];
+        let causes = RootCauseAnalyzer::analyze(&tree_profile, &hotspots);
+        assert_eq!(indexes(&causes[0]), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_spill_without_tree() {
+        let mut profile = join_profile("INNER JOIN", "0");
+        profile.execution_tree = None;
+        let hotspots = vec![
+            hotspot_at(IssueCode::HighMemoryUsage, HotSeverity::Moderate, Some(2)),
+            hotspot_at(IssueCode::OperatorSpill, HotSeverity::Severe, Some(2)),
+            hotspot_at(IssueCode::HighScanTime, HotSeverity::Moderate, Some(3)),
+        ];
+        let causes = RootCauseAnalyzer::analyze(&profile, &hotspots);
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].kind, RootCauseKind::SpillMemoryPressure);
+        assert_eq!(indexes(&causes[0]), [0, 1]);
+    }
+
+    #[test]
+    fn test_spill_ignores_unrelated_memory_hotspots() {
+        let hotspots = vec![
+            hotspot_at(IssueCode::OperatorSpill, HotSeverity::Severe, Some(2)),
+            hotspot_at(IssueCode::HighMemoryUsage, HotSeverity::Moderate, Some(3)),
+            hotspot_at(IssueCode::LocalExchangeMemory, HotSeverity::Moderate, Some(1)),
+            hotspot_at(IssueCode::MemoryUsage, HotSeverity::Mild, None),
+        ];
+        let causes = RootCauseAnalyzer::analyze(&join_profile("LEFT OUTER JOIN", "0"), &hotspots);
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].kind, RootCauseKind::SpillMemoryPressure);
+        // the scan feeds the spilling join; the exchange above it has a memory problem of its own
+        assert_eq!(indexes(&causes[0]), [0, 1, 3]);
+
+        let mut profile = join_profile("LEFT OUTER JOIN", "0");
+        profile.execution_tree = None;
+        let causes = RootCauseAnalyzer::analyze(&profile, &hotspots);
+        assert_eq!(indexes(&causes[0]), [0, 3]);
+    }
+
+    #[test]
+    fn test_slow_scan_profile1() {
+        // every finding on profile1 comes back to the cold, fragmented scan
+        let profile = load("profile1.txt");
+        let hotspots = crate::HotSpotDetector::analyze(&profile);
+        let causes = RootCauseAnalyzer::analyze(&profile, &hotspots);
+        assert_eq!(causes.len(), 1);
+        assert_eq!(causes[0].kind, RootCauseKind::SlowScan);
+        assert_eq!(causes[0].node.plan_node_id, Some(0));
+        assert_eq!(causes[0].hotspots.len(), hotspots.len());
+    }
+}
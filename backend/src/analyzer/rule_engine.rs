@@ -0,0 +1,338 @@
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::rules::{CompiledRule, Expr, RuleScope, RuleSet, ValueFormat};
+
+/// Evaluates the declarative hotspot rules (`crate::rules`) against a query, its execution
+/// tree nodes and its pipeline operators.
+pub struct RuleEngine;
+
+impl RuleEngine {
+    pub fn evaluate_query(rules: &RuleSet, profile: &Profile) -> Vec<HotSpot> {
+        let vars = |name: &str| Self::query_var(profile, name);
+        rules.rules_for(RuleScope::Query)
+            .filter_map(|rule| {
+                let node_path = rule.definition.node_path.clone().unwrap_or_else(|| "Query".to_string());
+                Self::fire(rule, &vars, "", node_path, NodeRef::default())
+            })
+            .collect()
+    }
+
+    pub fn evaluate_node(rules: &RuleSet, node: &ExecutionTreeNode, profile: &Profile) -> Vec<HotSpot> {
+        let vars = |name: &str| match name.split_once('.') {
+            Some(("node", field)) => Self::node_var(node, field),
+            Some(("scan", field)) => Self::scan_var(node, field),
+            _ => Self::query_var(profile, name),
+        };
+        rules.rules_for(RuleScope::Node)
+            .filter(|rule| rule.applies_to(&node.operator_name))
+            .filter_map(|rule| {
+                let node_path = format!("{} ({})", node.operator_name, node.id);
+                Self::fire(rule, &vars, &node.operator_name, node_path, NodeRef::tree_node(node))
+            })
+            .collect()
+    }
+
+    pub fn evaluate_operator(rules: &RuleSet, fragment_id: &str, pipeline_id: &str, operator: &Operator, profile: &Profile) -> Vec<HotSpot> {
+        let vars = |name: &str| match name.split_once('.') {
+            Some(("common", counter)) => operator.common_metrics.get(counter).and_then(|v| Self::counter_value(v)),
+            Some(("unique", counter)) => operator.unique_metrics.get(counter).and_then(|v| Self::counter_value(v)),
+            _ => Self::query_var(profile, name),
+        };
+        rules.rules_for(RuleScope::Operator)
+            .filter(|rule| rule.applies_to(&operator.name))
+            .filter_map(|rule| {
+                let node_path = format!("Fragment{}.Pipeline{}.{}", fragment_id, pipeline_id, operator.name);
+                Self::fire(rule, &vars, &operator.name, node_path, NodeRef::operator(fragment_id, pipeline_id, operator))
+            })
+            .collect()
+    }
+
+    fn fire(rule: &CompiledRule, vars: &dyn Fn(&str) -> Option<f64>, operator_name: &str, node_path: String, node: NodeRef) -> Option<HotSpot> {
+        if let Some(when) = &rule.when {
+            if when.eval(vars).unwrap_or(0.0) == 0.0 {
+                return None;
+            }
+        }
+        let value = rule.metric.eval(vars)?;
+        let level = rule.level_for(value)?;
+
+        let with_value = |name: &str| if name == "value" { Some(value) } else { vars(name) };
+        Some(HotSpot {
+            node_path,
+            node,
+            severity: level.severity.clone(),
+            issue_type: rule.definition.issue_type.clone(),
+            confidence: rule.definition.confidence,
+            description: Self::render(level.message.current(), &with_value, operator_name, rule.definition.format),
+            suggestions: level.suggestions.current().iter()
+                .map(|s| Self::render(s, &with_value, operator_name, rule.definition.format))
+                .collect(),
+            evidence: vec![Evidence {
+                glossary: Self::glossary(&rule.definition.metric),
+                ..Evidence::new(&rule.definition.metric, value, rule.definition.format.unit()).threshold(level.threshold)
+            }],
+        })
+    }
+
+    /// Glossary entries of the counters behind a rule metric: `common.` / `unique.` variables
+    /// name their counter, the others are translated by `counter_of`.
+    fn glossary(metric: &str) -> Vec<String> {
+        let counters: Vec<&str> = metric.split(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '.'))
+            .map(|word| Self::counter_of(word).unwrap_or(word))
+            .collect();
+        crate::glossary::references(&counters.join(" "))
+    }
+
+    /// The profile counter a `query.*`, `node.*` or `scan.*` variable is read from.
+    fn counter_of(var: &str) -> Option<&'static str> {
+        Some(match var {
+            "query.wall_time_ms" => "QueryExecutionWallTime",
+            "query.cpu_time_ms" => "QueryCumulativeCpuTime",
+            "query.scan_time_ms" => "QueryCumulativeScanTime",
+            "query.network_time_ms" => "QueryCumulativeNetworkTime",
+            "query.operator_time_ms" => "QueryCumulativeOperatorTime",
+            "query.result_deliver_time_ms" => "ResultDeliverTime",
+            "query.total_dop" => "TotalDegreeOfParallelism",
+            "node.operator_time_ms" => "OperatorTotalTime",
+            "node.output_chunk_bytes" => "OutputChunkBytes",
+            "node.memory_usage" => "MemoryUsage",
+            "node.push_rows" => "PushRowNum",
+            "node.pull_rows" => "PullRowNum",
+            "scan.io_time_ms" => "IOTime",
+            "scan.scan_time_ms" => "ScanTime",
+            _ => return None,
+        })
+    }
+
+    /// Replaces `{value}`, `{operator}` and `{expression|format}` placeholders.
+    fn render(template: &str, vars: &dyn Fn(&str) -> Option<f64>, operator_name: &str, value_format: ValueFormat) -> String {
+        let mut out = String::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let Some(len) = rest[start..].find('}') else {
+                break;
+            };
+            out.push_str(&rest[..start]);
+            let placeholder = &rest[start + 1..start + len];
+            let (expression, format) = match placeholder.rsplit_once('|') {
+                Some((expression, format)) => (expression.trim(), Self::parse_format(format.trim())),
+                None => (placeholder.trim(), None),
+            };
+
+            if expression == "operator" {
+                out.push_str(operator_name);
+            } else {
+                let format = format.unwrap_or(if expression == "value" { value_format } else { ValueFormat::Number });
+                match Expr::parse(expression).ok().and_then(|e| e.eval(vars)) {
+                    Some(v) => out.push_str(&Self::format_value(v, format)),
+                    None => out.push('?'),
+                }
+            }
+            rest = &rest[start + len + 1..];
+        }
+        out.push_str(rest);
+        out
+    }
+
+    fn parse_format(name: &str) -> Option<ValueFormat> {
+        match name {
+            "number" => Some(ValueFormat::Number),
+            "integer" => Some(ValueFormat::Integer),
+            "percent" => Some(ValueFormat::Percent),
+            "seconds" => Some(ValueFormat::Seconds),
+            "bytes" => Some(ValueFormat::Bytes),
+            _ => None,
+        }
+    }
+
+    fn format_value(value: f64, format: ValueFormat) -> String {
+        match format {
+            ValueFormat::Number if value.fract() == 0.0 => format!("{:.0}", value),
+            ValueFormat::Number => format!("{:.2}", value),
+            ValueFormat::Integer => format!("{:.0}", value),
+            ValueFormat::Percent => format!("{:.1}%", value * 100.0),
+            ValueFormat::Seconds => format!("{:.2}s", value / 1000.0),
+            ValueFormat::Bytes => {
+                const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+                let mut size = value;
+                let mut unit_index = 0;
+                while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+                    size /= 1024.0;
+                    unit_index += 1;
+                }
+                format!("{:.2} {}", size, UNITS[unit_index])
+            }
+        }
+    }
+
+    /// Counter text to a number: durations in ms, sizes in bytes, counts as is.
+    fn counter_value(raw: &str) -> Option<f64> {
+        let raw = raw.trim();
+        if raw.ends_with('B') {
+            ValueParser::parse_bytes(raw).ok().map(|b| b as f64)
+        } else if raw.chars().any(|c| c.is_ascii_lowercase() || c == 'μ') {
+            ValueParser::parse_time_to_ms(raw).ok()
+        } else {
+            ValueParser::parse_number::<f64>(raw).ok()
+        }
+    }
+
+    pub(crate) fn query_var(profile: &Profile, name: &str) -> Option<f64> {
+        let summary = &profile.summary;
+        match name {
+            "query.total_time_ms" => summary.total_time_ms,
+            "query.wall_time_ms" => summary.query_execution_wall_time_ms,
+            "query.cpu_time_ms" => summary.query_cumulative_cpu_time_ms,
+            "query.scan_time_ms" => summary.query_cumulative_scan_time_ms,
+            "query.network_time_ms" => summary.query_cumulative_network_time_ms,
+            "query.operator_time_ms" => summary.query_cumulative_operator_time_ms,
+            "query.result_deliver_time_ms" => summary.result_deliver_time_ms,
+            "query.total_dop" => summary.total_degree_of_parallelism.map(|d| d as f64),
+            "query.cpu_efficiency" => summary.cpu_efficiency,
+            _ => None,
+        }
+    }
+
+    fn node_var(node: &ExecutionTreeNode, field: &str) -> Option<f64> {
+        let metrics = &node.metrics;
+        match field {
+            // operator_total_time is in nanoseconds
+            "operator_time_ms" => metrics.operator_total_time.map(|ns| ns as f64 / 1_000_000.0),
+            "time_percentage" => node.time_percentage,
+            "output_chunk_bytes" => metrics.output_chunk_bytes.map(|b| b as f64),
+            "memory_usage" => metrics.memory_usage.map(|b| b as f64),
+            "push_rows" => metrics.push_row_num.map(|r| r as f64),
+            "pull_rows" => metrics.pull_row_num.map(|r| r as f64),
+            _ => None,
+        }
+    }
+
+    fn scan_var(node: &ExecutionTreeNode, field: &str) -> Option<f64> {
+        let OperatorSpecializedMetrics::ConnectorScan(scan) = &node.metrics.specialized else {
+            return None;
+        };
+        let ms = |d: Option<std::time::Duration>| d.map(|d| d.as_millis() as f64);
+        match field {
+            "io_time_ms" => ms(scan.io_time),
+            "scan_time_ms" => ms(scan.scan_time),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, operator, profile, tree_node};
+    use crate::rules::RuleFile;
+
+    fn scan_operator(unique: &[(&str, &str)]) -> Operator {
+        operator("CONNECTOR_SCAN", 0, &[("ScanTime", "40m"), ("DegreeOfParallelism", "1")], unique)
+    }
+
+    fn long_running() -> Profile {
+        let mut profile = profile(Vec::new());
+        profile.summary.total_time_ms = Some(2.0 * 3_600_000.0);
+        profile
+    }
+
+    #[test]
+    fn test_builtin_operator_rules() {
+        let rules = RuleSet::builtin();
+        let operator = scan_operator(&[("SegmentsReadCount", "60.000K (60000)"), ("RawRowsRead", "200000")]);
+        let hotspots = RuleEngine::evaluate_operator(&rules, "1", "0", &operator, &long_running());
+
+        let issue = |t: IssueCode| hotspots.iter().find(|h| h.issue_type == t);
+        let segments = issue(IssueCode::RowsetFragmentation).unwrap();
+        assert_eq!(segments.severity, HotSeverity::Severe);
+        assert_eq!(segments.description, "大量元信息段需要读取: 60000 个 - 表碎片化严重");
+        assert_eq!(segments.node.fragment_id.as_deref(), Some("1"));
+        assert_eq!(segments.node.plan_node_id, Some(0));
+        assert_eq!(segments.evidence[0], Evidence::new("unique.SegmentsReadCount", 60000.0, MetricUnit::Count).threshold(50000.0));
+        assert_eq!(issue(IssueCode::HighScanTime).unwrap().description, "扫描操作耗时过长: 2400.00s");
+        assert_eq!(issue(IssueCode::MissingPredicatePushdown).unwrap().severity, HotSeverity::Severe);
+        assert!(issue(IssueCode::InsufficientParallelism).is_some());
+        assert!(issue(IssueCode::ExcessiveScanTime).is_none());
+
+        let query = RuleEngine::evaluate_query(&rules, &long_running());
+        assert_eq!(query[0].issue_type, IssueCode::LongRunning);
+        assert_eq!(query[0].node_path, "Query");
+    }
+
+    #[test]
+    fn test_custom_rule_and_template() {
+        let file = RuleFile::parse(r#"
+[[rules]]
+id = "filtered_scan"
+scope = "operator"
+operators = ["CONNECTOR_*"]
+issue_type = "LowSelectivity"
+metric = "unique.RowsRead / unique.RawRowsRead"
+direction = "below"
+format = "percent"
+levels = [{ severity = "Mild", threshold = 0.1, message = "{operator} 只保留了 {value} 的行 ({unique.RowsRead}/{unique.RawRowsRead|integer})" }]
+"#).unwrap();
+        let rules = RuleSet::compile(file).unwrap();
+        let operator = scan_operator(&[("RowsRead", "500"), ("RawRowsRead", "10.000K (10000)")]);
+        let hotspots = RuleEngine::evaluate_operator(&rules, "1", "0", &operator, &long_running());
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::Custom("LowSelectivity".to_string()));
+        assert_eq!(hotspots[0].description, "CONNECTOR_SCAN 只保留了 5.0% 的行 (500/10000)");
+    }
+
+    #[test]
+    fn test_localized_rule_messages() {
+        let operator = scan_operator(&[("SegmentsReadCount", "60.000K (60000)")]);
+        let segments = |lang| crate::i18n::with_lang(lang, || {
+            RuleEngine::evaluate_operator(&RuleSet::builtin(), "1", "0", &operator, &long_running())
+                .into_iter()
+                .find(|h| h.issue_type == IssueCode::RowsetFragmentation)
+                .unwrap()
+        });
+        let en = segments(crate::i18n::Lang::En);
+        assert_eq!(en.description, "Many segments have to be read: 60000 - the table is badly fragmented");
+        assert_eq!(en.suggestions[0], "Run a compaction first");
+        assert_eq!(segments(crate::i18n::Lang::Zh).suggestions[0], "优先执行compaction操作");
+    }
+
+    #[test]
+    fn test_node_latency_without_wall_time() {
+        let mut node = tree_node(3, "HASH_JOIN", &[]);
+        node.metrics.operator_total_time = Some(90_000_000_000);
+        let latency = |profile: &Profile| RuleEngine::evaluate_node(&RuleSet::builtin(), &node, profile)
+            .into_iter()
+            .find(|h| h.issue_type == IssueCode::HighLatency)
+            .map(|h| h.severity);
+
+        // No QueryExecutionWallTime: the share is taken of the total query time
+        let mut profile = profile(Vec::new());
+        profile.summary.total_time_ms = Some(100_000.0);
+        assert_eq!(latency(&profile), Some(HotSeverity::Critical));
+
+        profile.summary.query_execution_wall_time_ms = Some(150_000.0);
+        assert_eq!(latency(&profile), Some(HotSeverity::Severe));
+    }
+
+    #[test]
+    fn test_evidence_glossary_of_variables() {
+        assert_eq!(
+            RuleEngine::glossary("node.operator_time_ms / default(query.wall_time_ms, query.total_time_ms)"),
+            ["OperatorTotalTime", "QueryExecutionWallTime"],
+        );
+        assert_eq!(RuleEngine::glossary("node.output_chunk_bytes"), ["OutputChunkBytes"]);
+        assert_eq!(RuleEngine::glossary("unique.RowsRead / unique.RawRowsRead"), ["RowsRead", "RawRowsRead"]);
+    }
+
+    #[test]
+    fn test_fragmented_scan_profile1() {
+        let profile = load("profile1.txt");
+        let (fragment, pipeline, scan) = profile.fragments.iter()
+            .flat_map(|f| f.pipelines.iter().map(move |p| (f, p)))
+            .find_map(|(f, p)| p.operators.iter().find(|op| op.name == "CONNECTOR_SCAN").map(|op| (f, p, op)))
+            .unwrap();
+        let hotspots = RuleEngine::evaluate_operator(&RuleSet::builtin(), &fragment.id, &pipeline.id, scan, &profile);
+        let segments = hotspots.iter().find(|h| h.issue_type == IssueCode::RowsetFragmentation).unwrap();
+        assert_eq!(segments.severity, HotSeverity::Severe);
+        assert_eq!(segments.evidence[0].value, 89600.0);
+    }
+}
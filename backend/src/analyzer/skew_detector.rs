@@ -0,0 +1,433 @@
+use crate::constants::starrocks::{MERGED_INFO_PREFIX_MAX, MERGED_INFO_PREFIX_MIN};
+use crate::models::*;
+use crate::parser::core::ValueParser;
+use crate::tr;
+use crate::tr_list;
+use std::collections::HashMap;
+
+/// Counters whose spread only mirrors skew elsewhere (waiting / scheduling), never the cause.
+const SYMPTOM_COUNTER_KEYWORDS: &[&str] = &[
+    "Wait", "Pending", "InputEmpty", "OutputFull", "Schedule", "Yield", "Queue", "Block",
+    "RuntimeFilter", "ProfileMerge", "InitialProcess",
+];
+
+/// Sources that funnel all rows through one driver by design (ordered merge / gather).
+const GATHER_SOURCES: &[&str] = &["MERGE_EXCHANGE", "GLOBAL_PARALLEL_MERGE_SOURCE", "LOCAL_MERGE_SOURCE"];
+
+/// max/avg ratio from which a counter is reported as skewed.
+pub(crate) const MILD_SKEW_RATIO: f64 = 2.0;
+const MODERATE_SKEW_RATIO: f64 = 4.0;
+const SEVERE_SKEW_RATIO: f64 = 10.0;
+/// Suffix of the per-counter max/avg ratio evidence, `ScanTime max/avg`.
+pub(crate) const SKEW_RATIO_EVIDENCE_SUFFIX: &str = " max/avg";
+
+/// Skew on tiny values is noise; the slowest/largest instance has to be at least this big.
+const MIN_SKEWED_TIME_MS: f64 = 1000.0;
+const MIN_SKEWED_BYTES: f64 = 64.0 * 1024.0 * 1024.0;
+const MIN_SKEWED_COUNT: f64 = 1_000_000.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CounterKind {
+    Time,
+    Bytes,
+    Count,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SkewSource {
+    Scan,
+    Shuffle,
+    Aggregation,
+    Other,
+}
+
+#[derive(Debug, Clone)]
+struct CounterSkew {
+    counter: String,
+    kind: CounterKind,
+    max: f64,
+    min: f64,
+    avg: f64,
+    ratio: f64,
+}
+
+/// Instance-level skew detection driven by the `__MAX_OF_` / `__MIN_OF_` companions that
+/// StarRocks attaches to merged counters.
+///
+/// The merged value is an average for time counters and for counters that fall between min
+/// and max (peak memory); otherwise it is the sum over all instances and is divided by the
+/// number of instances (pipeline DOP, or InstanceNum for fragment counters), then kept within
+/// min and max. Counters on which every instance agrees (max == min) are never skewed.
+pub struct SkewDetector;
+
+impl SkewDetector {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let mut hotspots = Vec::new();
+
+        for fragment in &profile.fragments {
+            let instance_num = fragment.metrics.get("InstanceNum")
+                .and_then(|v| ValueParser::parse_number::<u64>(v).ok())
+                .or_else(|| (!fragment.instance_ids.is_empty()).then_some(fragment.instance_ids.len() as u64));
+            let skews = Self::collect_skews(&fragment.metrics, instance_num);
+            let node = NodeRef { fragment_id: Some(fragment.id.clone()), ..NodeRef::default() };
+            hotspots.extend(Self::build_hotspot(format!("Fragment{}", fragment.id), node, "Fragment", SkewSource::Other, skews, &[]));
+
+            for pipeline in &fragment.pipelines {
+                let dop = Self::pipeline_dop(pipeline);
+                let is_gather = pipeline.operators.last()
+                    .map(|op| GATHER_SOURCES.contains(&op.name.as_str()))
+                    .unwrap_or(false);
+                if is_gather {
+                    continue;
+                }
+
+                // operators are listed sink first; walk from the source so skew is reported
+                // where it enters the pipeline and the operators it propagates to are listed
+                let mut skewed = pipeline.operators.iter().rev().filter(|op| Self::is_slow(op)).filter_map(|operator| {
+                    let skews = Self::collect_skews(&Self::operator_counters(operator), dop);
+                    (!skews.is_empty()).then_some((operator, skews))
+                });
+
+                if let Some((origin, skews)) = skewed.next() {
+                    let affected: Vec<(&str, f64)> = skewed.map(|(op, s)| (op.name.as_str(), s[0].ratio)).collect();
+                    let node_path = format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, origin.name);
+                    let node = NodeRef::operator(&fragment.id, &pipeline.id, origin);
+                    hotspots.extend(Self::build_hotspot(node_path, node, &origin.name, Self::attribute(&origin.name), skews, &affected));
+                }
+            }
+        }
+
+        hotspots
+    }
+
+    /// The spread of every merged counter of an operator, worst first, whether or not it is
+    /// large enough for a hotspot (`significant`).
+    pub(crate) fn operator_skews(pipeline: &Pipeline, operator: &Operator) -> Vec<SkewIndicator> {
+        Self::counter_skews(&Self::operator_counters(operator), Self::pipeline_dop(pipeline))
+            .into_iter()
+            .map(|skew| SkewIndicator {
+                operator: operator.name.clone(),
+                significant: skew.ratio >= MILD_SKEW_RATIO && Self::is_significant(&skew) && Self::is_slow(operator),
+                metric: skew.counter,
+                max: skew.max,
+                min: skew.min,
+                avg: skew.avg,
+                ratio: skew.ratio,
+                unit: Self::unit(skew.kind),
+            })
+            .collect()
+    }
+
+    /// DegreeOfParallelism is per backend; summed counters cover every driver of the fragment.
+    fn pipeline_dop(pipeline: &Pipeline) -> Option<u64> {
+        pipeline.metrics.get("TotalDegreeOfParallelism")
+            .or_else(|| pipeline.metrics.get("DegreeOfParallelism"))
+            .and_then(|v| ValueParser::parse_number::<u64>(v).ok())
+    }
+
+    fn operator_counters(operator: &Operator) -> HashMap<String, String> {
+        let mut counters = operator.common_metrics.clone();
+        counters.extend(operator.unique_metrics.iter().map(|(k, v)| (k.clone(), v.clone())));
+        counters
+    }
+
+    /// All significant skews of one counter set, worst first.
+    fn collect_skews(metrics: &HashMap<String, String>, instances: Option<u64>) -> Vec<CounterSkew> {
+        Self::counter_skews(metrics, instances).into_iter()
+            .filter(|skew| skew.ratio >= MILD_SKEW_RATIO && Self::is_significant(skew))
+            .collect()
+    }
+
+    fn counter_skews(metrics: &HashMap<String, String>, instances: Option<u64>) -> Vec<CounterSkew> {
+        let mut skews: Vec<CounterSkew> = metrics.iter()
+            .filter(|(key, _)| !key.starts_with(MERGED_INFO_PREFIX_MAX) && !key.starts_with(MERGED_INFO_PREFIX_MIN))
+            .filter(|(key, _)| !SYMPTOM_COUNTER_KEYWORDS.iter().any(|kw| key.contains(kw)))
+            .filter_map(|(key, value)| {
+                let max_value = metrics.get(&format!("{}{}", MERGED_INFO_PREFIX_MAX, key))?;
+                let min_value = metrics.get(&format!("{}{}", MERGED_INFO_PREFIX_MIN, key));
+                Self::counter_skew(key, value, max_value, min_value, instances)
+            })
+            .collect();
+
+        skews.sort_by(|a, b| b.ratio.partial_cmp(&a.ratio).unwrap_or(std::cmp::Ordering::Equal));
+        skews
+    }
+
+    fn counter_skew(key: &str, value: &str, max_value: &str, min_value: Option<&String>, instances: Option<u64>) -> Option<CounterSkew> {
+        let kind = Self::counter_kind(key, value);
+        let merged = Self::parse_value(kind, value)?;
+        let max = Self::parse_value(kind, max_value)?;
+        let min = min_value.and_then(|v| Self::parse_value(kind, v)).unwrap_or(0.0);
+        // every instance reported the same value; the merged one may then be a copy or a sum
+        if max <= min {
+            return None;
+        }
+
+        let avg = if kind == CounterKind::Time || merged < max {
+            merged
+        } else {
+            merged / instances.filter(|n| *n > 1)? as f64
+        };
+        // the DOP overestimates the instances of counters that only some drivers report
+        let avg = avg.clamp(min, max);
+
+        if avg <= 0.0 || max <= 0.0 {
+            return None;
+        }
+
+        Some(CounterSkew { counter: key.to_string(), kind, max, min, avg, ratio: max / avg })
+    }
+
+    fn counter_kind(key: &str, value: &str) -> CounterKind {
+        let value = value.trim();
+        let starts_numeric = value.chars().next().map(|c| c.is_ascii_digit()).unwrap_or(false);
+        if starts_numeric && !value.contains(' ') && value.ends_with(['s', 'm', 'h']) {
+            CounterKind::Time
+        } else if key.contains("Bytes") || key.contains("Memory") || value.ends_with('B') {
+            CounterKind::Bytes
+        } else {
+            CounterKind::Count
+        }
+    }
+
+    fn parse_value(kind: CounterKind, value: &str) -> Option<f64> {
+        match kind {
+            CounterKind::Time => ValueParser::parse_time_to_ms(value).ok(),
+            CounterKind::Bytes | CounterKind::Count => ValueParser::parse_bytes(value).ok().map(|v| v as f64),
+        }
+    }
+
+    /// An operator whose every timer stays under `MIN_SKEWED_TIME_MS` on its slowest instance holds
+    /// nothing up, however unevenly its rows or bytes are spread (e.g. a scan handing a single row
+    /// to one driver). Scans count their IO timers, not just `OperatorTotalTime`.
+    fn is_slow(operator: &Operator) -> bool {
+        let mut times = Self::operator_counters(operator).into_iter()
+            .filter(|(key, value)| !key.starts_with(MERGED_INFO_PREFIX_MIN) && Self::counter_kind(key, value) == CounterKind::Time)
+            .filter(|(key, _)| !SYMPTOM_COUNTER_KEYWORDS.iter().any(|kw| key.contains(kw)))
+            .filter_map(|(_, value)| ValueParser::parse_time_to_ms(&value).ok())
+            .peekable();
+        times.peek().is_none() || times.any(|ms| ms >= MIN_SKEWED_TIME_MS)
+    }
+
+    fn is_significant(skew: &CounterSkew) -> bool {
+        match skew.kind {
+            CounterKind::Time => skew.max >= MIN_SKEWED_TIME_MS,
+            CounterKind::Bytes => skew.max >= MIN_SKEWED_BYTES,
+            CounterKind::Count => skew.max >= MIN_SKEWED_COUNT,
+        }
+    }
+
+    fn attribute(operator_name: &str) -> SkewSource {
+        if operator_name.contains("SCAN") {
+            SkewSource::Scan
+        } else if operator_name.contains("AGGREGATE") || operator_name.contains("DISTINCT") {
+            SkewSource::Aggregation
+        } else if operator_name.contains("EXCHANGE") || operator_name.contains("JOIN") {
+            SkewSource::Shuffle
+        } else {
+            SkewSource::Other
+        }
+    }
+
+    /// `affected` lists downstream operators of the same pipeline that inherit the skew, with their worst ratio.
+    fn build_hotspot(node_path: String, node: NodeRef, subject: &str, source: SkewSource, skews: Vec<CounterSkew>, affected: &[(&str, f64)]) -> Option<HotSpot> {
+        let worst = skews.first()?;
+
+        let peak_ratio = affected.iter().map(|(_, ratio)| *ratio).fold(worst.ratio, f64::max);
+        let severity = match peak_ratio {
+            r if r >= SEVERE_SKEW_RATIO => HotSeverity::Severe,
+            r if r >= MODERATE_SKEW_RATIO => HotSeverity::Moderate,
+            _ => HotSeverity::Mild,
+        };
+
+        let source_label = match source {
+            SkewSource::Scan => tr!("skew.source.scan"),
+            SkewSource::Shuffle => tr!("skew.source.shuffle"),
+            SkewSource::Aggregation => tr!("skew.source.aggregation"),
+            SkewSource::Other => tr!("skew.source.other"),
+        };
+
+        let mut description = tr!(
+            "skew.description",
+            subject = subject,
+            source = source_label,
+            counter = worst.counter,
+            max = Self::format_value(worst.kind, worst.max),
+            avg = Self::format_value(worst.kind, worst.avg),
+            ratio = worst.ratio,
+            min = Self::format_value(worst.kind, worst.min),
+        );
+        if skews.len() > 1 {
+            let others: Vec<String> = skews[1..].iter()
+                .map(|s| format!("{}({:.1}x)", s.counter, s.ratio))
+                .collect();
+            description.push_str(&tr!("skew.also_skewed", counters = others.join(", ")));
+        }
+        if !affected.is_empty() {
+            let names: Vec<&str> = affected.iter().map(|(name, _)| *name).collect();
+            description.push_str(&tr!("skew.affected", operators = names.join(", ")));
+        }
+
+        let unit = Self::unit(worst.kind);
+        let mut evidence = vec![
+            Evidence::new(format!("{}{}", MERGED_INFO_PREFIX_MAX, worst.counter), worst.max, unit),
+            Evidence::new(format!("{} (avg)", worst.counter), worst.avg, unit),
+        ];
+        evidence.extend(skews.iter().map(|s| Evidence::new(format!("{}{}", s.counter, SKEW_RATIO_EVIDENCE_SUFFIX), s.ratio, MetricUnit::Ratio).threshold(MILD_SKEW_RATIO)));
+
+        Some(HotSpot {
+            node_path,
+            node,
+            severity,
+            issue_type: IssueCode::DataSkew,
+            confidence: Confidence::High,
+            description,
+            suggestions: Self::suggestions(source),
+            evidence,
+        })
+    }
+
+    fn suggestions(source: SkewSource) -> Vec<String> {
+        match source {
+            SkewSource::Scan => tr_list!("skew.suggestions.scan"),
+            SkewSource::Shuffle => tr_list!("skew.suggestions.shuffle"),
+            SkewSource::Aggregation => tr_list!("skew.suggestions.aggregation"),
+            SkewSource::Other => tr_list!("skew.suggestions.other"),
+        }
+    }
+
+    fn unit(kind: CounterKind) -> MetricUnit {
+        match kind {
+            CounterKind::Time => MetricUnit::Milliseconds,
+            CounterKind::Bytes => MetricUnit::Bytes,
+            CounterKind::Count => MetricUnit::Count,
+        }
+    }
+
+    fn format_value(kind: CounterKind, value: f64) -> String {
+        match kind {
+            CounterKind::Time if value >= 1000.0 => format!("{:.2}s", value / 1000.0),
+            CounterKind::Time => format!("{:.2}ms", value),
+            CounterKind::Bytes if value >= 1024.0 * 1024.0 * 1024.0 => format!("{:.2}GB", value / (1024.0 * 1024.0 * 1024.0)),
+            CounterKind::Bytes => format!("{:.2}MB", value / (1024.0 * 1024.0)),
+            CounterKind::Count => format!("{:.0}", value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, metrics};
+
+    #[test]
+    fn test_time_counter_is_average() {
+        let m = metrics(&[
+            ("OperatorTotalTime", "2s"),
+            ("__MAX_OF_OperatorTotalTime", "12s"),
+            ("__MIN_OF_OperatorTotalTime", "100ms"),
+        ]);
+        let skews = SkewDetector::collect_skews(&m, Some(8));
+        assert_eq!(skews.len(), 1);
+        assert_eq!(skews[0].kind, CounterKind::Time);
+        assert!((skews[0].ratio - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_count_counter_is_sum() {
+        let m = metrics(&[
+            ("PushRowNum", "40.000M (40000000)"),
+            ("__MAX_OF_PushRowNum", "20.000M (20000000)"),
+            ("__MIN_OF_PushRowNum", "0"),
+            ("PullRowNum", "4.000M (4000000)"),
+            ("__MAX_OF_PullRowNum", "1.000M (1000000)"),
+        ]);
+        let skews = SkewDetector::collect_skews(&m, Some(4));
+        assert_eq!(skews.len(), 1);
+        assert_eq!(skews[0].counter, "PushRowNum");
+        assert!((skews[0].avg - 10_000_000.0).abs() < 1e-6);
+
+        // without the instance count a summed counter cannot be averaged
+        assert!(SkewDetector::collect_skews(&m, None).is_empty());
+    }
+
+    #[test]
+    fn test_symptom_and_tiny_counters_ignored() {
+        let m = metrics(&[
+            ("PendingTime", "1s"),
+            ("__MAX_OF_PendingTime", "30s"),
+            ("ScanTime", "10ms"),
+            ("__MAX_OF_ScanTime", "900ms"),
+        ]);
+        assert!(SkewDetector::collect_skews(&m, Some(8)).is_empty());
+    }
+
+    #[test]
+    fn test_hotspot_attribution() {
+        let skews = vec![CounterSkew { counter: "ScanTime".to_string(), kind: CounterKind::Time, max: 12_000.0, min: 500.0, avg: 1_000.0, ratio: 12.0 }];
+        let hotspot = SkewDetector::build_hotspot("Fragment1.Pipeline0.OLAP_SCAN".to_string(), NodeRef::default(), "OLAP_SCAN", SkewDetector::attribute("OLAP_SCAN"), skews, &[("PROJECT", 12.0)]).unwrap();
+        assert_eq!(hotspot.severity, HotSeverity::Severe);
+        assert_eq!(hotspot.issue_type, IssueCode::DataSkew);
+        assert_eq!(hotspot.evidence[2], Evidence::new("ScanTime max/avg", 12.0, MetricUnit::Ratio).threshold(MILD_SKEW_RATIO));
+        assert!(hotspot.description.contains("ScanTime"));
+        assert!(hotspot.description.contains("PROJECT"));
+        assert!(hotspot.suggestions.iter().any(|s| s.contains("分桶")));
+        assert_eq!(SkewDetector::attribute("HASH_JOIN_PROBE"), SkewSource::Shuffle);
+        assert_eq!(SkewDetector::attribute("AGGREGATE_BLOCKING_SINK"), SkewSource::Aggregation);
+    }
+
+    #[test]
+    fn test_sink_skew_profile5() {
+        // some OLAP_TABLE_SINK instances wait much longer on their tablet writers than others
+        let hotspots = SkewDetector::analyze(&load("profile5.txt"));
+        let sink = hotspots.iter().find(|h| h.node_path.ends_with("OLAP_TABLE_SINK")).unwrap();
+        assert_eq!(sink.severity, HotSeverity::Mild);
+        assert!(sink.evidence.iter().any(|e| e.metric == "RpcServerSideTime max/avg"));
+    }
+
+    #[test]
+    fn test_averages_within_range_profiles() {
+        for name in ["profile1.txt", "profile3.txt", "profile4.txt", "profile5.txt"] {
+            let profile = load(name);
+            for pipeline in profile.fragments.iter().flat_map(|f| &f.pipelines) {
+                for operator in &pipeline.operators {
+                    for skew in SkewDetector::operator_skews(pipeline, operator) {
+                        assert!(skew.min <= skew.avg && skew.avg <= skew.max, "{} {}.{}: {:?}", name, operator.name, skew.metric, skew);
+                    }
+                }
+            }
+        }
+
+        // 32 tablets over fewer instances than the scan's 32 drivers: 2 or 3 each, not 1
+        let profile = load("profile3.txt");
+        let pipeline = profile.fragments.iter().flat_map(|f| &f.pipelines)
+            .find(|p| p.operators.iter().any(|op| op.name == "CONNECTOR_SCAN"))
+            .unwrap();
+        let scan = pipeline.operators.iter().find(|op| op.name == "CONNECTOR_SCAN").unwrap();
+        let tablets = SkewDetector::operator_skews(pipeline, scan).into_iter().find(|s| s.metric == "TabletCount").unwrap();
+        assert_eq!((tablets.avg, tablets.ratio), (2.0, 1.5));
+    }
+
+    #[test]
+    fn test_equal_instances_not_skewed() {
+        let m = metrics(&[
+            ("MorselsCount", "14"),
+            ("__MAX_OF_MorselsCount", "1"),
+            ("__MIN_OF_MorselsCount", "1"),
+            ("TabletCount", "3"),
+            ("__MAX_OF_TabletCount", "3"),
+            ("__MIN_OF_TabletCount", "3"),
+        ]);
+        assert!(SkewDetector::counter_skews(&m, Some(14)).is_empty());
+    }
+
+    #[test]
+    fn test_single_row_scan_profile5() {
+        // the scan hands its only row to one driver in 60us; the unnest that follows runs on
+        // that driver alone for 6.8s
+        let hotspots = SkewDetector::analyze(&load("profile5.txt"));
+        assert!(!hotspots.iter().any(|h| h.node_path.ends_with("CONNECTOR_SCAN")));
+        let unnest = hotspots.iter().find(|h| h.node_path.ends_with("TABLE_FUNCTION")).unwrap();
+        assert!(unnest.evidence.iter().any(|e| e.metric == "OperatorTotalTime max/avg"));
+    }
+}
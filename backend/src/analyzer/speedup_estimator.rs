@@ -0,0 +1,268 @@
+use crate::analyzer::data_cache_analyzer::IO_SAVING_EVIDENCE;
+use crate::analyzer::olap_scan_analyzer::PRED_FILTER_RATIO_EVIDENCE;
+use crate::analyzer::skew_detector::SKEW_RATIO_EVIDENCE_SUFFIX;
+use crate::analyzer::spill_analyzer::SPILL_COST_EVIDENCE;
+use crate::models::*;
+use crate::tr;
+
+/// Share of a broadcast join's time won back by shuffling instead: the build side is sent once
+/// rather than to every instance, but the probe side now has to be shuffled too.
+const BROADCAST_GAIN: (f64, f64) = (0.2, 0.6);
+/// Share of a fragmented scan's time spent opening and merging rowsets that compaction removes.
+const COMPACTION_GAIN: (f64, f64) = (0.2, 0.5);
+/// Fallback for hotspots without a dedicated model.
+const GENERIC_GAIN: (f64, f64) = (0.1, 0.3);
+/// IO and spilling overlap computation, so at least this share of the saved time shows.
+const MIN_OVERLAP_SHARE: f64 = 0.5;
+/// Work off the critical path overlaps other pipelines; at most this share of it shows in the
+/// wall time.
+const OFF_PATH_SHARE: f64 = 0.5;
+
+/// What-if estimates of the wall time saved by fixing each hotspot.
+///
+/// A node's time is its share of the cumulative operator time (`NodeInfo::compute_time_usage`)
+/// applied to the query's wall time. The scenario of the hotspot's issue decides which part of
+/// that time goes away, from the hotspot's evidence where it measured the cost and from a fixed
+/// range otherwise. Savings off the critical path only partly reach the wall time.
+///
+/// Each estimate assumes only its own hotspot gets fixed; estimates of hotspots on the same node
+/// overlap and do not add up.
+pub struct SpeedupEstimator;
+
+impl SpeedupEstimator {
+    /// Needs the execution tree marked with the critical path (`CriticalPathAnalyzer::mark_tree`).
+    pub fn estimate(
+        profile: &Profile,
+        tree: Option<&ExecutionTree>,
+        critical_path: Option<&CriticalPath>,
+        hotspots: &[HotSpot],
+    ) -> Vec<SpeedupEstimate> {
+        let (Some(tree), Some(wall_ms)) = (tree, Self::wall_time_ms(profile)) else {
+            return Vec::new();
+        };
+
+        let mut estimates: Vec<SpeedupEstimate> = hotspots.iter().enumerate()
+            .filter_map(|(index, hotspot)| {
+                let node = tree.locate(profile, hotspot)?;
+                let share = node.time_percentage.filter(|p| *p > 0.0)? / 100.0;
+                let node_ms = share * wall_ms;
+                let mut assumptions = vec![tr!(
+                    "speedup.assumption.time_share",
+                    operator = node.operator_name,
+                    percent = share * 100.0,
+                    node_ms = node_ms,
+                )];
+
+                let (scenario, (min_ms, max_ms)) = Self::node_saving(hotspot, node_ms, &mut assumptions);
+                let on_critical_path = critical_path.is_none() || node.is_on_critical_path;
+                let (min_ms, max_ms) = match critical_path {
+                    None => {
+                        assumptions.push(tr!("speedup.assumption.no_critical_path"));
+                        (min_ms, max_ms)
+                    }
+                    Some(_) if on_critical_path => {
+                        assumptions.push(tr!("speedup.assumption.on_critical_path"));
+                        (min_ms, max_ms)
+                    }
+                    Some(_) => {
+                        assumptions.push(tr!("speedup.assumption.off_critical_path", percent = OFF_PATH_SHARE * 100.0));
+                        (0.0, max_ms * OFF_PATH_SHARE)
+                    }
+                };
+
+                let (min_ms, max_ms) = (min_ms.min(wall_ms), max_ms.min(wall_ms));
+                Some(SpeedupEstimate {
+                    hotspot: HotspotRef { index, issue_type: hotspot.issue_type.clone(), node_path: hotspot.node_path.clone() },
+                    scenario,
+                    gain: GainRange {
+                        min_ms,
+                        max_ms,
+                        min_percentage: min_ms * 100.0 / wall_ms,
+                        max_percentage: max_ms * 100.0 / wall_ms,
+                    },
+                    on_critical_path,
+                    assumptions,
+                })
+            })
+            .collect();
+
+        estimates.sort_by(|a, b| b.gain.max_ms.partial_cmp(&a.gain.max_ms).unwrap_or(std::cmp::Ordering::Equal));
+        estimates
+    }
+
+    /// The node time the scenario removes, at most the node's time.
+    fn node_saving(hotspot: &HotSpot, node_ms: f64, assumptions: &mut Vec<String>) -> (SpeedupScenario, (f64, f64)) {
+        let evidence = |metric: &str| hotspot.evidence.iter().find(|e| e.metric == metric).map(|e| e.value);
+        let share_of_node = |(low, high): (f64, f64)| (node_ms * low, node_ms * high);
+        let capped = |(low, high): (f64, f64)| (low.min(node_ms), high.min(node_ms));
+
+        match hotspot.issue_type {
+            IssueCode::LowDataCacheHit if evidence(IO_SAVING_EVIDENCE).is_some() => {
+                let saving = evidence(IO_SAVING_EVIDENCE).unwrap_or(0.0);
+                assumptions.push(tr!("speedup.assumption.full_cache_hit", saving_ms = saving, percent = MIN_OVERLAP_SHARE * 100.0));
+                (SpeedupScenario::FullCacheHit, capped((saving * MIN_OVERLAP_SHARE, saving)))
+            }
+            IssueCode::OperatorSpill if evidence(SPILL_COST_EVIDENCE).is_some() => {
+                let cost = evidence(SPILL_COST_EVIDENCE).unwrap_or(0.0);
+                assumptions.push(tr!("speedup.assumption.spill_avoided", cost_ms = cost, percent = MIN_OVERLAP_SHARE * 100.0));
+                (SpeedupScenario::SpillAvoided, capped((cost * MIN_OVERLAP_SHARE, cost)))
+            }
+            IssueCode::DataSkew => match hotspot.evidence.iter().find(|e| e.metric.ends_with(SKEW_RATIO_EVIDENCE_SUFFIX) && e.value > 1.0) {
+                Some(skew) => {
+                    // the slowest instance sets the node's time; without skew it does the average
+                    let removable = 1.0 - 1.0 / skew.value;
+                    assumptions.push(tr!(
+                        "speedup.assumption.skew_removed",
+                        counter = skew.metric,
+                        ratio = skew.value,
+                        percent = removable * 100.0,
+                    ));
+                    (SpeedupScenario::SkewRemoved, share_of_node((removable * MIN_OVERLAP_SHARE, removable)))
+                }
+                None => Self::generic(node_ms, assumptions),
+            },
+            IssueCode::BroadcastJoinTooLarge => {
+                assumptions.push(tr!(
+                    "speedup.assumption.broadcast_to_shuffle",
+                    low = BROADCAST_GAIN.0 * 100.0,
+                    high = BROADCAST_GAIN.1 * 100.0,
+                ));
+                (SpeedupScenario::BroadcastToShuffle, share_of_node(BROADCAST_GAIN))
+            }
+            IssueCode::RowsetFragmentation => {
+                assumptions.push(tr!(
+                    "speedup.assumption.rowsets_compacted",
+                    low = COMPACTION_GAIN.0 * 100.0,
+                    high = COMPACTION_GAIN.1 * 100.0,
+                ));
+                (SpeedupScenario::RowsetsCompacted, share_of_node(COMPACTION_GAIN))
+            }
+            IssueCode::MissingPredicatePushdown | IssueCode::LateRowFiltering | IssueCode::IneffectiveZoneMap
+            | IssueCode::IneffectiveBloomFilter => match evidence(PRED_FILTER_RATIO_EVIDENCE).filter(|r| *r > 0.0) {
+                Some(discarded) => {
+                    assumptions.push(tr!("speedup.assumption.rows_filtered_early", percent = discarded * 100.0));
+                    (SpeedupScenario::RowsFilteredEarly, share_of_node((discarded * MIN_OVERLAP_SHARE, discarded)))
+                }
+                None => Self::generic(node_ms, assumptions),
+            },
+            _ => Self::generic(node_ms, assumptions),
+        }
+    }
+
+    fn generic(node_ms: f64, assumptions: &mut Vec<String>) -> (SpeedupScenario, (f64, f64)) {
+        assumptions.push(tr!("speedup.assumption.generic", low = GENERIC_GAIN.0 * 100.0, high = GENERIC_GAIN.1 * 100.0));
+        (SpeedupScenario::HotspotFixed, (node_ms * GENERIC_GAIN.0, node_ms * GENERIC_GAIN.1))
+    }
+
+    fn wall_time_ms(profile: &Profile) -> Option<f64> {
+        profile.summary.query_execution_wall_time_ms
+            .or(profile.summary.total_time_ms)
+            .filter(|ms| *ms > 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analyzer::{DataCacheAnalyzer, OlapScanAnalyzer, SkewDetector, SpillAnalyzer};
+    use crate::test_support::{hotspot, load, operator, pipeline, profile, tree, tree_node, with_pipelines};
+
+    fn scan_profile(on_critical_path: bool) -> Profile {
+        let mut scan = tree_node(0, "OLAP_SCAN", &[]);
+        scan.time_percentage = Some(50.0);
+        scan.is_on_critical_path = on_critical_path;
+        let mut profile = profile(Vec::new());
+        profile.summary.query_execution_wall_time_ms = Some(10_000.0);
+        profile.execution_tree = Some(tree(vec![scan]));
+        profile
+    }
+
+    fn scan_hotspot(issue_type: IssueCode, evidence: Vec<Evidence>) -> HotSpot {
+        HotSpot {
+            node_path: "OLAP_SCAN (node_0)".to_string(),
+            node: NodeRef { plan_node_id: Some(0), ..NodeRef::default() },
+            evidence,
+            ..hotspot(issue_type, HotSeverity::Moderate)
+        }
+    }
+
+    fn estimate(profile: &Profile, hotspots: &[HotSpot]) -> Vec<SpeedupEstimate> {
+        let path = CriticalPath { stages: Vec::new(), path_time_ms: 0.0, wall_time_ms: None, coverage_percentage: None };
+        SpeedupEstimator::estimate(profile, profile.execution_tree.as_ref(), Some(&path), hotspots)
+    }
+
+    #[test]
+    fn test_evidence_based_estimates() {
+        let hotspots = vec![
+            scan_hotspot(IssueCode::LowDataCacheHit, vec![Evidence::new(IO_SAVING_EVIDENCE, 3_000.0, MetricUnit::Milliseconds)]),
+            scan_hotspot(IssueCode::DataSkew, vec![Evidence::new("ScanTime max/avg", 4.0, MetricUnit::Ratio)]),
+            scan_hotspot(IssueCode::UnderfilledChunks, Vec::new()),
+        ];
+        let estimates = estimate(&scan_profile(true), &hotspots);
+        let gains: Vec<(usize, SpeedupScenario, f64, f64)> = estimates.iter()
+            .map(|e| (e.hotspot.index, e.scenario, e.gain.min_ms, e.gain.max_ms))
+            .collect();
+
+        // the scan takes half of the 10s wall time
+        assert_eq!(gains, [
+            (1, SpeedupScenario::SkewRemoved, 1_875.0, 3_750.0),
+            (0, SpeedupScenario::FullCacheHit, 1_500.0, 3_000.0),
+            (2, SpeedupScenario::HotspotFixed, 500.0, 1_500.0),
+        ]);
+        assert_eq!(estimates[0].gain.max_percentage, 37.5);
+        assert_eq!(estimates[0].assumptions.len(), 3);
+    }
+
+    #[test]
+    fn test_scenarios_from_analyzer_evidence() {
+        let scenarios = |profile: &Profile, hotspots: Vec<HotSpot>| -> Vec<(IssueCode, SpeedupScenario)> {
+            estimate(profile, &hotspots).into_iter().map(|e| (e.hotspot.issue_type, e.scenario)).collect()
+        };
+
+        let profile1 = load("profile1.txt");
+        assert_eq!(
+            scenarios(&profile1, DataCacheAnalyzer::analyze(&profile1)),
+            [(IssueCode::LowDataCacheHit, SpeedupScenario::FullCacheHit)],
+        );
+        let filtering = scenarios(&profile1, OlapScanAnalyzer::analyze(&profile1));
+        assert_eq!(filtering.iter().filter(|(_, scenario)| *scenario == SpeedupScenario::RowsFilteredEarly).count(), 3);
+
+        let profile5 = load("profile5.txt");
+        let skew = scenarios(&profile5, SkewDetector::analyze(&profile5));
+        assert_eq!(skew.len(), 2);
+        assert!(skew.iter().all(|(_, scenario)| *scenario == SpeedupScenario::SkewRemoved));
+
+        let mut spill = scan_profile(true);
+        spill.fragments = vec![with_pipelines("1", vec![pipeline("0", &[], vec![
+            operator("AGGREGATE_BLOCKING_SINK", 0, &[], &[("SpillBytes", "6.000 GB"), ("SpillTime", "12s"), ("RestoreTime", "8s")]),
+        ])])];
+        assert_eq!(
+            scenarios(&spill, SpillAnalyzer::analyze(&spill)),
+            [(IssueCode::OperatorSpill, SpeedupScenario::SpillAvoided)],
+        );
+    }
+
+    #[test]
+    fn test_off_critical_path() {
+        let hotspots = vec![scan_hotspot(IssueCode::BroadcastJoinTooLarge, Vec::new())];
+        let estimates = estimate(&scan_profile(false), &hotspots);
+        assert!(!estimates[0].on_critical_path);
+        assert_eq!((estimates[0].gain.min_ms, estimates[0].gain.max_ms), (0.0, 1_500.0));
+
+        let mut no_tree = scan_profile(true);
+        no_tree.execution_tree = None;
+        assert!(estimate(&no_tree, &hotspots).is_empty());
+    }
+
+    #[test]
+    fn test_estimates_profile3() {
+        let profile = load("profile3.txt");
+        let hotspots = crate::HotSpotDetector::analyze(&profile);
+        let path = crate::CriticalPathAnalyzer::analyze(&profile);
+        let estimates = SpeedupEstimator::estimate(&profile, profile.execution_tree.as_ref(), path.as_ref(), &hotspots);
+
+        assert!(estimates.iter().any(|e| e.scenario == SpeedupScenario::FullCacheHit));
+        // no fix saves more than the 168s the query took
+        assert!(estimates.iter().all(|e| e.gain.min_ms <= e.gain.max_ms && e.gain.max_ms <= 168_000.0));
+    }
+}
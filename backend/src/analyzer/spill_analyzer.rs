@@ -0,0 +1,239 @@
+use crate::models::*;
+use crate::parser::core::{MetricsParser, ValueParser};
+use crate::tr;
+
+/// Spill + restore wall time (per driver) from which spilling dominates the operator.
+const SEVERE_SPILL_COST_SECS: f64 = 30.0;
+const MODERATE_SPILL_COST_SECS: f64 = 5.0;
+/// Peak memory below this share of the limit means spilling was not forced by memory pressure.
+const LOW_LIMIT_USAGE: f64 = 0.5;
+/// Raising the limit is only realistic up to this multiple of the current one.
+const MAX_LIMIT_RAISE: f64 = 2.0;
+const LARGE_QUERY_SPILL_BYTES: u64 = 1024 * 1024 * 1024;
+/// Evidence holding the spill + restore time of an operator, read by the speedup estimate.
+pub(crate) const SPILL_COST_EVIDENCE: &str = "SpillTime + RestoreTime";
+
+/// Query memory context the spill advice is based on.
+struct MemoryContext {
+    /// `QueryMemoryLimit`, `None` when unlimited (-1) or not reported.
+    limit: Option<u64>,
+    peak_per_node: Option<u64>,
+    nodes: usize,
+}
+
+/// Per-operator spill analysis: which operators spilled, what spilling and restoring cost,
+/// and whether a larger `query_mem_limit` or a different `spill_mode` would avoid it.
+pub struct SpillAnalyzer;
+
+impl SpillAnalyzer {
+    pub fn analyze(profile: &Profile) -> Vec<HotSpot> {
+        let context = Self::memory_context(profile);
+        let mut spilled = Vec::new();
+
+        for fragment in &profile.fragments {
+            for pipeline in &fragment.pipelines {
+                for operator in &pipeline.operators {
+                    let Some(spill) = MetricsParser::spill_from_hashmap(&operator.unique_metrics) else {
+                        continue;
+                    };
+                    if spill.spill_bytes.unwrap_or(0) == 0 {
+                        continue;
+                    }
+                    let node_path = format!("Fragment{}.Pipeline{}.{}", fragment.id, pipeline.id, operator.name);
+                    let operator_time = operator.common_metrics.get("OperatorTotalTime")
+                        .and_then(|v| ValueParser::parse_duration(v).ok())
+                        .map(|d| d.as_secs_f64());
+                    let node = NodeRef::operator(&fragment.id, &pipeline.id, operator);
+                    spilled.push((node_path, node, operator.name.as_str(), spill, operator_time));
+                }
+            }
+        }
+
+        let total_spill: u64 = spilled.iter().map(|(_, _, _, spill, _)| spill.spill_bytes.unwrap_or(0)).sum();
+        if spilled.is_empty() {
+            return Self::analyze_query_spill(profile, &context).into_iter().collect();
+        }
+
+        spilled.iter()
+            .map(|(node_path, node, name, spill, operator_time)| {
+                Self::build_hotspot(node_path, node, name, spill, *operator_time, total_spill, &context)
+            })
+            .collect()
+    }
+
+    fn build_hotspot(
+        node_path: &str,
+        node: &NodeRef,
+        operator_name: &str,
+        spill: &OperatorSpillMetrics,
+        operator_time: Option<f64>,
+        total_spill: u64,
+        context: &MemoryContext,
+    ) -> HotSpot {
+        let secs = |nanos: Option<u64>| nanos.unwrap_or(0) as f64 / 1e9;
+        let spill_secs = secs(spill.spill_time);
+        let restore_secs = secs(spill.restore_time);
+        let cost = spill_secs + restore_secs;
+
+        let mut description = tr!(
+            "spill.operator.description",
+            operator = operator_name,
+            spilled = ValueParser::format_bytes(spill.spill_bytes.unwrap_or(0)),
+            rows = spill.spill_rows.map(|r| r.to_string()).unwrap_or_else(|| "?".to_string()),
+            partitions = spill.spill_partition_num.map(|p| p.to_string()).unwrap_or_else(|| "?".to_string()),
+            restored = ValueParser::format_bytes(spill.restore_bytes.unwrap_or(0)),
+            spill_secs = spill_secs,
+            restore_secs = restore_secs,
+        );
+        if let Some(total) = operator_time.filter(|t| *t > 0.0) {
+            description.push_str(&tr!("spill.operator.time_share", percent = cost / total * 100.0));
+        }
+
+        let mut suggestions = Self::memory_suggestions(total_spill, context);
+        suggestions.extend(Self::operator_suggestions(operator_name));
+        if cost >= MODERATE_SPILL_COST_SECS {
+            suggestions.push(tr!("spill.operator.slow_disk"));
+        }
+
+        HotSpot {
+            node_path: node_path.to_string(),
+            node: node.clone(),
+            severity: match cost {
+                c if c >= SEVERE_SPILL_COST_SECS => HotSeverity::Severe,
+                c if c >= MODERATE_SPILL_COST_SECS => HotSeverity::Moderate,
+                _ => HotSeverity::Mild,
+            },
+            issue_type: IssueCode::OperatorSpill,
+            confidence: Confidence::High,
+            description,
+            suggestions,
+            evidence: vec![
+                Evidence::new("SpillBytes", spill.spill_bytes.unwrap_or(0) as f64, MetricUnit::Bytes),
+                Evidence::new(SPILL_COST_EVIDENCE, cost * 1000.0, MetricUnit::Milliseconds).threshold(MODERATE_SPILL_COST_SECS * 1000.0),
+            ],
+        }
+    }
+
+    /// Operators without spill counters (older versions) only leave `QuerySpillBytes` in the summary.
+    fn analyze_query_spill(profile: &Profile, context: &MemoryContext) -> Option<HotSpot> {
+        let spill_bytes = profile.execution.metrics.get("QuerySpillBytes")
+            .and_then(|v| ValueParser::parse_bytes(v).ok())
+            .filter(|b| *b > 0)?;
+
+        Some(HotSpot {
+            node_path: "Execution.Overview".to_string(),
+            node: NodeRef::default(),
+            severity: if spill_bytes > LARGE_QUERY_SPILL_BYTES { HotSeverity::Moderate } else { HotSeverity::Mild },
+            issue_type: IssueCode::DiskSpill,
+            confidence: Confidence::High,
+            description: tr!("spill.query.description", bytes = ValueParser::format_bytes(spill_bytes)),
+            suggestions: Self::memory_suggestions(spill_bytes, context),
+            evidence: vec![Evidence::new("QuerySpillBytes", spill_bytes as f64, MetricUnit::Bytes)],
+        })
+    }
+
+    fn memory_suggestions(total_spill: u64, context: &MemoryContext) -> Vec<String> {
+        let spill_per_node = total_spill / context.nodes.max(1) as u64;
+        let Some(peak) = context.peak_per_node else {
+            return vec![tr!("spill.memory.check_skew")];
+        };
+        let needed = peak + spill_per_node;
+
+        match context.limit {
+            Some(limit) if (peak as f64) < limit as f64 * LOW_LIMIT_USAGE => vec![tr!(
+                "spill.memory.not_memory_bound",
+                peak = ValueParser::format_bytes(peak),
+                limit = ValueParser::format_bytes(limit),
+                percent = peak as f64 / limit as f64 * 100.0,
+            )],
+            Some(limit) if needed as f64 <= limit as f64 * MAX_LIMIT_RAISE => vec![tr!(
+                "spill.memory.raise_limit",
+                peak = ValueParser::format_bytes(peak),
+                spill = ValueParser::format_bytes(spill_per_node),
+                needed = ValueParser::format_bytes(needed),
+                limit_bytes = (needed as f64 * 1.2) as u64,
+            )],
+            Some(limit) => vec![tr!(
+                "spill.memory.reduce_data",
+                spill = ValueParser::format_bytes(spill_per_node),
+                limit = ValueParser::format_bytes(limit),
+            )],
+            None => vec![tr!("spill.memory.no_limit", needed = ValueParser::format_bytes(needed))],
+        }
+    }
+
+    fn operator_suggestions(operator_name: &str) -> Vec<String> {
+        if operator_name.contains("HASH_JOIN") {
+            vec![tr!("spill.operator.join")]
+        } else if operator_name.contains("AGGREGATE") {
+            vec![tr!("spill.operator.aggregate")]
+        } else if operator_name.contains("SORT") || operator_name.contains("MERGE") {
+            vec![tr!("spill.operator.sort")]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn memory_context(profile: &Profile) -> MemoryContext {
+        let limit = profile.fragments.iter()
+            .filter_map(|f| f.metrics.get("QueryMemoryLimit"))
+            .filter_map(|v| ValueParser::parse_bytes(v).ok())
+            .find(|l| *l > 0);
+        let peak_per_node = profile.execution.metrics.get("QueryPeakMemoryUsagePerNode")
+            .and_then(|v| ValueParser::parse_bytes(v).ok());
+        let mut backends: Vec<&String> = profile.fragments.iter().flat_map(|f| f.backend_addresses.iter()).collect();
+        backends.sort();
+        backends.dedup();
+
+        MemoryContext { limit, peak_per_node, nodes: backends.len().max(1) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{load, metrics, operator, pipeline, profile, with_pipelines};
+
+    fn spill_profile(limit: &str, peak: &str) -> Profile {
+        let operator = operator("AGGREGATE_BLOCKING_SINK", 3, &[("OperatorTotalTime", "40s")], &[
+            ("SpillBytes", "6.000 GB"),
+            ("SpillRows", "120000000"),
+            ("SpillTime", "12s"),
+            ("RestoreTime", "8s"),
+            ("SpillPartitionNum", "16"),
+        ]);
+        let fragment = Fragment {
+            backend_addresses: vec!["10.0.0.1:9060".to_string(), "10.0.0.2:9060".to_string(), "10.0.0.3:9060".to_string()],
+            metrics: metrics(&[("QueryMemoryLimit", limit)]),
+            ..with_pipelines("1", vec![pipeline("0", &[], vec![operator])])
+        };
+        let mut profile = profile(vec![fragment]);
+        profile.execution.metrics = metrics(&[("QueryPeakMemoryUsagePerNode", peak)]);
+        profile
+    }
+
+    #[test]
+    fn test_spill_under_memory_pressure() {
+        let hotspots = SpillAnalyzer::analyze(&spill_profile("8.000 GB", "7.500 GB"));
+        assert_eq!(hotspots.len(), 1);
+        assert_eq!(hotspots[0].issue_type, IssueCode::OperatorSpill);
+        assert_eq!(hotspots[0].severity, HotSeverity::Moderate);
+        assert!(hotspots[0].description.contains("50.0%"));
+        // 7.5GB peak + 2GB spilled per node fits under twice the limit
+        assert!(hotspots[0].suggestions[0].contains("SET query_mem_limit"));
+    }
+
+    #[test]
+    fn test_spill_without_memory_pressure() {
+        let hotspots = SpillAnalyzer::analyze(&spill_profile("16.000 GB", "2.000 GB"));
+        assert!(hotspots[0].suggestions[0].contains("spill_mode"));
+
+        let hotspots = SpillAnalyzer::analyze(&spill_profile("-1.000 B", "2.000 GB"));
+        assert!(hotspots[0].suggestions[0].contains("未设置内存限制"));
+    }
+
+    #[test]
+    fn test_no_spill_profile1() {
+        assert!(SpillAnalyzer::analyze(&load("profile1.txt")).is_empty());
+    }
+}
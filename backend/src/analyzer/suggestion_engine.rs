@@ -1,4 +1,9 @@
 use crate::models::*;
+use crate::i18n;
+use crate::rules::ScoreConfig;
+use crate::{tr, tr_list};
+use super::rule_engine::RuleEngine;
+use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 
 pub struct SuggestionEngine;
@@ -14,34 +19,14 @@ impl SuggestionEngine {
                 if mem_percentage > 80.0 {
                     hotspots.push(HotSpot {
                         node_path: "Execution.Overview".to_string(),
+                        node: NodeRef::default(),
                         severity: HotSeverity::Critical,
-                        issue_type: "MemoryUsage".to_string(),
-                        description: format!("内存使用率过高: {:.1}% (超过80%阈值)", mem_percentage),
-                        suggestions: vec![
-                            "检查内存配置参数 (be.conf 中 mem_limit)".to_string(),
-                            "考虑启用可溢出运算符 (spillable_operators)".to_string(),
-                            "优化查询以减少内存占用 (减少JOIN大小或使用分区)".to_string(),
-                            "增加BE节点内存或扩展集群".to_string(),
-                        ],
-                    });
-                }
-            }
-        }
-
-        if let Some(spill_str) = profile.execution.metrics.get("QuerySpillBytes") {
-            if let Ok(spill_bytes) = Self::parse_bytes(spill_str) {
-                if spill_bytes > 1_073_741_824 {
-                    hotspots.push(HotSpot {
-                        node_path: "Execution.Overview".to_string(),
-                        severity: HotSeverity::High,
-                        issue_type: "DiskSpill".to_string(),
-                        description: format!("磁盘溢出严重: {} (超过1GB阈值)", Self::format_bytes(spill_bytes)),
-                        suggestions: vec![
-                            "增加内存分配给查询".to_string(),
-                            "检查是否存在数据倾斜导致的内存不足".to_string(),
-                            "启用自适应降级 (enable_adaptive_sink_dop)".to_string(),
-                            "优化JOIN顺序以减少内存使用".to_string(),
-                        ],
+                        issue_type: IssueCode::MemoryUsage,
+                        // measured against an assumed 12GB node, not the real BE memory
+                        confidence: Confidence::Low,
+                        description: tr!("overview.memory_usage.description", percent = mem_percentage),
+                        suggestions: tr_list!("overview.memory_usage.suggestions"),
+                        evidence: vec![Evidence::new("QueryPeakMemoryUsagePerNode", mem_bytes as f64, MetricUnit::Bytes).threshold(total_memory as f64 * 0.8)],
                     });
                 }
             }
@@ -78,54 +63,172 @@ impl SuggestionEngine {
         operators
     }
 
-    pub fn generate_official_recipes(hotspot_type: &str, _context: &OperatorContext) -> Vec<String> {
-        match hotspot_type {
-            "cold_storage" => vec![
-                "将热数据迁移到NVMe/SSD存储".to_string(),
-                "启用存储缓存配置 (storage_root_path 指定SSD路径)".to_string(),
-                "提升远程缓存容量 (remote_cache_capacity 参数)".to_string(),
-                "检查存储系统IOPS是否充足".to_string(),
-            ],
-            "missing_predicate_pushdown" => vec![
-                "重写谓词为简单比较条件 (避免 LIKE '%xxx%')".to_string(),
-                "添加zonemap索引 (适合范围查询)".to_string(),
-                "添加Bloom索引 (适合等值查询)".to_string(),
-                "创建物化视图以便谓词下推".to_string(),
-            ],
-            "thread_pool_starvation" => vec![
-                "增加BE I/O线程池大小 (max_io_threads_per_disk)".to_string(),
-                "增加扫描线程池大小 (num_io_threads_backlog)".to_string(),
-                "启用存储缓存以减少I/O压力".to_string(),
-                "考虑数据预热减少冷读".to_string(),
-            ],
-            "data_skew" => vec![
-                "重新选择hash分桶键 (选择分布均匀的列)".to_string(),
-                "增加分桶数量以分散数据".to_string(),
-                "使用倾斜数据处理 (enable_skew_optimization)".to_string(),
-                "检查统计信息是否最新".to_string(),
-            ],
-            "fragmented_rowsets" => vec![
-                "触发手动compaction (ALTER TABLE ... COMPACT)".to_string(),
-                "批量导入小文件合并".to_string(),
-                "调整compaction参数 (cumulative_compaction_num_deltas)".to_string(),
-                "启用自动compaction调度".to_string(),
-            ],
-            "suboptimal_aggregation" => vec![
-                "检查是否使用一阶段聚合 (large dataset不适合)".to_string(),
-                "考虑分布式预聚合 (enable_distributed_aggregation)".to_string(),
-                "启用可溢出聚合 (spillable_operators)".to_string(),
-                "优化GROUP BY键的选择".to_string(),
-            ],
-            _ => vec![
-                "检查操作符具体指标和调优文档".to_string(),
-                "分析操作符时间占比确定优化优先级".to_string(),
-            ]
+    /// The recipe for a recipe name (`fragmented_rowsets`) or issue code (`RowsetFragmentation`):
+    /// statements filled in from the operator context first, then the general advice. Advice a
+    /// statement would replace (`recipe_manual`) is only given when the statement can't be filled in.
+    pub fn generate_official_recipes(hotspot_type: &str, context: &OperatorContext) -> Vec<TuningRecipe> {
+        let catalog = i18n::catalog(i18n::current());
+        let recipe = if catalog.contains(&format!("recipe.{}", hotspot_type)) {
+            Some(hotspot_type)
+        } else {
+            Self::recipe_name(&IssueCode::from(hotspot_type))
+        };
+        let Some(recipe) = recipe else {
+            return Self::advice(tr_list!("recipe.default"));
+        };
+
+        let mut steps = Self::recipe_statements(recipe, context);
+        steps.extend(Self::advice(tr_list!(&format!("recipe.{}", recipe))));
+        steps
+    }
+
+    /// Recipes for the hotspots located at an operator, one per recipe and operator.
+    pub fn generate_recipes(hotspots: &[HotSpot], profile: &Profile) -> Vec<HotspotRecipe> {
+        let mut seen = HashSet::new();
+        hotspots.iter().enumerate()
+            .filter_map(|(index, hotspot)| {
+                let recipe = Self::recipe_name(&hotspot.issue_type)?;
+                let context = Self::operator_context(profile, hotspot)?;
+                if !seen.insert((recipe, context.fragment_id.clone(), context.pipeline_id.clone(), context.operator_name.clone())) {
+                    return None;
+                }
+                Some(HotspotRecipe {
+                    hotspot: HotspotRef { index, issue_type: hotspot.issue_type.clone(), node_path: hotspot.node_path.clone() },
+                    steps: Self::generate_official_recipes(recipe, &context),
+                    expected_gain: None,
+                })
+            })
+            .collect()
+    }
+
+    fn recipe_name(issue_type: &IssueCode) -> Option<&'static str> {
+        use IssueCode::*;
+        Some(match issue_type {
+            RowsetFragmentation => "fragmented_rowsets",
+            DataSkew => "data_skew",
+            MissingPredicatePushdown | LateRowFiltering | IneffectiveZoneMap | IneffectiveBloomFilter => "missing_predicate_pushdown",
+            LowDataCacheHit | HighIoTime | IoBottleneck => "cold_storage",
+            ThreadPoolStarvation => "thread_pool_starvation",
+            InsufficientParallelism | UnderParallelized | AdaptiveDopTooLow => "low_parallelism",
+            IneffectivePreAggregation | HighCardinalityAggregation => "suboptimal_aggregation",
+            _ => return None,
+        })
+    }
+
+    fn recipe_statements(recipe: &str, context: &OperatorContext) -> Vec<TuningRecipe> {
+        let table = Self::qualified_table(context);
+        let step = |key: &str, sql: String| TuningRecipe {
+            advice: tr!(&format!("recipe_step.{}", key)),
+            statement: Some(RecipeStatement { sql, risk: tr!(&format!("recipe_step.{}_risk", key)) }),
+        };
+
+        let step_or_manual = |key: &str, sql: Option<String>| match sql {
+            Some(sql) => step(key, sql),
+            None => TuningRecipe { advice: tr!(&format!("recipe_manual.{}", key)), statement: None },
+        };
+
+        let mut steps = Vec::new();
+        match recipe {
+            "fragmented_rowsets" => {
+                steps.push(step_or_manual("compact", table.map(|table| format!("ALTER TABLE {} COMPACT;", table))));
+            }
+            "data_skew" => {
+                steps.push(step_or_manual("analyze", table.map(|table| format!("ANALYZE TABLE {};", table))));
+            }
+            "missing_predicate_pushdown" => {
+                if let (Some(table), Some(column)) = (table, Self::filtered_column(context)) {
+                    steps.push(step(
+                        "bitmap_index",
+                        format!("CREATE INDEX idx_{column} ON {table} ({column}) USING BITMAP;"),
+                    ));
+                }
+            }
+            "cold_storage" if Self::reads_remote_storage(context) => {
+                steps.extend(table.map(|table| step("cache_select", format!("CACHE SELECT * FROM {};", table))));
+            }
+            "low_parallelism" => {
+                steps.extend(Self::higher_dop(context).map(|dop| step("pipeline_dop", format!("SET pipeline_dop = {};", dop))));
+            }
+            "suboptimal_aggregation" => {
+                steps.push(step("force_streaming", "SET streaming_preaggregation_mode = 'force_streaming';".to_string()));
+            }
+            _ => {}
+        }
+        steps
+    }
+
+    fn advice(lines: Vec<String>) -> Vec<TuningRecipe> {
+        lines.into_iter().map(|advice| TuningRecipe { advice, statement: None }).collect()
+    }
+
+    /// The scanned table as `db.table`: the database it is qualified with in the SQL, else the
+    /// session's default database.
+    fn qualified_table(context: &OperatorContext) -> Option<String> {
+        let table = context.metrics.get("Table").map(|t| t.trim()).filter(|t| !t.is_empty())?;
+        if table.contains('.') {
+            return Some(table.to_string());
         }
+        let pattern = format!(r"([A-Za-z0-9_]+)`?\s*\.\s*`?{}\b", regex::escape(table));
+        let db = regex::Regex::new(&pattern).ok()
+            .and_then(|re| re.captures(&context.sql_statement))
+            .map(|caps| caps[1].to_string())
+            .or_else(|| context.default_db.clone());
+        Some(match db {
+            Some(db) => format!("{}.{}", db, table),
+            None => table.to_string(),
+        })
+    }
+
+    /// External catalog and shared-data scans, the ones `CACHE SELECT` can warm up; scans of
+    /// shared-nothing tables read local disks and have no Data Cache.
+    fn reads_remote_storage(context: &OperatorContext) -> bool {
+        context.operator_name == "CONNECTOR_SCAN"
+            || ["CompressedBytesReadRemote", "IOCountRemote"].iter().any(|name| context.metrics.contains_key(*name))
+    }
+
+    /// The column of the first pushed-down predicate, `Predicates: 2: model = 'PKB110'`.
+    fn filtered_column(context: &OperatorContext) -> Option<String> {
+        let predicates = context.metrics.get("Predicates")?;
+        let re = regex::Regex::new(r"^\s*\d+:\s*`?([A-Za-z_][A-Za-z0-9_]*)`?").ok()?;
+        re.captures(predicates).map(|caps| caps[1].to_string())
+    }
+
+    /// Twice the pipeline's current parallelism.
+    fn higher_dop(context: &OperatorContext) -> Option<u64> {
+        let current = context.pipeline_metrics.get("DegreeOfParallelism")
+            .or_else(|| context.variables.get("pipeline_dop"))
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|dop| *dop > 0)?;
+        Some(current * 2)
+    }
+
+    /// The operator a hotspot points at, by its node reference or its `FragmentF.PipelineP.NAME` path.
+    fn operator_context(profile: &Profile, hotspot: &HotSpot) -> Option<OperatorContext> {
+        let node = &hotspot.node;
+        let from_path = || {
+            let mut parts = hotspot.node_path.split(", ").next()?.splitn(3, '.');
+            let fragment_id = parts.next()?.strip_prefix("Fragment")?.to_string();
+            let pipeline_id = parts.next()?.strip_prefix("Pipeline")?.to_string();
+            Some((fragment_id, pipeline_id, parts.next()?.to_string()))
+        };
+        let (fragment_id, pipeline_id, operator_name) = match (&node.fragment_id, &node.pipeline_id, &node.operator_name) {
+            (Some(f), Some(p), Some(o)) => (f.clone(), p.clone(), o.clone()),
+            _ => from_path()?,
+        };
+
+        let fragment = profile.fragments.iter().find(|f| f.id == fragment_id)?;
+        let pipeline = fragment.pipelines.iter().find(|p| p.id == pipeline_id)?;
+        // tree nodes are named after the plan node (`OLAP_SCAN`), not the operator (`CONNECTOR_SCAN`)
+        let plan_node_id = node.plan_node_id.map(|id| id.to_string());
+        let operator = pipeline.operators.iter()
+            .find(|op| op.name == operator_name)
+            .or_else(|| pipeline.operators.iter().find(|op| plan_node_id.is_some() && op.plan_node_id == plan_node_id))?;
+        Some(OperatorContext::new(profile, fragment, pipeline, operator))
     }
 
     pub fn generate_conclusion(hotspots: &[HotSpot], profile: &Profile) -> String {
         if hotspots.is_empty() {
-            return "查询执行良好，未发现明显性能问题。".to_string();
+            return tr!("conclusion.healthy");
         }
 
         let severe_count = hotspots.iter().filter(|h| matches!(h.severity, HotSeverity::Severe | HotSeverity::Critical)).count();
@@ -135,22 +238,18 @@ impl SuggestionEngine {
         let total_time = Self::parse_total_time(&profile.summary.total_time).unwrap_or(0.0);
 
         let conclusion = if severe_count > 0 {
-            format!(
-                "查询存在{}个严重性能问题，执行时间较长（{}）。主要问题是{}。建议优先解决严重问题。",
-                severe_count,
-                Self::format_duration(total_time),
-                hotspots.first().unwrap().issue_type
+            tr!(
+                "conclusion.severe",
+                count = severe_count,
+                duration = Self::format_duration(total_time),
+                issue = i18n::issue_title(&hotspots.first().unwrap().issue_type),
             )
         } else if moderate_count > 2 {
-            format!(
-                "查询存在{}个中等程度性能问题，整体性能需优化。执行时间{}。",
-                moderate_count,
-                Self::format_duration(total_time)
-            )
+            tr!("conclusion.moderate", count = moderate_count, duration = Self::format_duration(total_time))
         } else if total_time > 300.0f64 {
-            format!("查询执行时间较长（{}），建议关注性能热点。", Self::format_duration(total_time))
+            tr!("conclusion.long_running", duration = Self::format_duration(total_time))
         } else {
-            format!("查询发现{}个小问题，整体性能可接受。", hotspots.len())
+            tr!("conclusion.minor", count = hotspots.len())
         };
 
         conclusion
@@ -169,12 +268,7 @@ impl SuggestionEngine {
         }
 
 
-        let general_suggestions = vec![
-            "考虑启用查询缓存以提高重复查询的性能".to_string(),
-            "检查硬件资源（CPU、内存、存储）是否充足".to_string(),
-            "定期维护表统计信息以优化查询计划".to_string(),
-            "考虑使用查询队列管理来避免资源争用".to_string(),
-        ];
+        let general_suggestions = tr_list!("general.suggestions");
 
         for suggestion in general_suggestions {
             if unique_suggestions.insert(suggestion.clone()) {
@@ -185,34 +279,83 @@ impl SuggestionEngine {
         suggestions
     }
 
+    /// The score of the active rule file's formula; see `score_breakdown` for how it is reached.
     pub fn calculate_performance_score(hotspots: &[HotSpot], profile: &Profile) -> f64 {
-        let mut score: f64 = 100.0;
+        Self::score_breakdown(hotspots, profile, &crate::rules::active().score).score
+    }
 
+    /// Scores hotspots ranked most severe first, as `HotSpotDetector` returns them: the first
+    /// hotspot of an issue type costs the full penalty, later ones decay.
+    pub fn score_breakdown(hotspots: &[HotSpot], profile: &Profile, config: &ScoreConfig) -> ScoreBreakdown {
+        let mut items = Vec::new();
+        let mut repeats: HashMap<&IssueCode, i32> = HashMap::new();
+
+        for (index, hotspot) in hotspots.iter().enumerate() {
+            let repeat = repeats.entry(&hotspot.issue_type).or_insert(0);
+            let severity_penalty = config.severity_penalty(&hotspot.severity);
+            let confidence_weight = config.confidence_weight(hotspot.confidence);
+            let decay = config.repeat_decay.powi(*repeat);
+            *repeat += 1;
+            let penalty = severity_penalty * confidence_weight * decay;
+            if penalty <= 0.0 {
+                continue;
+            }
 
-        for hotspot in hotspots {
-            let penalty = match hotspot.severity {
-                HotSeverity::Critical => 25.0,
-                HotSeverity::Severe => 15.0,
-                HotSeverity::High => 12.0,
-                HotSeverity::Moderate => 8.0,
-                HotSeverity::Mild => 3.0,
-                HotSeverity::Normal => 0.0,
-            };
-            score -= penalty;
+            let mut reason = tr!("score.severity", severity = format!("{:?}", hotspot.severity), points = severity_penalty);
+            if confidence_weight != 1.0 {
+                reason.push_str(&tr!("score.confidence", confidence = format!("{:?}", hotspot.confidence), weight = confidence_weight));
+            }
+            if decay != 1.0 {
+                reason.push_str(&tr!("score.repeat", repeat = repeat, weight = decay));
+            }
+            items.push(ScoreItem {
+                points: -penalty,
+                dimension: Some(config.dimension(&hotspot.issue_type)),
+                source: ScoreSource::Hotspot {
+                    index,
+                    issue_type: hotspot.issue_type.clone(),
+                    node_path: hotspot.node_path.clone(),
+                },
+                reason,
+            });
         }
 
-
-        if let Ok(total_seconds) = Self::parse_total_time(&profile.summary.total_time) {
-            if total_seconds > 3600.0 {
-                score -= 20.0;
-            } else if total_seconds > 1800.0 {
-                score -= 10.0;
-            } else if total_seconds > 300.0 {
-                score -= 5.0;
+        let vars = |name: &str| RuleEngine::query_var(profile, name);
+        for adjustment in &config.adjustments {
+            let Some(value) = adjustment.metric.eval(&vars) else {
+                continue;
+            };
+            if let Some(level) = adjustment.level_for(value) {
+                items.push(ScoreItem {
+                    points: level.points,
+                    dimension: adjustment.definition.dimension,
+                    source: ScoreSource::Metric {
+                        adjustment: adjustment.definition.id.clone(),
+                        metric: adjustment.definition.metric.clone(),
+                        value,
+                    },
+                    reason: level.label.current().clone(),
+                });
             }
         }
 
-        score.max(0.0)
+        // `None` scores every item, a dimension only its own
+        let score_of = |dimension: Option<ScoreDimension>| {
+            let points: f64 = items.iter()
+                .filter(|item| dimension.is_none() || item.dimension == dimension)
+                .map(|item| item.points)
+                .sum();
+            (config.base + points).clamp(0.0, 100.0)
+        };
+        let sub_scores = ScoreDimension::ALL.iter().map(|&d| (d, score_of(Some(d)))).collect();
+        let score = score_of(None);
+
+        ScoreBreakdown {
+            score,
+            base: config.base,
+            sub_scores,
+            items,
+        }
     }
 
     fn parse_total_time(time_str: &str) -> Result<f64, ()> {
@@ -229,11 +372,11 @@ impl SuggestionEngine {
 
     fn format_duration(seconds: f64) -> String {
         if seconds >= 3600.0 {
-            format!("{:.1}小时", seconds / 3600.0)
+            tr!("duration.hours", value = seconds / 3600.0)
         } else if seconds >= 60.0 {
-            format!("{:.0}分钟", seconds / 60.0)
+            tr!("duration.minutes", value = seconds / 60.0)
         } else {
-            format!("{:.1}秒", seconds)
+            tr!("duration.seconds", value = seconds)
         }
     }
 
@@ -268,20 +411,6 @@ impl SuggestionEngine {
         }
     }
 
-    fn format_bytes(bytes: u64) -> String {
-        const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
-
-        let mut size = bytes as f64;
-        let mut unit_index = 0;
-
-        while size >= 1024.0 && unit_index < UNITS.len() - 1 {
-            size /= 1024.0;
-            unit_index += 1;
-        }
-
-        format!("{:.3} {}", size, UNITS[unit_index])
-    }
-
     pub fn parse_duration(duration_str: &str) -> Result<Duration, ()> {
         let mut total_nanos: u128 = 0;
 
@@ -322,3 +451,93 @@ impl SuggestionEngine {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{hotspot, load, profile};
+    use crate::rules::RuleSet;
+
+    fn timed_profile(total_time_ms: f64) -> Profile {
+        let mut profile = profile(Vec::new());
+        profile.summary.total_time_ms = Some(total_time_ms);
+        profile
+    }
+
+    fn scored_hotspot(issue_type: IssueCode, severity: HotSeverity, confidence: Confidence) -> HotSpot {
+        HotSpot {
+            node_path: "Fragment0.Pipeline0.OLAP_SCAN".to_string(),
+            confidence,
+            ..hotspot(issue_type, severity)
+        }
+    }
+
+    #[test]
+    fn test_score_breakdown() {
+        let hotspots = vec![
+            scored_hotspot(IssueCode::RowsetFragmentation, HotSeverity::Severe, Confidence::High),
+            scored_hotspot(IssueCode::DataSkew, HotSeverity::Moderate, Confidence::Medium),
+            scored_hotspot(IssueCode::DataSkew, HotSeverity::Moderate, Confidence::Medium),
+        ];
+        let rules = RuleSet::builtin();
+        let breakdown = SuggestionEngine::score_breakdown(&hotspots, &timed_profile(2_000_000.0), &rules.score);
+
+        let points: Vec<f64> = breakdown.items.iter().map(|item| item.points).collect();
+        assert_eq!(points, [-15.0, -6.0, -3.0, -10.0]);
+        assert_eq!(breakdown.score, 66.0);
+        assert_eq!(breakdown.sub_scores[&ScoreDimension::Io], 85.0);
+        assert_eq!(breakdown.sub_scores[&ScoreDimension::Cpu], 91.0);
+        assert_eq!(breakdown.sub_scores[&ScoreDimension::Memory], 100.0);
+        assert!(matches!(&breakdown.items[2].source, ScoreSource::Hotspot { index: 2, .. }));
+        assert!(matches!(&breakdown.items[3].source, ScoreSource::Metric { adjustment, .. } if adjustment == "query_duration"));
+        assert!(breakdown.items[3].dimension.is_none());
+    }
+
+    #[test]
+    fn test_context_recipes() {
+        let context = OperatorContext {
+            operator_name: "OLAP_SCAN".to_string(),
+            metrics: [("Table", "orders"), ("Predicates", "2: status = 'PAID'")]
+                .iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            pipeline_metrics: [("DegreeOfParallelism".to_string(), "4".to_string())].into_iter().collect(),
+            default_db: Some("default_db".to_string()),
+            sql_statement: "select * from sales.orders where status = 'PAID'".to_string(),
+            ..OperatorContext::default()
+        };
+        let sql = |hotspot_type: &str| {
+            SuggestionEngine::generate_official_recipes(hotspot_type, &context)[0].statement.clone().unwrap().sql
+        };
+
+        assert_eq!(sql("fragmented_rowsets"), "ALTER TABLE sales.orders COMPACT;");
+        assert_eq!(sql("DataSkew"), "ANALYZE TABLE sales.orders;");
+        assert_eq!(sql("IneffectiveZoneMap"), "CREATE INDEX idx_status ON sales.orders (status) USING BITMAP;");
+        assert_eq!(sql("InsufficientParallelism"), "SET pipeline_dop = 8;");
+        // shared-nothing tables have no Data Cache to warm up
+        assert!(SuggestionEngine::generate_official_recipes("HighIoTime", &context).iter().all(|step| step.statement.is_none()));
+        let remote = OperatorContext { operator_name: "CONNECTOR_SCAN".to_string(), ..context.clone() };
+        assert_eq!(
+            SuggestionEngine::generate_official_recipes("HighIoTime", &remote)[0].statement.clone().unwrap().sql,
+            "CACHE SELECT * FROM sales.orders;",
+        );
+
+        let recipes = SuggestionEngine::generate_official_recipes("RowsetFragmentation", &OperatorContext::default());
+        assert!(recipes.iter().all(|step| step.statement.is_none()));
+        assert_eq!(recipes[0].advice, tr!("recipe_manual.compact"));
+        assert_eq!(recipes.len(), tr_list!("recipe.fragmented_rowsets").len() + 1);
+        assert!(!SuggestionEngine::generate_official_recipes("Unknown", &context).is_empty());
+    }
+
+    #[test]
+    fn test_compaction_recipe_profile1() {
+        let profile = load("profile1.txt");
+        let hotspots = crate::HotSpotDetector::analyze(&profile);
+        let recipes = SuggestionEngine::generate_recipes(&hotspots, &profile);
+        let fragmentation = recipes.iter()
+            .find(|r| hotspots[r.hotspot.index].issue_type == IssueCode::RowsetFragmentation)
+            .unwrap();
+        let sql = fragmentation.steps.iter().find_map(|step| step.statement.as_ref()).unwrap().sql.clone();
+        assert!(sql.starts_with("ALTER TABLE ") && sql.ends_with("ads_user_basic_portrait_all_d_all_1015 COMPACT;"), "{}", sql);
+        // the statement replaces the generic "ALTER TABLE ... COMPACT" advice
+        assert!(fragmentation.steps.iter().all(|step| !step.advice.contains("ALTER TABLE ...")));
+    }
+}
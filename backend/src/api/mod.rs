@@ -1,6 +1,7 @@
 use warp::Filter;
 use serde_json::json;
 use serde::{Serialize, Deserialize};
+use crate::i18n::{self, Lang};
 use crate::static_files::StaticFiles;
 use mime_guess;
 
@@ -9,10 +10,33 @@ struct AnalyzeRequest {
     profile_text: String,
 }
 
+#[derive(Deserialize)]
+struct ExplainNodeRequest {
+    profile_text: String,
+    plan_node_id: i32,
+}
+
+/// `?lang=en|zh` of the analyze endpoints.
+#[derive(Deserialize)]
+struct LangQuery {
+    lang: Option<String>,
+}
+
+/// Output language: the `lang` parameter, then `Accept-Language`, then the default.
+fn output_language() -> impl Filter<Extract = (Lang,), Error = warp::Rejection> + Clone {
+    warp::query::<LangQuery>()
+        .and(warp::header::optional::<String>("accept-language"))
+        .map(|query: LangQuery, accept_language: Option<String>| {
+            query.lang.as_deref().and_then(Lang::parse)
+                .or_else(|| accept_language.as_deref().and_then(Lang::from_accept_language))
+                .unwrap_or_default()
+        })
+}
+
 pub async fn start_server(host: String, port: u16) {
     let cors = warp::cors()
         .allow_any_origin()
-        .allow_headers(vec!["content-type"])
+        .allow_headers(vec!["content-type", "accept-language"])
         .allow_methods(vec!["GET", "POST"]);
 
     let analyze_profile_json = warp::path("api")
@@ -20,6 +44,7 @@ pub async fn start_server(host: String, port: u16) {
         .and(warp::post())
         .and(warp::body::content_length_limit(1024 * 1024 * 50))
         .and(warp::body::json())
+        .and(output_language())
         .and_then(handle_analyze_profile);
 
     use crate::constants::file_limits;
@@ -29,15 +54,55 @@ pub async fn start_server(host: String, port: u16) {
         .and(warp::post())
         .and(warp::body::content_length_limit(file_limits::MAX_UPLOAD_SIZE))
         .and(warp::multipart::form().max_length(file_limits::MAX_UPLOAD_SIZE))
+        .and(output_language())
         .and_then(handle_analyze_profile_file);
 
+    let explain_node = warp::path("api")
+        .and(warp::path("explain-node"))
+        .and(warp::post())
+        .and(warp::body::content_length_limit(1024 * 1024 * 50))
+        .and(warp::body::json())
+        .and(output_language())
+        .and_then(handle_explain_node);
+
+    let glossary = warp::path("api")
+        .and(warp::path("glossary"))
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(output_language())
+        .map(|lang: Lang| {
+            let entries = i18n::with_lang(lang, crate::glossary::all);
+            warp::reply::json(&AnalyzeResponse { success: true, error: None, data: Some(entries) })
+        });
+
+    let glossary_entry = warp::path("api")
+        .and(warp::path("glossary"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(warp::get())
+        .and(output_language())
+        .map(|metric: String, lang: Lang| {
+            let response = match i18n::with_lang(lang, || crate::glossary::entry(&metric)) {
+                Some(entry) => AnalyzeResponse { success: true, error: None, data: Some(entry) },
+                None => AnalyzeResponse {
+                    success: false,
+                    error: Some(i18n::t_in(lang, "error.metric_not_found", &[("metric", &metric)])),
+                    data: None,
+                },
+            };
+            warp::reply::json(&response)
+        });
+
     let health = warp::path("health")
         .and(warp::get())
         .map(|| warp::reply::json(&json!({"status": "ok"})));
 
     let api_routes = health
         .or(analyze_profile_json)
-        .or(analyze_profile_file_api);
+        .or(analyze_profile_file_api)
+        .or(explain_node)
+        .or(glossary)
+        .or(glossary_entry);
 
     let static_routes = warp::get()
         .and(warp::path::tail())
@@ -59,13 +124,13 @@ pub async fn start_server(host: String, port: u16) {
 }
 
 #[derive(Serialize)]
-struct AnalyzeResponse {
+struct AnalyzeResponse<T = crate::models::ProfileAnalysisResponse> {
     success: bool,
     error: Option<String>,
-    data: Option<crate::models::ProfileAnalysisResponse>,
+    data: Option<T>,
 }
 
-async fn handle_analyze_profile_file(mut form: warp::multipart::FormData) -> Result<impl warp::Reply, warp::Rejection> {
+async fn handle_analyze_profile_file(mut form: warp::multipart::FormData, lang: Lang) -> Result<impl warp::Reply, warp::Rejection> {
     use futures::TryStreamExt;
     use bytes::Buf;
     
@@ -92,7 +157,7 @@ async fn handle_analyze_profile_file(mut form: warp::multipart::FormData) -> Res
         })));
     }
     
-    match crate::analyze_profile(&profile_text) {
+    match crate::analyze_profile_in(&profile_text, lang) {
         Ok(result) => {
             let response = AnalyzeResponse {
                 success: true,
@@ -102,9 +167,9 @@ async fn handle_analyze_profile_file(mut form: warp::multipart::FormData) -> Res
             Ok(warp::reply::json(&response))
         }
         Err(err) => {
-            let response = AnalyzeResponse {
+            let response: AnalyzeResponse = AnalyzeResponse {
                 success: false,
-                error: Some(format!("解析Profile失败: {}", err)),
+                error: Some(i18n::t_in(lang, "error.parse_profile", &[("error", &err)])),
                 data: None,
             };
             Ok(warp::reply::json(&response))
@@ -112,8 +177,8 @@ async fn handle_analyze_profile_file(mut form: warp::multipart::FormData) -> Res
     }
 }
 
-async fn handle_analyze_profile(req: AnalyzeRequest) -> Result<impl warp::Reply, warp::Rejection> {
-    match crate::analyze_profile(&req.profile_text) {
+async fn handle_analyze_profile(req: AnalyzeRequest, lang: Lang) -> Result<impl warp::Reply, warp::Rejection> {
+    match crate::analyze_profile_in(&req.profile_text, lang) {
         Ok(result) => {
             let response = AnalyzeResponse {
                 success: true,
@@ -123,7 +188,7 @@ async fn handle_analyze_profile(req: AnalyzeRequest) -> Result<impl warp::Reply,
             Ok(warp::reply::json(&response))
         }
         Err(err) => {
-            let response = AnalyzeResponse {
+            let response: AnalyzeResponse = AnalyzeResponse {
                 success: false,
                 error: Some(err),
                 data: None,
@@ -133,6 +198,14 @@ async fn handle_analyze_profile(req: AnalyzeRequest) -> Result<impl warp::Reply,
     }
 }
 
+async fn handle_explain_node(req: ExplainNodeRequest, lang: Lang) -> Result<impl warp::Reply, warp::Rejection> {
+    let response = match crate::explain_node_in(&req.profile_text, req.plan_node_id, lang) {
+        Ok(explanation) => AnalyzeResponse { success: true, error: None, data: Some(explanation) },
+        Err(err) => AnalyzeResponse { success: false, error: Some(err), data: None },
+    };
+    Ok(warp::reply::json(&response))
+}
+
 async fn serve_static(path: warp::path::Tail) -> Result<impl warp::Reply, warp::Rejection> {
     let path_str = path.as_str();
     
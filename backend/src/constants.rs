@@ -8,6 +8,16 @@ pub mod time_thresholds {
     pub const METRIC_CONSUMING_THRESHOLD: f64 = 0.3;
     
     pub const MAX_REASONABLE_BASE_TIME_MS: f64 = 100_000.0;
+
+    /// `most_consuming_percentage` of the active rule file, defaulting to the constant.
+    pub fn most_consuming() -> f64 {
+        crate::rules::active().threshold("most_consuming_percentage").unwrap_or(MOST_CONSUMING_THRESHOLD)
+    }
+
+    /// `second_consuming_percentage` of the active rule file, defaulting to the constant.
+    pub fn second_consuming() -> f64 {
+        crate::rules::active().threshold("second_consuming_percentage").unwrap_or(SECOND_CONSUMING_THRESHOLD)
+    }
 }
 
 pub mod top_n {
@@ -24,6 +34,9 @@ pub mod starrocks {
     pub const MERGED_INFO_PREFIX_MIN: &str = "__MIN_OF_";
     
     pub const FINAL_SINK_PSEUDO_PLAN_NODE_ID: i32 = -1;
+
+    /// Default of the `query_timeout` session variable, in seconds.
+    pub const DEFAULT_QUERY_TIMEOUT_SECS: u64 = 300;
 }
 
 pub mod performance {
@@ -0,0 +1,159 @@
+//! Built-in glossary of StarRocks profile counters.
+//!
+//! The entries ship as `glossary/metrics.toml` and are compiled into the binary; they are looked
+//! up by counter name, with or without the `__MAX_OF_` / `__MIN_OF_` prefix of merged counters.
+//! Hotspot evidence points at the entries of the counters it is computed from
+//! (`Evidence::glossary`).
+
+use crate::constants::starrocks::{MERGED_INFO_PREFIX_MAX, MERGED_INFO_PREFIX_MIN};
+use crate::i18n::Localized;
+use crate::models::MetricUnit;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const METRICS: &str = include_str!("../glossary/metrics.toml");
+
+/// Where in the profile a counter is reported.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricLevel {
+    Query,
+    Fragment,
+    Pipeline,
+    #[default]
+    Operator,
+}
+
+/// A glossary entry as written in the glossary file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricEntry {
+    pub meaning: Localized<String>,
+    /// Durations are listed in milliseconds, like evidence values.
+    pub unit: MetricUnit,
+    #[serde(default)]
+    pub level: MetricLevel,
+    /// Operators that emit the counter; empty for every operator of the level.
+    #[serde(default)]
+    pub operators: Vec<String>,
+    #[serde(default)]
+    pub good: Option<Localized<String>>,
+    #[serde(default)]
+    pub bad: Option<Localized<String>>,
+    #[serde(default)]
+    pub session_variables: Vec<String>,
+    #[serde(default)]
+    pub be_configs: Vec<String>,
+}
+
+/// A glossary entry in the current language, as served by the API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlossaryEntry {
+    pub metric: String,
+    pub meaning: String,
+    pub unit: MetricUnit,
+    pub level: MetricLevel,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub operators: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub good: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bad: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub session_variables: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub be_configs: Vec<String>,
+}
+
+impl MetricEntry {
+    pub fn localized(&self, metric: &str) -> GlossaryEntry {
+        GlossaryEntry {
+            metric: metric.to_string(),
+            meaning: self.meaning.current().clone(),
+            unit: self.unit,
+            level: self.level,
+            operators: self.operators.clone(),
+            good: self.good.as_ref().map(|text| text.current().clone()),
+            bad: self.bad.as_ref().map(|text| text.current().clone()),
+            session_variables: self.session_variables.clone(),
+            be_configs: self.be_configs.clone(),
+        }
+    }
+}
+
+static ENTRIES: Lazy<BTreeMap<String, MetricEntry>> =
+    Lazy::new(|| toml::from_str(METRICS).expect("built-in glossary is valid"));
+
+/// Every entry by counter name.
+pub fn entries() -> &'static BTreeMap<String, MetricEntry> {
+    &ENTRIES
+}
+
+/// Every entry in the current language, by counter name.
+pub fn all() -> Vec<GlossaryEntry> {
+    ENTRIES.iter().map(|(metric, entry)| entry.localized(metric)).collect()
+}
+
+pub fn lookup(metric: &str) -> Option<&'static MetricEntry> {
+    ENTRIES.get(base_name(metric))
+}
+
+/// The entry of a counter in the current language.
+pub fn entry(metric: &str) -> Option<GlossaryEntry> {
+    let name = base_name(metric);
+    ENTRIES.get(name).map(|entry| entry.localized(name))
+}
+
+/// The glossary counters a metric name is made of: `PredFilterRows / RawRowsRead`,
+/// `__MAX_OF_ScanTime` or `ScanTime max/avg` all point at their counters.
+pub fn references(metric: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for word in metric.split(|c: char| !c.is_ascii_alphanumeric()) {
+        if ENTRIES.contains_key(word) && !names.iter().any(|name| name == word) {
+            names.push(word.to_string());
+        }
+    }
+    names
+}
+
+fn base_name(metric: &str) -> &str {
+    metric.strip_prefix(MERGED_INFO_PREFIX_MAX)
+        .or_else(|| metric.strip_prefix(MERGED_INFO_PREFIX_MIN))
+        .unwrap_or(metric)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::i18n::{self, Lang};
+
+    fn translated(text: &Localized<String>) -> bool {
+        match text {
+            Localized::ByLang(values) => Lang::ALL.iter().all(|lang| values.contains_key(lang)),
+            Localized::Plain(_) => false,
+        }
+    }
+
+    #[test]
+    fn test_glossary_entries() {
+        assert!(entries().len() > 60);
+        for (name, entry) in entries() {
+            let texts = [Some(&entry.meaning), entry.good.as_ref(), entry.bad.as_ref()];
+            assert!(texts.into_iter().flatten().all(translated), "{} is not translated", name);
+        }
+
+        let entry = i18n::with_lang(Lang::En, || entry("__MAX_OF_PeakDriverQueueSize")).expect("known counter");
+        assert_eq!((entry.metric.as_str(), entry.unit, entry.level), ("PeakDriverQueueSize", MetricUnit::Count, MetricLevel::Pipeline));
+        assert!(entry.bad.is_some());
+        assert_eq!(entry.be_configs, ["pipeline_exec_thread_pool_thread_num"]);
+        assert!(lookup("NoSuchCounter").is_none());
+    }
+
+    #[test]
+    fn test_references() {
+        assert_eq!(references("PredFilterRows / RawRowsRead"), ["PredFilterRows", "RawRowsRead"]);
+        assert_eq!(references("__MAX_OF_ScanTime"), ["ScanTime"]);
+        assert_eq!(references("ScanTime max/avg"), ["ScanTime"]);
+        assert!(references("EstimatedIoSaving").is_empty());
+    }
+}
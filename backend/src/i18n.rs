@@ -0,0 +1,319 @@
+//! Message catalog for the analysis output.
+//!
+//! Every user-facing text is a template in `messages/<lang>.toml`, looked up by a dotted key
+//! (`section.name`) and rendered with named `{param}` placeholders; `{param:.N}` renders a number
+//! with N decimals. Entries are either one string or a list of strings (recipes).
+//!
+//! The language is chosen per analysis with [`with_lang`]; analyzers render through [`t`] / [`tr!`]
+//! in the language of the analysis running on the current thread.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt::Display;
+
+const ZH_MESSAGES: &str = include_str!("../messages/zh.toml");
+const EN_MESSAGES: &str = include_str!("../messages/en.toml");
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    #[default]
+    Zh,
+    En,
+}
+
+impl Lang {
+    pub const ALL: [Lang; 2] = [Lang::Zh, Lang::En];
+
+    /// `zh`, `zh-CN`, `en-US`, ... by primary subtag.
+    pub fn parse(tag: &str) -> Option<Lang> {
+        let primary = tag.trim().split(['-', '_']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "zh" => Some(Lang::Zh),
+            "en" => Some(Lang::En),
+            _ => None,
+        }
+    }
+
+    /// The supported language with the highest `q` in an `Accept-Language` header.
+    pub fn from_accept_language(header: &str) -> Option<Lang> {
+        header.split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let lang = Lang::parse(parts.next()?)?;
+                let q = parts
+                    .find_map(|p| p.trim().strip_prefix("q="))
+                    .and_then(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                (q > 0.0).then_some((lang, q))
+            })
+            // the first of equally weighted ranges wins
+            .fold(None, |best: Option<(Lang, f32)>, (lang, q)| match best {
+                Some((_, best_q)) if best_q >= q => best,
+                _ => Some((lang, q)),
+            })
+            .map(|(lang, _)| lang)
+    }
+}
+
+impl std::str::FromStr for Lang {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Lang::parse(s).ok_or_else(|| format!("unsupported language: {}", s))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// One language's templates by dotted key.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    entries: HashMap<String, Entry>,
+}
+
+impl Catalog {
+    pub fn parse(text: &str) -> Result<Catalog, toml::de::Error> {
+        let table: toml::Table = toml::from_str(text)?;
+        let mut entries = HashMap::new();
+        Self::flatten("", toml::Value::Table(table), &mut entries)?;
+        Ok(Catalog { entries })
+    }
+
+    fn flatten(prefix: &str, value: toml::Value, entries: &mut HashMap<String, Entry>) -> Result<(), toml::de::Error> {
+        match value {
+            toml::Value::Table(table) => {
+                for (key, value) in table {
+                    let key = if prefix.is_empty() { key } else { format!("{}.{}", prefix, key) };
+                    Self::flatten(&key, value, entries)?;
+                }
+            }
+            value => {
+                entries.insert(prefix.to_string(), value.try_into()?);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.entries.keys().map(String::as_str)
+    }
+
+    pub fn contains(&self, key: &str) -> bool {
+        self.entries.contains_key(key)
+    }
+
+    /// The raw templates of an entry, one for a string entry.
+    pub fn templates(&self, key: &str) -> Option<Vec<&str>> {
+        self.entries.get(key).map(|entry| match entry {
+            Entry::One(text) => vec![text.as_str()],
+            Entry::Many(texts) => texts.iter().map(String::as_str).collect(),
+        })
+    }
+}
+
+/// Text written in a rule file: one value for every language, or one per language
+/// (`message = { zh = "...", en = "..." }`). Missing languages fall back to Chinese, then to
+/// any given language.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum Localized<T> {
+    Plain(T),
+    ByLang(HashMap<Lang, T>),
+}
+
+impl<T: Default> Default for Localized<T> {
+    fn default() -> Self {
+        Localized::Plain(T::default())
+    }
+}
+
+impl<T> Localized<T> {
+    /// The value in the current language.
+    pub fn current(&self) -> &T {
+        self.get(current())
+    }
+
+    /// Panics on an empty per-language table; rule files are checked with [`Localized::is_valid`].
+    pub fn get(&self, lang: Lang) -> &T {
+        match self {
+            Localized::Plain(value) => value,
+            Localized::ByLang(values) => values.get(&lang)
+                .or_else(|| values.get(&Lang::Zh))
+                .or_else(|| values.values().next())
+                .expect("localized text has at least one language"),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        match self {
+            Localized::Plain(_) => true,
+            Localized::ByLang(values) => !values.is_empty(),
+        }
+    }
+}
+
+static CATALOGS: Lazy<HashMap<Lang, Catalog>> = Lazy::new(|| {
+    HashMap::from([
+        (Lang::Zh, Catalog::parse(ZH_MESSAGES).expect("built-in zh catalog is valid")),
+        (Lang::En, Catalog::parse(EN_MESSAGES).expect("built-in en catalog is valid")),
+    ])
+});
+
+pub fn catalog(lang: Lang) -> &'static Catalog {
+    &CATALOGS[&lang]
+}
+
+thread_local! {
+    static CURRENT: Cell<Lang> = const { Cell::new(Lang::Zh) };
+}
+
+/// The language of the analysis running on this thread.
+pub fn current() -> Lang {
+    CURRENT.with(Cell::get)
+}
+
+/// Runs `f` with `lang` as the output language of this thread.
+pub fn with_lang<R>(lang: Lang, f: impl FnOnce() -> R) -> R {
+    struct Restore(Lang);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            CURRENT.with(|c| c.set(self.0));
+        }
+    }
+
+    let _restore = Restore(CURRENT.with(|c| c.replace(lang)));
+    f()
+}
+
+/// Renders `key` in the current language. Unknown keys render as the key itself.
+pub fn t(key: &str, args: &[(&str, &dyn Display)]) -> String {
+    t_in(current(), key, args)
+}
+
+pub fn t_in(lang: Lang, key: &str, args: &[(&str, &dyn Display)]) -> String {
+    t_list_in(lang, key, args).join("")
+}
+
+/// Renders every template of a list entry in the current language.
+pub fn t_list(key: &str, args: &[(&str, &dyn Display)]) -> Vec<String> {
+    t_list_in(current(), key, args)
+}
+
+pub fn t_list_in(lang: Lang, key: &str, args: &[(&str, &dyn Display)]) -> Vec<String> {
+    match catalog(lang).templates(key) {
+        Some(templates) => templates.into_iter().map(|template| render(template, args)).collect(),
+        None => {
+            tracing::warn!("Missing {:?} message: {}", lang, key);
+            vec![key.to_string()]
+        }
+    }
+}
+
+/// Localized name of an issue code; custom codes are shown as they are.
+pub fn issue_title(issue_type: &crate::models::IssueCode) -> String {
+    let key = format!("issue.{}", issue_type.as_str());
+    if catalog(current()).contains(&key) {
+        t(&key, &[])
+    } else {
+        issue_type.to_string()
+    }
+}
+
+/// Replaces `{name}` and `{name:.N}` placeholders; unknown names are left in place.
+pub fn render(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let placeholder = &rest[start + 1..start + len];
+        let (name, precision) = match placeholder.split_once(":.") {
+            Some((name, precision)) => (name, precision.parse::<usize>().ok()),
+            None => (placeholder, None),
+        };
+        match args.iter().find(|(arg, _)| *arg == name) {
+            Some((_, value)) => match precision {
+                Some(precision) => out.push_str(&format!("{:.*}", precision, value)),
+                None => out.push_str(&value.to_string()),
+            },
+            None => out.push_str(&rest[start..start + len + 1]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Renders a catalog message in the current language: `tr!("skew.also_skewed", counters = names)`.
+#[macro_export]
+macro_rules! tr {
+    ($key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::t($key, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
+
+/// Like [`tr!`] for list entries, returning one string per template.
+#[macro_export]
+macro_rules! tr_list {
+    ($key:expr $(, $name:ident = $value:expr)* $(,)?) => {
+        $crate::i18n::t_list($key, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::IssueCode;
+    use std::collections::BTreeSet;
+
+    fn placeholders(template: &str) -> BTreeSet<String> {
+        template.split('{').skip(1)
+            .filter_map(|s| s.split_once('}').map(|(p, _)| p.split(':').next().unwrap_or(p).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_catalogs_match() {
+        let zh = catalog(Lang::Zh);
+        let en = catalog(Lang::En);
+        let zh_keys: BTreeSet<&str> = zh.keys().collect();
+        let en_keys: BTreeSet<&str> = en.keys().collect();
+        assert_eq!(zh_keys.difference(&en_keys).collect::<Vec<_>>(), Vec::<&&str>::new(), "missing in en");
+        assert_eq!(en_keys.difference(&zh_keys).collect::<Vec<_>>(), Vec::<&&str>::new(), "missing in zh");
+
+        for key in zh_keys {
+            let (zh, en) = (zh.templates(key).unwrap(), en.templates(key).unwrap());
+            assert_eq!(zh.len(), en.len(), "{}", key);
+            for (zh, en) in zh.iter().zip(&en) {
+                assert_eq!(placeholders(zh), placeholders(en), "{}", key);
+            }
+        }
+        for code in IssueCode::ALL {
+            assert!(zh.contains(&format!("issue.{}", code)), "no title for {}", code);
+        }
+    }
+
+    #[test]
+    fn test_render_and_language() {
+        assert_eq!(render("{a} / {b:.1} / {c}", &[("a", &"x"), ("b", &2.345)]), "x / 2.3 / {c}");
+        assert_eq!(Lang::from_accept_language("fr-FR, en-US;q=0.8, zh;q=0.9"), Some(Lang::Zh));
+        assert_eq!(Lang::from_accept_language("en-GB,en;q=0.9"), Some(Lang::En));
+        assert_eq!(Lang::from_accept_language("fr"), None);
+
+        assert_eq!(current(), Lang::Zh);
+        let title = with_lang(Lang::En, || issue_title(&IssueCode::DataSkew));
+        assert_eq!(title, "Data skew");
+        assert_eq!(current(), Lang::Zh);
+        assert_eq!(issue_title(&IssueCode::Custom("Mine".to_string())), "Mine");
+    }
+}
@@ -3,23 +3,87 @@ pub mod models;
 pub mod analyzer;
 pub mod api;
 pub mod constants;
+pub mod i18n;
+pub mod rules;
+pub mod glossary;
 pub mod static_files;
+#[cfg(test)]
+mod test_support;
 
 pub use models::*;
+pub use i18n::Lang;
 pub use analyzer::hotspot_detector::HotSpotDetector;
 pub use analyzer::suggestion_engine::SuggestionEngine;
+pub use analyzer::critical_path::CriticalPathAnalyzer;
+pub use analyzer::skew_detector::SkewDetector;
+pub use analyzer::exchange_analyzer::ExchangeAnalyzer;
+pub use analyzer::olap_scan_analyzer::OlapScanAnalyzer;
+pub use analyzer::data_cache_analyzer::DataCacheAnalyzer;
+pub use analyzer::external_scan_analyzer::ExternalScanAnalyzer;
+pub use analyzer::aggregate_analyzer::AggregateAnalyzer;
+pub use analyzer::spill_analyzer::SpillAnalyzer;
+pub use analyzer::chunk_analyzer::ChunkAnalyzer;
+pub use analyzer::local_exchange_analyzer::LocalExchangeAnalyzer;
+pub use analyzer::adaptive_dop_analyzer::AdaptiveDopAnalyzer;
+pub use analyzer::lifecycle_analyzer::LifecycleAnalyzer;
+pub use analyzer::efficiency_analyzer::EfficiencyAnalyzer;
+pub use analyzer::failure_analyzer::FailureAnalyzer;
+pub use analyzer::risk_analyzer::RiskAnalyzer;
+pub use analyzer::speedup_estimator::SpeedupEstimator;
+pub use analyzer::root_cause::RootCauseAnalyzer;
+pub use analyzer::node_explainer::NodeExplainer;
+pub use analyzer::rule_engine::RuleEngine;
+pub use analyzer::detector::{Detector, DetectorRegistry, FnDetector};
 pub use parser::ProfileComposer;
 
 pub fn analyze_profile(profile_text: &str) -> Result<ProfileAnalysisResponse, String> {
+    analyze_profile_with(profile_text, &analyzer::detector::active())
+}
+
+/// `analyze_profile` with its descriptions, suggestions and conclusion in `lang`.
+pub fn analyze_profile_in(profile_text: &str, lang: Lang) -> Result<ProfileAnalysisResponse, String> {
+    i18n::with_lang(lang, || analyze_profile(profile_text))
+}
+
+/// `analyze_profile` with an explicit set of hotspot detectors instead of the active registry.
+pub fn analyze_profile_with(profile_text: &str, detectors: &DetectorRegistry) -> Result<ProfileAnalysisResponse, String> {
     let mut composer = ProfileComposer::new();
     let profile = composer.parse(profile_text)
-        .map_err(|e| format!("解析Profile失败: {:?}", e))?;
+        .map_err(|e| tr!("error.parse_profile", error = format!("{:?}", e)))?;
 
-    let hotspots = HotSpotDetector::analyze(&profile);
-    let conclusion = SuggestionEngine::generate_conclusion(&hotspots, &profile);
-    let suggestions = SuggestionEngine::generate_suggestions(&hotspots);
-    let performance_score = SuggestionEngine::calculate_performance_score(&hotspots, &profile);
-    let execution_tree = profile.execution_tree.clone();
+    // a failed query is diagnosed, not scored: its partial execution says little about performance
+    let failure = FailureAnalyzer::diagnose(&profile);
+    let (hotspots, conclusion, suggestions, score_breakdown) = match &failure {
+        Some(diagnosis) => (Vec::new(), diagnosis.explanation.clone(), diagnosis.suggestions.clone(), None),
+        None => {
+            let hotspots = HotSpotDetector::analyze_with(&profile, detectors);
+            let conclusion = SuggestionEngine::generate_conclusion(&hotspots, &profile);
+            let suggestions = SuggestionEngine::generate_suggestions(&hotspots);
+            let breakdown = SuggestionEngine::score_breakdown(&hotspots, &profile, &rules::active().score);
+            (hotspots, conclusion, suggestions, Some(breakdown))
+        }
+    };
+    let performance_score = score_breakdown.as_ref().map(|b| b.score).unwrap_or(0.0);
+    let critical_path = CriticalPathAnalyzer::analyze(&profile);
+    let mut execution_tree = profile.execution_tree.clone();
+    if let (Some(tree), Some(path)) = (execution_tree.as_mut(), critical_path.as_ref()) {
+        CriticalPathAnalyzer::mark_tree(tree, path);
+    }
+    if let Some(tree) = execution_tree.as_mut() {
+        ChunkAnalyzer::mark_tree(tree, &profile);
+    }
+    let data_cache = DataCacheAnalyzer::report(&profile);
+    let chunk_edges = ChunkAnalyzer::report(&profile);
+    let adaptive_dop = AdaptiveDopAnalyzer::report(&profile);
+    let lifecycle = LifecycleAnalyzer::report(&profile);
+    let efficiency = EfficiencyAnalyzer::report(&profile);
+    let risks = if failure.is_none() { RiskAnalyzer::report(&profile) } else { Vec::new() };
+    let root_causes = RootCauseAnalyzer::analyze(&profile, &hotspots);
+    let speedups = SpeedupEstimator::estimate(&profile, execution_tree.as_ref(), critical_path.as_ref(), &hotspots);
+    let mut recipes = SuggestionEngine::generate_recipes(&hotspots, &profile);
+    for recipe in &mut recipes {
+        recipe.expected_gain = speedups.iter().find(|s| s.hotspot.index == recipe.hotspot.index).map(|s| s.gain);
+    }
     let summary = Some(profile.summary.clone());
 
     Ok(ProfileAnalysisResponse {
@@ -27,11 +91,38 @@ pub fn analyze_profile(profile_text: &str) -> Result<ProfileAnalysisResponse, St
         conclusion,
         suggestions,
         performance_score,
+        score_breakdown,
         execution_tree,
         summary,
+        critical_path,
+        data_cache,
+        chunk_edges,
+        adaptive_dop,
+        lifecycle,
+        efficiency,
+        failure,
+        risks,
+        root_causes,
+        recipes,
+        speedups,
     })
 }
 
+/// Why the execution tree node with `plan_node_id` is slow, see [`NodeExplainer`].
+pub fn explain_node(profile_text: &str, plan_node_id: i32) -> Result<NodeExplanation, String> {
+    let mut composer = ProfileComposer::new();
+    let profile = composer.parse(profile_text)
+        .map_err(|e| tr!("error.parse_profile", error = format!("{:?}", e)))?;
+    let hotspots = HotSpotDetector::analyze_with(&profile, &analyzer::detector::active());
+    NodeExplainer::explain(&profile, plan_node_id, &hotspots)
+        .ok_or_else(|| tr!("error.node_not_found", id = plan_node_id))
+}
+
+/// `explain_node` with its narrative in `lang`.
+pub fn explain_node_in(profile_text: &str, plan_node_id: i32, lang: Lang) -> Result<NodeExplanation, String> {
+    i18n::with_lang(lang, || explain_node(profile_text, plan_node_id))
+}
+
 #[cfg(test)]
 mod integration_tests {
     use super::*;
@@ -155,6 +246,39 @@ mod integration_tests {
         println!("🎉 所有profiles解析成功！官方解析逻辑完全兼容");
     }
 
+    #[test]
+    fn test_critical_path_profile3() {
+        let profile_text = fs::read_to_string("../profiles/profile3.txt").expect("Failed to read profile3.txt");
+        let result = analyze_profile(&profile_text).expect("profile3 should parse");
+
+        let path = result.critical_path.expect("critical path should be computed");
+        let first = path.stages.first().unwrap();
+        let last = path.stages.last().unwrap();
+
+        assert_eq!(path.stages.len(), 5);
+        assert!((path.path_time_ms - 168_108.0).abs() < 1.0, "{}", path.path_time_ms);
+        assert!(first.operators.iter().any(|op| op == "CONNECTOR_SCAN"), "path should start at the scan pipeline");
+        assert!(last.operators.iter().any(|op| op == "RESULT_SINK"), "path should end at the result sink");
+        assert!(path.coverage_percentage.unwrap() > 90.0);
+
+        let tree = result.execution_tree.unwrap();
+        let scan = tree.nodes.iter().find(|n| n.plan_node_id == Some(0)).unwrap();
+        assert!(scan.is_on_critical_path);
+    }
+
+    #[test]
+    fn test_chunk_accumulate_profile1() {
+        let profile_text = fs::read_to_string("../profiles/profile1.txt").expect("Failed to read profile1.txt");
+        let result = analyze_profile(&profile_text).expect("profile1 should parse");
+
+        assert!(result.chunk_edges.iter().any(|e| e.from_operator == "CONNECTOR_SCAN" && e.accumulated));
+
+        let tree = result.execution_tree.unwrap();
+        let scan = tree.nodes.iter().find(|n| n.plan_node_id == Some(0)).unwrap();
+        assert!(scan.has_chunk_accumulate);
+        assert!(scan.chunk_fill.unwrap() > 0.5);
+    }
+
     #[test]
     fn test_validate_all_profiles_regression() {
         println!("=== 严格验证所有profiles与官方图片的一致性 ===\n");
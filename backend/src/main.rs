@@ -1,4 +1,5 @@
 use clap::Parser;
+use std::path::PathBuf;
 
 #[derive(Parser, Debug)]
 #[command(name = "starrocks-profile-analyzer")]
@@ -13,6 +14,10 @@ struct Args {
     /// Server host
     #[arg(long, default_value = "0.0.0.0")]
     host: String,
+
+    /// Rule file overriding the built-in hotspot rules
+    #[arg(long)]
+    rules: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -26,7 +31,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("StarRocks Profile Analyzer v0.1.0");
     println!("Starting server on http://{}:{}", args.host, args.port);
     println!("Frontend: http://{}:{}", args.host, args.port);
-    println!("API: http://{}:{}/health, /analyze, /analyze-file", args.host, args.port);
+    println!("API: http://{}:{}/health, /analyze, /analyze-file, /explain-node, /glossary", args.host, args.port);
+    if let Some(path) = &args.rules {
+        starrocks_profile_analyzer::rules::load_overrides(path)?;
+        println!("Rules: {}", path.display());
+    }
     println!();
 
     starrocks_profile_analyzer::api::start_server(args.host, args.port).await;
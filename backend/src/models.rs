@@ -1,5 +1,6 @@
+use crate::glossary;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +63,13 @@ pub struct ProfileSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query_cumulative_network_time_ms: Option<f64>,
     
+    /// Drivers of all fragments over all backends, each fragment counted with its widest pipeline.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_degree_of_parallelism: Option<u64>,
+    /// `QueryCumulativeCpuTime / (QueryExecutionWallTime * total_degree_of_parallelism)`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_efficiency: Option<f64>,
+    
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query_peak_schedule_time: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -72,6 +80,12 @@ pub struct ProfileSummary {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub result_deliver_time_ms: Option<f64>,
     
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub collect_profile_time_ms: Option<f64>,
+    /// `Reason:` of failed or cancelled queries.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure_reason: Option<String>,
+    
     // === Memory Metrics ===
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query_sum_memory_usage: Option<String>,
@@ -100,6 +114,18 @@ pub struct TopNode {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlannerInfo {
     pub details: HashMap<String, String>,
+    /// Planner / deploy timers (`-- Name[count] time` lines) in profile order.
+    #[serde(default)]
+    pub timers: Vec<PlannerTimer>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlannerTimer {
+    pub name: String,
+    /// Nesting level, 0 for top-level phases such as `Total` or `Deploy`.
+    pub depth: usize,
+    pub count: u64,
+    pub time_ms: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -113,6 +139,8 @@ pub struct Fragment {
     pub id: String,
     pub backend_addresses: Vec<String>,
     pub instance_ids: Vec<String>,
+    #[serde(default)]
+    pub metrics: HashMap<String, String>,
     pub pipelines: Vec<Pipeline>,
 }
 
@@ -129,6 +157,32 @@ pub struct ExecutionTree {
     pub nodes: Vec<ExecutionTreeNode>,
 }
 
+impl ExecutionTree {
+    /// The node a hotspot is located at: by tree node id, by plan node id, or by the plan node of
+    /// the operator in its `FragmentF.PipelineP.OPERATOR` path (per-table hotspots only carry that).
+    pub fn locate(&self, profile: &Profile, hotspot: &HotSpot) -> Option<&ExecutionTreeNode> {
+        if let Some(id) = &hotspot.node.tree_node_id {
+            return self.nodes.iter().find(|n| &n.id == id);
+        }
+        let plan_node_id = hotspot.node.plan_node_id.or_else(|| Self::plan_node_of_path(profile, &hotspot.node_path))?;
+        self.nodes.iter().find(|n| n.plan_node_id == Some(plan_node_id))
+    }
+
+    fn plan_node_of_path(profile: &Profile, node_path: &str) -> Option<i32> {
+        let mut parts = node_path.split(", ").next()?.splitn(3, '.');
+        let fragment_id = parts.next()?.strip_prefix("Fragment")?;
+        let pipeline_id = parts.next()?.strip_prefix("Pipeline")?;
+        let operator_name = parts.next()?;
+        profile.fragments.iter()
+            .filter(|f| f.id == fragment_id)
+            .flat_map(|f| &f.pipelines)
+            .filter(|p| p.id == pipeline_id)
+            .flat_map(|p| &p.operators)
+            .find(|op| op.name == operator_name)
+            .and_then(|op| op.plan_node_id.as_deref()?.parse().ok())
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionTreeNode {
     pub id: String,
@@ -155,6 +209,17 @@ pub struct ExecutionTreeNode {
     #[serde(default)]
     pub is_second_most_consuming: bool,
     
+    #[serde(default)]
+    pub is_on_critical_path: bool,
+    
+    /// A `CHUNK_ACCUMULATE` re-packs this node's small output chunks.
+    #[serde(default)]
+    pub has_chunk_accumulate: bool,
+    
+    /// Average output rows per chunk relative to `chunk_size`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunk_fill: Option<f64>,
+    
     // 添加unique_metrics字段，直接传递原始数据
     #[serde(skip_serializing_if = "HashMap::is_empty")]
     pub unique_metrics: HashMap<String, String>,
@@ -213,10 +278,25 @@ pub struct OperatorMetrics {
 
     pub output_chunk_bytes: Option<u64>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub spill: Option<OperatorSpillMetrics>,
 
     pub specialized: OperatorSpecializedMetrics,
 }
 
+/// Spill counters of a spillable operator (aggregate, join build, sort, ...).
+/// Bytes and rows are summed over drivers, times are per-driver averages in nanoseconds.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OperatorSpillMetrics {
+    pub spill_bytes: Option<u64>,
+    pub spill_rows: Option<u64>,
+    pub spill_time: Option<u64>,
+    pub restore_bytes: Option<u64>,
+    pub restore_rows: Option<u64>,
+    pub restore_time: Option<u64>,
+    pub spill_partition_num: Option<u64>,
+}
+
 impl Default for OperatorMetrics {
     fn default() -> Self {
         Self {
@@ -236,6 +316,7 @@ impl Default for OperatorMetrics {
             pull_total_time_max: None,
             memory_usage: None,
             output_chunk_bytes: None,
+            spill: None,
             specialized: OperatorSpecializedMetrics::None,
         }
     }
@@ -250,6 +331,7 @@ pub enum OperatorSpecializedMetrics {
     Join(JoinSpecializedMetrics),
     Aggregate(AggregateSpecializedMetrics),
     ResultSink(ResultSinkSpecializedMetrics),
+    LocalExchange(LocalExchangeSpecializedMetrics),
 
 }
 
@@ -281,6 +363,66 @@ pub struct ConnectorScanSpecializedMetrics {
     pub segment_init: Option<Duration>,
     pub segment_read: Option<Duration>,
     pub segment_read_count: Option<u64>,
+
+    #[serde(default)]
+    pub funnel: ScanRowFunnel,
+    /// Catalog specific counters, present when `DataSourceType` is an external catalog.
+    #[serde(default)]
+    pub external: Option<ExternalScanMetrics>,
+}
+
+/// Catalog behind a `CONNECTOR_SCAN`, keyed on `DataSourceType` and refined by the
+/// catalog specific counters (Iceberg, Hudi, Paimon and Delta Lake all scan through `HiveDataSource`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExternalCatalog {
+    Hive,
+    Iceberg,
+    Hudi,
+    Paimon,
+    DeltaLake,
+    Jdbc,
+    Elasticsearch,
+    File,
+}
+
+/// Counters of a scan over data lake files (external catalogs).
+/// Counts and bytes are summed over drivers, times are per-driver averages.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalScanMetrics {
+    pub catalog: ExternalCatalog,
+    /// `Parquet`, `ORC` or `JNI` (Java readers such as Hudi MOR or Paimon), when recognizable.
+    pub file_format: Option<String>,
+    /// Splits assigned to the scan, one or more per file.
+    pub scan_ranges: Option<u64>,
+    pub scan_ranges_size: Option<u64>,
+    pub row_groups_total: Option<u64>,
+    pub row_groups_filtered: Option<u64>,
+    pub stripes_total: Option<u64>,
+    pub stripes_filtered: Option<u64>,
+    pub page_index_filtered_rows: Option<u64>,
+    pub delete_files_per_scan: Option<u64>,
+    pub delete_file_build_time: Option<Duration>,
+    pub delete_file_build_filter_time: Option<Duration>,
+    pub open_file_time: Option<Duration>,
+    pub reader_init_time: Option<Duration>,
+}
+
+impl ExternalCatalog {
+    /// `None` for native tables (`LakeDataSource`) and unknown sources.
+    pub fn from_data_source_type(data_source_type: &str) -> Option<Self> {
+        let name = data_source_type.trim().trim_end_matches("DataSource").to_lowercase();
+        match name.as_str() {
+            "hive" => Some(Self::Hive),
+            "iceberg" => Some(Self::Iceberg),
+            "hudi" => Some(Self::Hudi),
+            "paimon" => Some(Self::Paimon),
+            "deltalake" => Some(Self::DeltaLake),
+            "jdbc" | "mysql" => Some(Self::Jdbc),
+            "es" | "elasticsearch" => Some(Self::Elasticsearch),
+            "file" => Some(Self::File),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -292,6 +434,48 @@ pub struct OlapScanSpecializedMetrics {
     pub io_time: Option<Duration>,
     pub bytes_read: Option<u64>,
     pub rows_read: Option<u64>,
+    pub raw_rows_read: Option<u64>,
+    pub tablet_count: Option<u64>,
+    pub pushdown_predicates: Option<u64>,
+    pub predicates: String,
+    pub segments_read_count: Option<u64>,
+    pub rowsets_read_count: Option<u64>,
+    pub segment_init: Option<Duration>,
+    pub create_segment_iter: Option<Duration>,
+    pub bloom_filter_time: Option<Duration>,
+    pub funnel: ScanRowFunnel,
+}
+
+/// Storage-layer row filtering of a native table scan, in evaluation order:
+/// short key -> zonemap -> bloom filter -> bitmap index -> delete vector -> raw rows read
+/// -> predicate evaluation -> rows returned.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScanRowFunnel {
+    pub rows_after_short_key: Option<u64>,
+    pub short_key_filter_rows: Option<u64>,
+    pub zone_map_filter_rows: Option<u64>,
+    pub bloom_filter_rows: Option<u64>,
+    pub bitmap_index_filter_rows: Option<u64>,
+    pub del_vec_filter_rows: Option<u64>,
+    pub raw_rows_read: Option<u64>,
+    pub pred_filter_rows: Option<u64>,
+    pub rows_read: Option<u64>,
+}
+
+impl ScanRowFunnel {
+    /// Rows removed by indexes before any data page is read.
+    pub fn index_filtered_rows(&self) -> u64 {
+        [self.short_key_filter_rows, self.zone_map_filter_rows, self.bloom_filter_rows, self.bitmap_index_filter_rows]
+            .iter()
+            .map(|v| v.unwrap_or(0))
+            .sum()
+    }
+
+    /// Rows entering the funnel: what was read plus everything the indexes and delete vectors skipped.
+    pub fn candidate_rows(&self) -> Option<u64> {
+        self.raw_rows_read
+            .map(|raw| raw + self.index_filtered_rows() + self.del_vec_filter_rows.unwrap_or(0))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -304,6 +488,13 @@ pub struct ExchangeSinkSpecializedMetrics {
     pub request_sent: Option<u64>,
     pub network_time: Option<Duration>,
     pub overall_time: Option<Duration>,
+    pub dest_id: Option<i32>,
+    pub channel_num: Option<u64>,
+    pub serialized_bytes: Option<u64>,
+    pub compressed_bytes: Option<u64>,
+    pub compress_time: Option<Duration>,
+    pub serialize_chunk_time: Option<Duration>,
+    pub shuffle_hash_time: Option<Duration>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -321,6 +512,30 @@ pub struct AggregateSpecializedMetrics {
     pub chunk_by_chunk: bool,
     pub input_rows: Option<u64>,
     pub agg_function_time: Option<Duration>,
+    #[serde(default)]
+    pub aggregate_functions: String,
+    #[serde(default)]
+    pub grouping_keys: String,
+    /// Groups in the hash table, summed over drivers.
+    pub hash_table_size: Option<u64>,
+    pub hash_table_memory: Option<u64>,
+    /// Rows a streaming pre-aggregation forwarded without aggregating.
+    pub pass_through_rows: Option<u64>,
+    pub agg_compute_time: Option<Duration>,
+    pub expr_compute_time: Option<Duration>,
+    pub streaming_time: Option<Duration>,
+    pub spill_bytes: Option<u64>,
+}
+
+/// `LOCAL_EXCHANGE_SINK` counters. `exchange_type` is `Passthrough`, `Shuffle` (hash partitioned
+/// into `shuffle_num` partitions) or `Broadcast`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalExchangeSpecializedMetrics {
+    pub exchange_type: String,
+    pub shuffle_num: Option<u64>,
+    pub peak_memory: Option<u64>,
+    pub peak_buffer_memory: Option<u64>,
+    pub buffer_unplug_count: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -343,23 +558,301 @@ pub struct Operator {
     pub children: Vec<Operator>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Ordered from least to most severe.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum HotSeverity {
     Normal,
     Mild,
     Moderate,
+    /// Older rule files spell it `High`.
+    #[serde(alias = "High")]
     Severe,
     Critical,
+}
+
+macro_rules! issue_codes {
+    ($($code:ident),* $(,)?) => {
+        /// Stable identifier of a hotspot's issue, serialized as its CamelCase name.
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum IssueCode {
+            $($code,)*
+            /// Issue types introduced by user rules or detectors.
+            Custom(String),
+        }
+
+        impl IssueCode {
+            /// Every built-in code.
+            pub const ALL: &'static [IssueCode] = &[$(IssueCode::$code),*];
+
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(IssueCode::$code => stringify!($code),)*
+                    IssueCode::Custom(name) => name,
+                }
+            }
+        }
+    };
+}
+
+issue_codes! {
+    // query level
+    LongRunning, MemoryUsage, QueryQueueWait, SlowDeploy, SlowResultDelivery,
+    CpuBound, IoBound, NetworkBound, UnderParallelized,
+    // operators
+    HighLatency, HighTimeCost, HighMemoryUsage, HighDataOutput, LargeDataOutput, DataSkew,
+    // scans
+    IoBottleneck, ExcessiveScanTime, HighScanTime, HighIoTime, MissingPredicatePushdown,
+    ThreadPoolStarvation, InsufficientParallelism, RowsetFragmentation, LateRowFiltering,
+    IneffectiveZoneMap, IneffectiveBloomFilter, LowDataCacheHit, MissedRowGroupPruning,
+    SmallFileExplosion, SlowMetadataListing, HeavyIcebergDeleteMerge,
+    // joins and exchanges
+    HighJoinMemory, LargeExchange, UncompressedExchange, IneffectiveExchangeCompression,
+    ExchangeSerializationCost, ExchangePassThroughDisabled, BroadcastJoinTooLarge,
+    ShuffleJoinShouldBroadcast, ShuffleJoinBothSides, BucketShuffleJoinLargeBuild,
+    // aggregation, spill and pipelines
+    IneffectivePreAggregation, AggregationShouldColocate, HighCardinalityAggregation,
+    OperatorSpill, DiskSpill, UnderfilledChunks, LocalExchangeMemory, LocalExchangeDopDrop,
+    UnnecessaryLocalShuffle, LocalShuffleAggDisabled, AdaptiveDopTooLow,
+}
+
+impl IssueCode {
+    /// The resource the issue costs, used for the performance sub-scores. Custom codes count as
+    /// CPU unless the rule file's `[score.dimensions]` says otherwise.
+    pub fn dimension(&self) -> ScoreDimension {
+        use IssueCode::*;
+        match self {
+            IoBound | IoBottleneck | ExcessiveScanTime | HighScanTime | HighIoTime | MissingPredicatePushdown
+            | ThreadPoolStarvation | RowsetFragmentation | LateRowFiltering | IneffectiveZoneMap
+            | IneffectiveBloomFilter | LowDataCacheHit | MissedRowGroupPruning | SmallFileExplosion
+            | SlowMetadataListing | HeavyIcebergDeleteMerge | DiskSpill => ScoreDimension::Io,
+            MemoryUsage | HighMemoryUsage | HighJoinMemory | OperatorSpill | LocalExchangeMemory => ScoreDimension::Memory,
+            NetworkBound | HighDataOutput | LargeDataOutput | SlowResultDelivery | LargeExchange
+            | UncompressedExchange | IneffectiveExchangeCompression | BroadcastJoinTooLarge => ScoreDimension::Network,
+            QueryQueueWait | SlowDeploy | UnderParallelized | InsufficientParallelism | ExchangePassThroughDisabled
+            | ShuffleJoinShouldBroadcast | ShuffleJoinBothSides | BucketShuffleJoinLargeBuild
+            | AggregationShouldColocate | LocalExchangeDopDrop | UnnecessaryLocalShuffle
+            | LocalShuffleAggDisabled | AdaptiveDopTooLow => ScoreDimension::Planning,
+            LongRunning | CpuBound | HighLatency | HighTimeCost | DataSkew | ExchangeSerializationCost
+            | IneffectivePreAggregation | HighCardinalityAggregation | UnderfilledChunks | Custom(_) => ScoreDimension::Cpu,
+        }
+    }
+}
+
+impl From<&str> for IssueCode {
+    /// Matches built-in codes ignoring case and underscores (`high_scan_time`, `IOBottleneck`).
+    fn from(name: &str) -> Self {
+        let normalize = |s: &str| s.replace('_', "").to_ascii_lowercase();
+        let wanted = normalize(name);
+        IssueCode::ALL.iter()
+            .find(|code| normalize(code.as_str()) == wanted)
+            .cloned()
+            .unwrap_or_else(|| IssueCode::Custom(name.to_string()))
+    }
+}
+
+impl std::fmt::Display for IssueCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for IssueCode {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for IssueCode {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(IssueCode::from(String::deserialize(deserializer)?.as_str()))
+    }
+}
+
+/// Where a hotspot is located. Operator-level detectors fill fragment / pipeline / operator,
+/// tree-level ones the tree node; query-level hotspots leave everything empty.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeRef {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_node_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pipeline_id: Option<String>,
+    /// `ExecutionTreeNode::id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tree_node_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator_name: Option<String>,
+}
+
+impl NodeRef {
+    pub fn operator(fragment_id: &str, pipeline_id: &str, operator: &Operator) -> Self {
+        NodeRef {
+            plan_node_id: operator.plan_node_id.as_deref().and_then(|id| id.parse().ok()),
+            fragment_id: Some(fragment_id.to_string()),
+            pipeline_id: Some(pipeline_id.to_string()),
+            tree_node_id: None,
+            operator_name: Some(operator.name.clone()),
+        }
+    }
+
+    pub fn tree_node(node: &ExecutionTreeNode) -> Self {
+        NodeRef {
+            plan_node_id: node.plan_node_id,
+            fragment_id: node.fragment_id.clone(),
+            pipeline_id: node.pipeline_id.clone(),
+            tree_node_id: Some(node.id.clone()),
+            operator_name: Some(node.operator_name.clone()),
+        }
+    }
+
+    pub fn plan_node(plan_node_id: i32, operator_name: &str) -> Self {
+        NodeRef {
+            plan_node_id: Some(plan_node_id),
+            operator_name: Some(operator_name.to_string()),
+            ..NodeRef::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricUnit {
+    Count,
+    /// Dimensionless; shares are fractions (0.25 = 25%).
+    Ratio,
+    Milliseconds,
+    Bytes,
+}
+
+/// A metric value that triggered a hotspot, and the threshold it crossed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Evidence {
+    pub metric: String,
+    pub value: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    pub unit: MetricUnit,
+    /// Glossary entries of the counters the metric is computed from, see [`glossary`].
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub glossary: Vec<String>,
+}
+
+impl Evidence {
+    pub fn new(metric: impl Into<String>, value: f64, unit: MetricUnit) -> Self {
+        let metric = metric.into();
+        let glossary = glossary::references(&metric);
+        Evidence { metric, value, threshold: None, unit, glossary }
+    }
+
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = Some(threshold);
+        self
+    }
+}
+
+/// How directly the evidence supports the diagnosis: `High` for a measured counter past its
+/// threshold, `Medium` for an inference across several counters, `Low` for a heuristic guess.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Confidence {
+    Low,
+    Medium,
     High,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HotSpot {
     pub node_path: String,
+    #[serde(default)]
+    pub node: NodeRef,
     pub severity: HotSeverity,
-    pub issue_type: String,
+    pub issue_type: IssueCode,
+    pub confidence: Confidence,
     pub description: String,
     pub suggestions: Vec<String>,
+    #[serde(default)]
+    pub evidence: Vec<Evidence>,
+}
+
+/// Resource areas the performance score is broken down into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScoreDimension {
+    Cpu,
+    Io,
+    Memory,
+    Network,
+    /// Plan shape, parallelism and scheduling choices.
+    Planning,
+}
+
+impl ScoreDimension {
+    pub const ALL: [ScoreDimension; 5] = [
+        ScoreDimension::Cpu, ScoreDimension::Io, ScoreDimension::Memory, ScoreDimension::Network, ScoreDimension::Planning,
+    ];
+}
+
+/// What a score item was derived from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ScoreSource {
+    /// `index` points into the response's `hotspots`.
+    Hotspot { index: usize, issue_type: IssueCode, node_path: String },
+    /// A `[[score.adjustments]]` entry of the rule file.
+    Metric { adjustment: String, metric: String, value: f64 },
+}
+
+/// One penalty (negative points) or bonus (positive points) of the performance score.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreItem {
+    pub points: f64,
+    /// `None` for items that only count towards the overall score.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dimension: Option<ScoreDimension>,
+    pub source: ScoreSource,
+    pub reason: String,
+}
+
+/// How `performance_score` was reached: `score = clamp(base + Σ items.points, 0, 100)`, and each
+/// sub-score applies the same formula to the items of its dimension only.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScoreBreakdown {
+    pub score: f64,
+    pub base: f64,
+    pub sub_scores: BTreeMap<ScoreDimension, f64>,
+    pub items: Vec<ScoreItem>,
+}
+
+/// The mechanism a [`RootCause`] stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RootCauseKind {
+    /// A scan whose slowness shows up again in the exchanges and operators above it.
+    SlowScan,
+    /// A hash join whose probe-side scan received no runtime filter.
+    MissingRuntimeFilter,
+    /// Spilling forced by memory pressure.
+    SpillMemoryPressure,
+}
+
+/// A hotspot explained by a [`RootCause`]; `index` points into the response's `hotspots`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HotspotRef {
+    pub index: usize,
+    pub issue_type: IssueCode,
+    pub node_path: String,
+}
+
+/// A probable cause behind several related hotspots, located at the node it originates from.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RootCause {
+    pub kind: RootCauseKind,
+    pub node_path: String,
+    pub node: NodeRef,
+    /// The most severe supporting hotspot's severity.
+    pub severity: HotSeverity,
+    pub description: String,
+    pub hotspots: Vec<HotspotRef>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -376,8 +869,273 @@ pub struct ProfileAnalysisResponse {
     pub conclusion: String,
     pub suggestions: Vec<String>,
     pub performance_score: f64,
+    /// Absent for failed queries, which are not scored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score_breakdown: Option<ScoreBreakdown>,
     pub execution_tree: Option<ExecutionTree>,
     pub summary: Option<ProfileSummary>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical_path: Option<CriticalPath>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub data_cache: Vec<TableDataCacheStats>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub chunk_edges: Vec<ChunkEdgeStats>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub adaptive_dop: Vec<AdaptiveDopDecision>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lifecycle: Option<QueryLifecycle>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<QueryEfficiency>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failure: Option<FailureDiagnosis>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub risks: Vec<RiskIndicator>,
+    /// Related hotspots grouped under their probable cause, most severe first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub root_causes: Vec<RootCause>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipes: Vec<HotspotRecipe>,
+    /// Estimated gain of fixing each hotspot, largest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub speedups: Vec<SpeedupEstimate>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RiskKind {
+    Timeout,
+    MemoryLimit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RiskLevel {
+    Safe,
+    Warning,
+    Critical,
+}
+
+/// How close a successful query came to one of its limits. Independent of the performance
+/// score: a fast query can still be one data-growth step away from failing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskIndicator {
+    pub kind: RiskKind,
+    pub level: RiskLevel,
+    /// Session variable or metric the limit comes from, e.g. `query_timeout`.
+    pub limit_source: String,
+    /// Milliseconds for timeouts, bytes for memory.
+    pub used: f64,
+    pub limit: f64,
+    pub usage_percentage: f64,
+    pub headroom_percentage: f64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suggestions: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FailureKind {
+    MemoryLimitExceeded,
+    Timeout,
+    RpcFailure,
+    DataQuality,
+    UserCancelled,
+    Unknown,
+}
+
+/// Diagnosis of a failed or cancelled query, replacing the performance hotspots.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureDiagnosis {
+    pub query_state: String,
+    pub reason: String,
+    pub kind: FailureKind,
+    pub explanation: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub elapsed_ms: Option<f64>,
+    /// `query_timeout` session variable in seconds.
+    pub query_timeout_secs: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suspect: Option<FailureSuspect>,
+    pub suggestions: Vec<String>,
+}
+
+/// Operator most likely responsible: the highest-memory one for memory failures, otherwise
+/// the one with the longest operator time up to when the query stopped.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailureSuspect {
+    pub fragment_id: String,
+    pub pipeline_id: String,
+    pub operator_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_node_id: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_memory_bytes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub operator_time_ms: Option<f64>,
+    /// Highest `InstancePeakMemoryUsage` of the fragment.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fragment_peak_memory_bytes: Option<u64>,
+}
+
+/// What bounds the execution wall time, derived from CPU time versus wall time x DOP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionBound {
+    CpuBound,
+    IoBound,
+    NetworkBound,
+    UnderParallelized,
+    /// Little CPU, scan or network time: drivers mostly wait (result delivery, dependencies).
+    Waiting,
+}
+
+/// Query-level CPU efficiency with a per-fragment breakdown. Efficiency is the share of the
+/// available driver time (wall time x total DOP) actually spent on CPU.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryEfficiency {
+    pub cpu_time_ms: f64,
+    pub wall_time_ms: f64,
+    pub scan_time_ms: f64,
+    pub network_time_ms: f64,
+    pub operator_time_ms: f64,
+    pub total_dop: u64,
+    pub cpu_efficiency: f64,
+    pub bound: ExecutionBound,
+    pub explanation: String,
+    pub fragments: Vec<FragmentEfficiency>,
+}
+
+/// `cpu_time_ms` sums pipeline `ActiveTime x TotalDegreeOfParallelism`; `total_dop` is the
+/// widest pipeline of the fragment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FragmentEfficiency {
+    pub fragment_id: String,
+    pub cpu_time_ms: f64,
+    pub total_dop: u64,
+    pub cpu_efficiency: f64,
+}
+
+/// End-to-end query timeline reconciling the summary `Total` with the planner, deploy and
+/// execution timers. Phases are sequential and in execution order; `Execute` excludes the
+/// result delivery that `QueryExecutionWallTime` also covers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueryLifecycle {
+    pub total_ms: f64,
+    pub phases: Vec<LifecyclePhase>,
+    /// `Total` minus all phases; negative when `Total` is rounded to whole seconds.
+    pub unaccounted_ms: f64,
+    /// Longest time a driver waited in the BE ready queue, overlapping `Execute`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_schedule_ms: Option<f64>,
+    /// The query was still running when the profile was taken.
+    pub running: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecyclePhase {
+    /// `Plan`, `Queue`, `Prepare`, `Deploy`, `Execute`, `ResultDeliver` or `CollectProfile`.
+    pub name: String,
+    pub time_ms: f64,
+    pub percentage: f64,
+}
+
+/// A runtime adaptive DOP decision taken at a `COLLECT_STATS_SINK` -> `COLLECT_STATS_SOURCE` boundary.
+/// DOPs are per backend, `*_total_dop` over all backends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdaptiveDopDecision {
+    pub fragment_id: String,
+    pub plan_node_id: Option<i32>,
+    pub producer_pipeline_id: String,
+    pub consumer_pipeline_id: String,
+    pub initial_dop: u64,
+    pub adapted_dop: u64,
+    pub initial_total_dop: u64,
+    pub adapted_total_dop: u64,
+    /// `RoundRobin` when the DOP was changed, `Passthrough` when it was kept.
+    pub state: String,
+    /// Rows collected before the decision.
+    pub input_rows: u64,
+    /// Per-driver active time of the pipelines running at the adapted DOP.
+    pub adapted_active_ms: f64,
+}
+
+/// Rows per chunk on one operator-to-operator edge of a pipeline.
+/// `fill_ratio` is the average chunk size relative to the `chunk_size` session variable.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkEdgeStats {
+    pub fragment_id: String,
+    pub pipeline_id: String,
+    pub from_operator: String,
+    pub to_operator: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plan_node_id: Option<i32>,
+    pub rows: u64,
+    pub chunks: u64,
+    pub avg_rows_per_chunk: f64,
+    pub fill_ratio: f64,
+    /// The downstream operator is a `CHUNK_ACCUMULATE` merging small chunks again.
+    pub accumulated: bool,
+}
+
+/// Data cache effectiveness of all scans over one table (shared-data / external storage).
+/// IO times are totals over every scan driver; `estimated_saving_ms` is the IO time one
+/// driver would save at a 100% hit ratio, an upper bound of the wall-clock gain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableDataCacheStats {
+    pub table: String,
+    pub scan_nodes: Vec<String>,
+    pub bytes_local: u64,
+    pub bytes_remote: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub byte_hit_percentage: Option<f64>,
+    pub io_count_local: u64,
+    pub io_count_remote: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub io_hit_percentage: Option<f64>,
+    pub io_time_local_ms: f64,
+    pub io_time_remote_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_latency_per_io_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_latency_per_io_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub estimated_saving_ms: Option<f64>,
+}
+
+/// Chain of pipelines that bounds the query wall-clock latency.
+/// Stages are ordered from the leaf-most pipeline up to the final sink.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalPath {
+    pub stages: Vec<CriticalPathStage>,
+    pub path_time_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub wall_time_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coverage_percentage: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CriticalPathStage {
+    pub fragment_id: String,
+    pub pipeline_id: String,
+    pub operators: Vec<String>,
+    pub plan_node_ids: Vec<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub degree_of_parallelism: Option<u64>,
+    pub active_time_ms: f64,
+    pub input_wait_ms: f64,
+    pub output_wait_ms: f64,
+    pub own_time_ms: f64,
+    pub finish_time_ms: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver_total_time_ms: Option<f64>,
+    /// How this stage feeds the next stage on the path; `None` for the last stage.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_to_next: Option<PipelineEdgeKind>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PipelineEdgeKind {
+    Exchange,
+    LocalExchange,
+    Blocking,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -396,11 +1154,182 @@ pub struct OperatorSummary {
     pub time_percentage: f64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// What a tuning recipe is tailored to: an operator's metrics (common and unique merged), its
+/// pipeline and the query it ran in.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct OperatorContext {
     pub operator_name: String,
     pub fragment_id: String,
     pub pipeline_id: String,
     pub metrics: HashMap<String, String>,
     pub execution_metrics: HashMap<String, String>,
+    #[serde(default)]
+    pub pipeline_metrics: HashMap<String, String>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+    #[serde(default)]
+    pub default_db: Option<String>,
+    #[serde(default)]
+    pub sql_statement: String,
+}
+
+impl OperatorContext {
+    pub fn new(profile: &Profile, fragment: &Fragment, pipeline: &Pipeline, operator: &Operator) -> Self {
+        let mut metrics = operator.common_metrics.clone();
+        metrics.extend(operator.unique_metrics.iter().map(|(k, v)| (k.clone(), v.clone())));
+        OperatorContext {
+            operator_name: operator.name.clone(),
+            fragment_id: fragment.id.clone(),
+            pipeline_id: pipeline.id.clone(),
+            metrics,
+            execution_metrics: profile.execution.metrics.clone(),
+            pipeline_metrics: pipeline.metrics.clone(),
+            variables: profile.summary.variables.clone(),
+            default_db: profile.summary.default_db.clone().filter(|db| !db.is_empty()),
+            sql_statement: profile.summary.sql_statement.clone(),
+        }
+    }
+}
+
+/// A runnable statement of a tuning recipe and what running it risks.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecipeStatement {
+    pub sql: String,
+    pub risk: String,
+}
+
+/// One step of a tuning recipe; steps that the profile gives enough names for carry a statement.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TuningRecipe {
+    pub advice: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub statement: Option<RecipeStatement>,
+}
+
+/// The tuning recipe for a hotspot, filled in with the names of the operator it points at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HotspotRecipe {
+    pub hotspot: HotspotRef,
+    pub steps: Vec<TuningRecipe>,
+    /// What fixing the hotspot is expected to save, see [`SpeedupEstimate`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_gain: Option<GainRange>,
+}
+
+/// What a [`SpeedupEstimate`] assumes gets fixed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpeedupScenario {
+    /// Every remote read hits the local Data Cache.
+    FullCacheHit,
+    /// The slowest instance does the average amount of work.
+    SkewRemoved,
+    /// The broadcast join runs as a shuffle join.
+    BroadcastToShuffle,
+    /// The operator fits in memory and does not spill.
+    SpillAvoided,
+    /// Compaction leaves a few rowsets per tablet.
+    RowsetsCompacted,
+    /// Rows the predicates discard are filtered in the storage layer.
+    RowsFilteredEarly,
+    /// No dedicated model: part of the node's time is won back.
+    HotspotFixed,
+}
+
+/// Expected wall-time reduction, `min_ms..=max_ms`, and the same as a share of the query's wall time.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct GainRange {
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub min_percentage: f64,
+    pub max_percentage: f64,
+}
+
+/// What-if estimate of the wall time saved by fixing a hotspot; `assumptions` spell out the model.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeedupEstimate {
+    pub hotspot: HotspotRef,
+    pub scenario: SpeedupScenario,
+    pub gain: GainRange,
+    pub on_critical_path: bool,
+    pub assumptions: Vec<String>,
+}
+
+/// Where a node's time goes, summed over its operators (per-driver averages, in ms).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct TimeBreakdown {
+    /// `OperatorTotalTime`: the operators computing.
+    pub cpu_ms: f64,
+    /// `ScanTime` (or `IOTaskExecTime`) of scans.
+    pub io_ms: f64,
+    /// `NetworkTime` of exchanges.
+    pub network_ms: f64,
+    /// Waiting for IO threads, the other side of an exchange, or the neighbouring pipelines.
+    pub wait_ms: f64,
+}
+
+impl TimeBreakdown {
+    pub fn total_ms(&self) -> f64 {
+        self.cpu_ms + self.io_ms + self.network_ms + self.wait_ms
+    }
+}
+
+/// Rows a child node hands to the explained node.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildRows {
+    pub plan_node_id: i32,
+    pub operator_name: String,
+    pub rows: Option<u64>,
+}
+
+/// A node's output compared with its input: the children's output, or the rows a scan read.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NodeDataFlow {
+    pub input_rows: Option<u64>,
+    pub output_rows: Option<u64>,
+    /// `output_rows / input_rows`: below 1 the node filters, above 1 it expands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ChildRows>,
+}
+
+/// Spread of a merged counter over the instances of an operator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SkewIndicator {
+    pub operator: String,
+    pub metric: String,
+    pub max: f64,
+    pub min: f64,
+    pub avg: f64,
+    /// `max / avg`.
+    pub ratio: f64,
+    pub unit: MetricUnit,
+    /// Large enough to be reported as a `DataSkew` hotspot.
+    pub significant: bool,
+}
+
+/// A counter of the node, with what it measures from the metric glossary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExplainedMetric {
+    pub metric: String,
+    pub operator: String,
+    pub value: String,
+    pub meaning: String,
+}
+
+/// Why an execution tree node is slow, for the node detail view.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeExplanation {
+    pub plan_node_id: i32,
+    pub operator_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_percentage: Option<f64>,
+    pub time_breakdown: TimeBreakdown,
+    pub data_flow: NodeDataFlow,
+    pub skew: Vec<SkewIndicator>,
+    pub top_metrics: Vec<ExplainedMetric>,
+    pub hotspots: Vec<HotspotRef>,
+    /// The findings above in plain language, most important first.
+    pub narrative: Vec<String>,
 }
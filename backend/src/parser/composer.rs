@@ -51,6 +51,7 @@ impl ProfileComposer {
         Self::extract_execution_metrics(&execution_info, &mut summary);
         
         let fragments = FragmentParser::extract_all_fragments(text);
+        Self::compute_cpu_efficiency(&fragments, &mut summary);
 
         let topology_result = Self::extract_topology_json(&execution_info.topology)
             .and_then(|json| {
@@ -170,6 +171,7 @@ impl ProfileComposer {
                 
 
                 let mut metrics = MetricsParser::from_hashmap(&aggregated_op.common_metrics);
+                metrics.spill = MetricsParser::spill_from_hashmap(&aggregated_op.unique_metrics);
 
                 // Debug: Check if unique_metrics is empty
                 println!("DEBUG: aggregated_op.unique_metrics.len() = {}", aggregated_op.unique_metrics.len());
@@ -213,6 +215,9 @@ impl ProfileComposer {
                     time_percentage: None,
                     is_most_consuming: false,
                     is_second_most_consuming: false,
+                    is_on_critical_path: false,
+                    has_chunk_accumulate: false,
+                    chunk_fill: None,
                     unique_metrics: aggregated_op.unique_metrics.clone(),
                 }
             } else {
@@ -232,6 +237,9 @@ impl ProfileComposer {
                     time_percentage: None,
                     is_most_consuming: false,
                     is_second_most_consuming: false,
+                    is_on_critical_path: false,
+                    has_chunk_accumulate: false,
+                    chunk_fill: None,
                     unique_metrics: HashMap::new(),
                 }
             };
@@ -256,6 +264,7 @@ impl ProfileComposer {
 
                         if !topology.nodes.iter().any(|n| n.id == plan_id) {
                             let mut metrics = MetricsParser::from_hashmap(&operator.common_metrics);
+                            metrics.spill = MetricsParser::spill_from_hashmap(&operator.unique_metrics);
                             
                             if !operator.unique_metrics.is_empty() {
                                 let specialized_parser = SpecializedMetricsParser::new();
@@ -279,6 +288,9 @@ impl ProfileComposer {
                                 time_percentage: None,
                                 is_most_consuming: false,
                                 is_second_most_consuming: false,
+                                is_on_critical_path: false,
+                                has_chunk_accumulate: false,
+                                chunk_fill: None,
                                 unique_metrics: operator.unique_metrics.clone(),
                             };
                             
@@ -596,6 +608,9 @@ impl ProfileComposer {
             time_percentage: None,
             is_most_consuming: false,
             is_second_most_consuming: false,
+            is_on_critical_path: false,
+            has_chunk_accumulate: false,
+            chunk_fill: None,
             unique_metrics: HashMap::new(), // 这个方法中没有unique_metrics数据
         })
     }
@@ -637,15 +652,37 @@ impl ProfileComposer {
                             .clone()
                             .unwrap_or_else(|| "N/A".to_string()),
                         time_percentage: percentage,
-                        is_most_consuming: percentage > time_thresholds::MOST_CONSUMING_THRESHOLD,
-                        is_second_most_consuming: percentage > time_thresholds::SECOND_CONSUMING_THRESHOLD 
-                            && percentage <= time_thresholds::MOST_CONSUMING_THRESHOLD,
+                        is_most_consuming: percentage > time_thresholds::most_consuming(),
+                        is_second_most_consuming: percentage > time_thresholds::second_consuming()
+                            && percentage <= time_thresholds::most_consuming(),
                     }
                 }
             })
             .collect()
     }
     
+    /// Total DOP sums the fragments, which are deployed together and run side by side. A
+    /// fragment counts with its widest pipeline: its pipelines are split at blocking operators
+    /// and mostly run one after the other.
+    fn compute_cpu_efficiency(fragments: &[crate::models::Fragment], summary: &mut ProfileSummary) {
+        let fragment_dops: Vec<u64> = fragments.iter()
+            .filter_map(|f| {
+                f.pipelines.iter()
+                    .filter_map(|p| p.metrics.get("TotalDegreeOfParallelism").or_else(|| p.metrics.get("DegreeOfParallelism")))
+                    .filter_map(|v| ValueParser::parse_number::<u64>(v).ok())
+                    .max()
+            })
+            .collect();
+        let total_dop = (!fragment_dops.is_empty()).then(|| fragment_dops.iter().sum::<u64>());
+        summary.total_degree_of_parallelism = total_dop;
+        
+        if let (Some(cpu), Some(wall), Some(dop)) = (summary.query_cumulative_cpu_time_ms, summary.query_execution_wall_time_ms, total_dop) {
+            if wall > 0.0 && dop > 0 {
+                summary.cpu_efficiency = Some(cpu / (wall * dop as f64));
+            }
+        }
+    }
+    
     /// Extract all execution metrics for overview diagnostics
     fn extract_execution_metrics(execution_info: &crate::models::ExecutionInfo, summary: &mut ProfileSummary) {
         // Memory metrics
@@ -707,4 +744,14 @@ mod tests {
         let json = ProfileComposer::extract_topology_json(text).unwrap();
         assert!(json.contains("rootId"));
     }
+
+    #[test]
+    fn test_total_dop_sums_fragments_profile3() {
+        // two fragments of 32 drivers each run side by side
+        let profile = crate::test_support::load("profile3.txt");
+        assert_eq!(profile.summary.total_degree_of_parallelism, Some(64));
+        let cpu = profile.summary.query_cumulative_cpu_time_ms.unwrap();
+        let wall = profile.summary.query_execution_wall_time_ms.unwrap();
+        assert!((profile.summary.cpu_efficiency.unwrap() - cpu / (wall * 64.0)).abs() < 1e-12);
+    }
 }
@@ -21,12 +21,14 @@ impl FragmentParser {
     pub fn parse_fragment(text: &str, id: &str) -> ParseResult<Fragment> {
         let backend_addresses = Self::extract_backend_addresses(text);
         let instance_ids = Self::extract_instance_ids(text);
+        let metrics = Self::extract_fragment_metrics(text);
         let pipelines = Self::parse_pipelines(text)?;
         
         Ok(Fragment {
             id: id.to_string(),
             backend_addresses,
             instance_ids,
+            metrics,
             pipelines,
         })
     }
@@ -132,6 +134,14 @@ impl FragmentParser {
         metrics
     }
     
+    /// Fragment-level counters (InstancePeakMemoryUsage, QueryMemoryLimit, ...) listed before the first pipeline.
+    fn extract_fragment_metrics(text: &str) -> HashMap<String, String> {
+        let head: Vec<&str> = text.lines()
+            .take_while(|line| !PIPELINE_REGEX.is_match(line.trim()))
+            .collect();
+        Self::extract_pipeline_metrics(&head.join("\n"))
+    }
+    
     fn extract_operators(text: &str) -> Vec<Operator> {
         use crate::parser::core::operator_parser::OperatorParser;
         use crate::parser::core::MetricsParser;
@@ -267,5 +277,14 @@ mod tests {
         assert_eq!(addrs.len(), 2);
         assert_eq!(addrs[0], "192.168.1.1:9060");
     }
+    
+    #[test]
+    fn test_extract_fragment_metrics() {
+        let text = "  Fragment 1:\n     - InstancePeakMemoryUsage: 647.063 KB\n       - __MAX_OF_InstancePeakMemoryUsage: 941.992 KB\n    Pipeline (id=0):\n       - ActiveTime: 16.522us";
+        let metrics = FragmentParser::extract_fragment_metrics(text);
+        assert_eq!(metrics.get("InstancePeakMemoryUsage"), Some(&"647.063 KB".to_string()));
+        assert!(metrics.contains_key("__MAX_OF_InstancePeakMemoryUsage"));
+        assert!(!metrics.contains_key("ActiveTime"));
+    }
 }
 
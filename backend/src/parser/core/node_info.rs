@@ -541,6 +541,7 @@ mod tests {
             id: "fragment_0".to_string(),
             backend_addresses: Vec::new(),
             instance_ids: Vec::new(),
+            metrics: HashMap::new(),
             pipelines: vec![pipeline],
         };
         
@@ -6,7 +6,7 @@
 //! 
 
 use crate::parser::error::{ParseError, ParseResult};
-use crate::models::OperatorMetrics;
+use crate::models::{OperatorMetrics, OperatorSpillMetrics};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::time::Duration;
@@ -103,6 +103,21 @@ impl ValueParser {
         Self::parse_bytes_to_u64(input)
     }
     
+    /// Bytes for report text: GB, MB or KB with two decimals, e.g. "1.50GB".
+    pub fn format_bytes(bytes: u64) -> String {
+        const KB: f64 = 1024.0;
+        const MB: f64 = KB * 1024.0;
+        const GB: f64 = MB * 1024.0;
+        let bytes = bytes as f64;
+        if bytes >= GB {
+            format!("{:.2}GB", bytes / GB)
+        } else if bytes >= MB {
+            format!("{:.2}MB", bytes / MB)
+        } else {
+            format!("{:.2}KB", bytes / KB)
+        }
+    }
+
     /// Parse bytes from string like "45.907 GB" to u64 bytes
     /// Supported units: B, KB, MB, GB, TB
     pub fn parse_bytes_to_u64(input: &str) -> ParseResult<u64> {
@@ -277,6 +292,13 @@ mod tests {
     fn test_parse_bytes_plain_number() {
         assert_eq!(ValueParser::parse_bytes("1024").unwrap(), 1024);
     }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(ValueParser::format_bytes(13709535608), "12.77GB");
+        assert_eq!(ValueParser::format_bytes(64 * 1024 * 1024), "64.00MB");
+        assert_eq!(ValueParser::format_bytes(2219), "2.17KB");
+    }
     
     
     #[test]
@@ -487,11 +509,33 @@ impl MetricsParser {
             "OutputChunkBytes" => {
                 metrics.output_chunk_bytes = ValueParser::parse_bytes(value).ok();
             }
+            "SpillBytes" | "SpillRows" | "SpillTime" | "RestoreBytes" | "RestoreRows" | "RestoreTime" | "SpillPartitionNum" => {
+                Self::set_spill_value(metrics.spill.get_or_insert_with(OperatorSpillMetrics::default), key, value);
+            }
             _ => {
             }
         }
     }
     
+    /// Spill counters live in UniqueMetrics, which `from_hashmap` callers usually parse separately.
+    pub fn spill_from_hashmap(map: &std::collections::HashMap<String, String>) -> Option<OperatorSpillMetrics> {
+        Self::from_hashmap(map).spill
+    }
+    
+    fn set_spill_value(spill: &mut OperatorSpillMetrics, key: &str, value: &str) {
+        let nanos = || ValueParser::parse_duration(value).ok().map(|d| d.as_nanos() as u64);
+        match key {
+            "SpillBytes" => spill.spill_bytes = ValueParser::parse_bytes(value).ok(),
+            "SpillRows" => spill.spill_rows = Self::extract_number(value),
+            "SpillTime" => spill.spill_time = nanos(),
+            "RestoreBytes" => spill.restore_bytes = ValueParser::parse_bytes(value).ok(),
+            "RestoreRows" => spill.restore_rows = Self::extract_number(value),
+            "RestoreTime" => spill.restore_time = nanos(),
+            "SpillPartitionNum" => spill.spill_partition_num = Self::extract_number(value),
+            _ => {}
+        }
+    }
+    
     fn extract_number(value: &str) -> Option<u64> {
         ValueParser::parse_number(value).ok()
     }
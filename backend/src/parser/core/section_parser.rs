@@ -1,6 +1,6 @@
 //! 
 
-use crate::models::{ProfileSummary, PlannerInfo, ExecutionInfo};
+use crate::models::{ProfileSummary, PlannerInfo, PlannerTimer, ExecutionInfo};
 use crate::parser::error::{ParseError, ParseResult};
 use super::ValueParser;
 use once_cell::sync::Lazy;
@@ -10,6 +10,12 @@ use std::collections::HashMap;
 static SUMMARY_LINE_REGEX: Lazy<Regex> = 
     Lazy::new(|| Regex::new(r"^\s*-\s+([^:]+):\s*(.*)$").unwrap());
 
+static REASON_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(\s*)Reason:\s*(.*)$").unwrap());
+
+static PLANNER_TIMER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\s*-( +)--\s+(.+)\[(\d+)\]\s+(\S+)\s*$").unwrap());
+
 pub struct SectionParser;
 
 impl SectionParser {
@@ -38,7 +44,7 @@ impl SectionParser {
             query_type: fields.get("Query Type").cloned(),
             user: fields.get("User").cloned(),
             default_db: fields.get("Default Db").cloned(),
-            variables: HashMap::new(),
+            variables: Self::parse_variables(&fields),
             query_allocated_memory: None,
             query_peak_memory: None,
             push_total_time: None,
@@ -58,10 +64,15 @@ impl SectionParser {
             query_cumulative_scan_time_ms: None,
             query_cumulative_network_time: None,
             query_cumulative_network_time_ms: None,
+            total_degree_of_parallelism: None,
+            cpu_efficiency: None,
             query_peak_schedule_time: None,
             query_peak_schedule_time_ms: None,
             result_deliver_time: None,
             result_deliver_time_ms: None,
+            failure_reason: Self::parse_reason(text),
+            collect_profile_time_ms: fields.get("Collect Profile Time")
+                .and_then(|time_str| Self::parse_total_time_ms(time_str)),
             
             // === Memory Metrics ===
             query_sum_memory_usage: None,
@@ -74,19 +85,75 @@ impl SectionParser {
         })
     }
     
+    /// Session variables from `Variables` (`k=v,k=v`) overlaid with the actual values of
+    /// `NonDefaultSessionVariables` (`{"k":{"defaultValue":..,"actualValue":..}}`).
+    fn parse_variables(fields: &HashMap<String, String>) -> HashMap<String, String> {
+        let mut variables = HashMap::new();
+        
+        if let Some(list) = fields.get("Variables") {
+            for pair in list.split(',') {
+                if let Some((key, value)) = pair.split_once('=') {
+                    variables.insert(key.trim().to_string(), value.trim().to_string());
+                }
+            }
+        }
+        
+        let non_default = fields.get("NonDefaultSessionVariables")
+            .and_then(|json| serde_json::from_str::<HashMap<String, serde_json::Value>>(json).ok())
+            .unwrap_or_default();
+        for (key, value) in non_default {
+            let actual = match value.get("actualValue") {
+                Some(serde_json::Value::String(s)) => s.clone(),
+                Some(other) => other.to_string(),
+                None => continue,
+            };
+            variables.insert(key, actual);
+        }
+        
+        variables
+    }
+    
+    /// Failure reason of the query header (`Reason:` before `Execution:`), either inline or as
+    /// the indented lines below it. Empty for successful queries.
+    fn parse_reason(text: &str) -> Option<String> {
+        let header = text.find("Execution:").map(|end| &text[..end]).unwrap_or(text);
+        let mut lines = header.lines();
+        let (indent, inline) = lines.by_ref()
+            .find_map(|line| REASON_REGEX.captures(line).map(|cap| (cap[1].len(), cap[2].trim().to_string())))?;
+        if !inline.is_empty() {
+            return Some(inline);
+        }
+        
+        let details: Vec<&str> = lines
+            .take_while(|line| line.trim().is_empty() || Self::get_indent(line) > indent)
+            .map(|line| line.trim().trim_start_matches("- "))
+            .map(|line| line.strip_prefix("Reason:").unwrap_or(line).trim())
+            .filter(|line| !line.is_empty())
+            .collect();
+        (!details.is_empty()).then(|| details.join("; "))
+    }
+    
     pub fn parse_planner(text: &str) -> ParseResult<PlannerInfo> {
         let planner_block = Self::extract_block(text, "Planner:")?;
         let mut details = HashMap::new();
+        let mut timers = Vec::new();
         
         for line in planner_block.lines() {
-            if let Some(cap) = SUMMARY_LINE_REGEX.captures(line) {
+            if let Some(cap) = PLANNER_TIMER_REGEX.captures(line) {
+                timers.push(PlannerTimer {
+                    name: cap[2].trim().to_string(),
+                    depth: (cap[1].len() - 1) / 4,
+                    count: cap[3].parse().unwrap_or(0),
+                    time_ms: ValueParser::parse_time_to_ms(&cap[4]).unwrap_or(0.0),
+                });
+            } else if let Some(cap) = SUMMARY_LINE_REGEX.captures(line) {
                 let key = cap.get(1).map(|m| m.as_str().trim()).unwrap_or("");
                 let value = cap.get(2).map(|m| m.as_str().trim()).unwrap_or("");
                 details.insert(key.to_string(), value.to_string());
             }
         }
         
-        Ok(PlannerInfo { details })
+        Ok(PlannerInfo { details, timers })
     }
     
     pub fn parse_execution(text: &str) -> ParseResult<ExecutionInfo> {
@@ -241,5 +308,60 @@ Query:
         assert_eq!(summary.query_id, "b1f9a935-a967-11f0-b3d8-f69e292b7593");
         assert_eq!(summary.total_time, "1h30m");
     }
+    
+    #[test]
+    fn test_parse_variables() {
+        let profile = r#"
+Query:
+  Summary:
+     - Query ID: b1f9a935-a967-11f0-b3d8-f69e292b7593
+     - Variables: parallel_fragment_exec_instance_num=8,pipeline_dop=0
+     - NonDefaultSessionVariables: {"query_timeout":{"defaultValue":300,"actualValue":90000},"character_set_results":{"defaultValue":"utf8","actualValue":"NULL"},"pipeline_dop":{"defaultValue":0,"actualValue":16}}
+"#;
+        let summary = SectionParser::parse_summary(profile).unwrap();
+        assert_eq!(summary.variables.get("parallel_fragment_exec_instance_num").map(String::as_str), Some("8"));
+        assert_eq!(summary.variables.get("query_timeout").map(String::as_str), Some("90000"));
+        assert_eq!(summary.variables.get("character_set_results").map(String::as_str), Some("NULL"));
+        assert_eq!(summary.variables.get("pipeline_dop").map(String::as_str), Some("16"));
+    }
+    
+    #[test]
+    fn test_parse_planner_timers() {
+        let profile = r#"
+Query:
+  Planner:
+     - -- Total[1] 4ms
+     -     -- Optimizer[1] 2ms
+     -         -- RuleBaseOptimize[1] 1ms
+     - -- Deploy[1] 7ms
+     -     -- DeployWaitTime[9] 0
+     - DeployDataSize: 108974
+  Execution:
+"#;
+        let planner = SectionParser::parse_planner(profile).unwrap();
+        assert_eq!(planner.details.get("DeployDataSize").map(String::as_str), Some("108974"));
+        assert_eq!(planner.timers.len(), 5);
+        assert_eq!(planner.timers[2].name, "RuleBaseOptimize");
+        assert_eq!(planner.timers[2].depth, 2);
+        assert_eq!(planner.timers[4].count, 9);
+        assert_eq!(planner.timers[3].time_ms, 7.0);
+    }
+    
+    #[test]
+    fn test_parse_reason() {
+        let failed = r#"
+Query:
+  Summary:
+     - Query State: Error
+  Planner:
+     - -- Deploy[1] 7ms
+    Reason: Memory of query_pool exceed limit. Pipeline Backend: 172.26.92.1, fragment: 2
+  Execution:
+"#;
+        let summary = SectionParser::parse_summary(failed).unwrap();
+        assert!(summary.failure_reason.unwrap().starts_with("Memory of query_pool"));
+        
+        let finished = failed.replace("Reason: Memory of query_pool exceed limit. Pipeline Backend: 172.26.92.1, fragment: 2", "Reason:");
+        assert_eq!(SectionParser::parse_summary(&finished).unwrap().failure_reason, None);
+    }
 }
-
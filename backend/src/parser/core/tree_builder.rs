@@ -226,10 +226,10 @@ impl TreeBuilder {
                     
                     use crate::constants::time_thresholds;
                     let percentage = node_info.total_time_percentage;
-                    if percentage > time_thresholds::MOST_CONSUMING_THRESHOLD {
+                    if percentage > time_thresholds::most_consuming() {
                         node.is_most_consuming = true;
                         node.is_second_most_consuming = false;
-                    } else if percentage > time_thresholds::SECOND_CONSUMING_THRESHOLD {
+                    } else if percentage > time_thresholds::second_consuming() {
                         node.is_most_consuming = false;
                         node.is_second_most_consuming = true;
                     } else {
@@ -838,6 +838,9 @@ mod tests {
                 time_percentage: None,
                 is_most_consuming: false,
                 is_second_most_consuming: false,
+                is_on_critical_path: false,
+                has_chunk_accumulate: false,
+                chunk_fill: None,
                 unique_metrics: HashMap::new(),
             },
             ExecutionTreeNode {
@@ -856,6 +859,9 @@ mod tests {
                 time_percentage: None,
                 is_most_consuming: false,
                 is_second_most_consuming: false,
+                is_on_critical_path: false,
+                has_chunk_accumulate: false,
+                chunk_fill: None,
                 unique_metrics: HashMap::new(),
             },
         ];
@@ -1,6 +1,7 @@
-
 use crate::models::{AggregateSpecializedMetrics, OperatorSpecializedMetrics};
 use super::strategy::SpecializedMetricsStrategy;
+use crate::parser::core::ValueParser;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct AggregateStrategy;
@@ -13,23 +14,51 @@ impl SpecializedMetricsStrategy for AggregateStrategy {
 
 impl AggregateStrategy {
     fn parse_aggregate(text: &str) -> AggregateSpecializedMetrics {
-        let mut agg_mode = String::from("NORMAL");
-        
-        for line in text.lines() {
-            let trimmed = line.trim();
-            if let Some((key, value)) = Self::parse_kv_line(trimmed) {
-                if key == "AggMode" {
-                    agg_mode = value.to_string();
-                }
-            }
-        }
-        
-        AggregateSpecializedMetrics {
-            agg_mode,
+        Self::from_pairs(text.lines().filter_map(|line| Self::parse_kv_line(line.trim())))
+    }
+    
+    /// Builds the metrics from an `AGGREGATE_*_SINK` operator's unique metrics.
+    pub fn from_metrics(metrics: &HashMap<String, String>) -> AggregateSpecializedMetrics {
+        Self::from_pairs(metrics.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+    
+    fn from_pairs<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> AggregateSpecializedMetrics {
+        let mut metrics = AggregateSpecializedMetrics {
+            agg_mode: String::from("NORMAL"),
             chunk_by_chunk: false,
             input_rows: None,
             agg_function_time: None,
+            aggregate_functions: String::new(),
+            grouping_keys: String::new(),
+            hash_table_size: None,
+            hash_table_memory: None,
+            pass_through_rows: None,
+            agg_compute_time: None,
+            expr_compute_time: None,
+            streaming_time: None,
+            spill_bytes: None,
+        };
+        
+        for (key, value) in pairs {
+            match key {
+                "AggMode" => metrics.agg_mode = value.to_string(),
+                "ChunkByChunk" => metrics.chunk_by_chunk = value.to_lowercase() == "true",
+                "InputRowCount" => metrics.input_rows = ValueParser::parse_number(value).ok(),
+                "AggFuncComputeTime" => metrics.agg_function_time = ValueParser::parse_duration(value).ok(),
+                "AggregateFunctions" => metrics.aggregate_functions = value.to_string(),
+                "GroupingKeys" => metrics.grouping_keys = value.to_string(),
+                "HashTableSize" => metrics.hash_table_size = ValueParser::parse_number(value).ok(),
+                "HashTableMemoryUsage" => metrics.hash_table_memory = ValueParser::parse_bytes(value).ok(),
+                "PassThroughRowCount" => metrics.pass_through_rows = ValueParser::parse_number(value).ok(),
+                "AggComputeTime" => metrics.agg_compute_time = ValueParser::parse_duration(value).ok(),
+                "ExprComputeTime" => metrics.expr_compute_time = ValueParser::parse_duration(value).ok(),
+                "StreamingTime" => metrics.streaming_time = ValueParser::parse_duration(value).ok(),
+                "SpillBytes" => metrics.spill_bytes = ValueParser::parse_bytes(value).ok(),
+                _ => {}
+            }
         }
+        
+        metrics
     }
     
     fn parse_kv_line(line: &str) -> Option<(&str, &str)> {
@@ -45,4 +74,3 @@ impl AggregateStrategy {
         }
     }
 }
-
@@ -2,7 +2,7 @@
 use crate::models::{ExchangeSinkSpecializedMetrics, OperatorSpecializedMetrics};
 use super::strategy::SpecializedMetricsStrategy;
 use crate::parser::core::ValueParser;
-use std::time::Duration;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct ExchangeSinkStrategy;
@@ -18,43 +18,56 @@ impl SpecializedMetricsStrategy for ExchangeSinkStrategy {
 
 impl ExchangeSinkStrategy {
     fn parse_exchange_sink(text: &str) -> ExchangeSinkSpecializedMetrics {
-        let mut part_type = String::from("UNPARTITIONED");
-        let mut bytes_sent: Option<u64> = None;
-        let mut bytes_pass_through: Option<u64> = None;
-        let mut request_sent: Option<u64> = None;
-        let mut network_time: Option<Duration> = None;
-        let mut overall_time: Option<Duration> = None;
-        let mut dest_fragment_ids = Vec::new();
-        let dest_be_addresses = Vec::new();
+        Self::from_pairs(text.lines().filter_map(|line| Self::parse_kv_line(line.trim())))
+    }
+    
+    /// Builds the metrics from an operator's already-split unique metrics.
+    pub fn from_metrics(metrics: &HashMap<String, String>) -> ExchangeSinkSpecializedMetrics {
+        Self::from_pairs(metrics.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+    
+    fn from_pairs<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> ExchangeSinkSpecializedMetrics {
+        let mut metrics = ExchangeSinkSpecializedMetrics {
+            dest_fragment_ids: Vec::new(),
+            dest_be_addresses: Vec::new(),
+            part_type: String::from("UNPARTITIONED"),
+            bytes_sent: None,
+            bytes_pass_through: None,
+            request_sent: None,
+            network_time: None,
+            overall_time: None,
+            dest_id: None,
+            channel_num: None,
+            serialized_bytes: None,
+            compressed_bytes: None,
+            compress_time: None,
+            serialize_chunk_time: None,
+            shuffle_hash_time: None,
+        };
         
-        for line in text.lines() {
-            let trimmed = line.trim();
-            if let Some((key, value)) = Self::parse_kv_line(trimmed) {
-                match key {
-                    "PartType" => part_type = value.to_string(),
-                    "BytesSent" => bytes_sent = ValueParser::parse_bytes(value).ok(),
-                    "BytesPassThrough" => bytes_pass_through = ValueParser::parse_bytes(value).ok(),
-                    "RequestSent" => request_sent = ValueParser::parse_number(value).ok(),
-                    "NetworkTime" => network_time = ValueParser::parse_duration(value).ok(),
-                    "OverallTime" => overall_time = ValueParser::parse_duration(value).ok(),
-                    "DestFragments" => {
-                        dest_fragment_ids = value.split(',').map(|s| s.trim().to_string()).collect();
-                    }
-                    _ => {}
+        for (key, value) in pairs {
+            match key {
+                "PartType" => metrics.part_type = value.to_string(),
+                "BytesSent" => metrics.bytes_sent = ValueParser::parse_bytes(value).ok(),
+                "BytesPassThrough" => metrics.bytes_pass_through = ValueParser::parse_bytes(value).ok(),
+                "RequestSent" => metrics.request_sent = ValueParser::parse_number(value).ok(),
+                "NetworkTime" => metrics.network_time = ValueParser::parse_duration(value).ok(),
+                "OverallTime" => metrics.overall_time = ValueParser::parse_duration(value).ok(),
+                "DestID" => metrics.dest_id = ValueParser::parse_number(value).ok(),
+                "ChannelNum" => metrics.channel_num = ValueParser::parse_number(value).ok(),
+                "SerializedBytes" => metrics.serialized_bytes = ValueParser::parse_bytes(value).ok(),
+                "CompressedBytes" => metrics.compressed_bytes = ValueParser::parse_bytes(value).ok(),
+                "CompressTime" => metrics.compress_time = ValueParser::parse_duration(value).ok(),
+                "SerializeChunkTime" => metrics.serialize_chunk_time = ValueParser::parse_duration(value).ok(),
+                "ShuffleHashTime" => metrics.shuffle_hash_time = ValueParser::parse_duration(value).ok(),
+                "DestFragments" => {
+                    metrics.dest_fragment_ids = value.split(',').map(|s| s.trim().to_string()).collect();
                 }
+                _ => {}
             }
         }
         
-        ExchangeSinkSpecializedMetrics {
-            dest_fragment_ids,
-            dest_be_addresses,
-            part_type,
-            bytes_sent,
-            bytes_pass_through,
-            request_sent,
-            network_time,
-            overall_time,
-        }
+        metrics
     }
     
     fn parse_kv_line(line: &str) -> Option<(&str, &str)> {
@@ -0,0 +1,51 @@
+use crate::models::{LocalExchangeSpecializedMetrics, OperatorSpecializedMetrics};
+use super::strategy::SpecializedMetricsStrategy;
+use crate::parser::core::ValueParser;
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub struct LocalExchangeStrategy;
+
+impl SpecializedMetricsStrategy for LocalExchangeStrategy {
+    fn parse(&self, text: &str) -> OperatorSpecializedMetrics {
+        OperatorSpecializedMetrics::LocalExchange(Self::from_pairs(
+            text.lines().filter_map(|line| Self::parse_kv_line(line.trim())),
+        ))
+    }
+}
+
+impl LocalExchangeStrategy {
+    /// Builds the metrics from a `LOCAL_EXCHANGE_SINK` operator's unique metrics.
+    pub fn from_metrics(metrics: &HashMap<String, String>) -> LocalExchangeSpecializedMetrics {
+        Self::from_pairs(metrics.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+    
+    fn from_pairs<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> LocalExchangeSpecializedMetrics {
+        let mut metrics = LocalExchangeSpecializedMetrics {
+            exchange_type: String::new(),
+            shuffle_num: None,
+            peak_memory: None,
+            peak_buffer_memory: None,
+            buffer_unplug_count: None,
+        };
+        
+        for (key, value) in pairs {
+            match key {
+                "Type" => metrics.exchange_type = value.to_string(),
+                "ShuffleNum" => metrics.shuffle_num = ValueParser::parse_number(value).ok(),
+                "LocalExchangePeakMemoryUsage" => metrics.peak_memory = ValueParser::parse_bytes(value).ok(),
+                "PeakBufferMemoryBytes" => metrics.peak_buffer_memory = ValueParser::parse_bytes(value).ok(),
+                "BufferUnplugCount" => metrics.buffer_unplug_count = ValueParser::parse_number(value).ok(),
+                _ => {}
+            }
+        }
+        
+        metrics
+    }
+    
+    fn parse_kv_line(line: &str) -> Option<(&str, &str)> {
+        let rest = line.strip_prefix('-')?.trim();
+        let (key, value) = rest.split_once(':')?;
+        Some((key.trim(), value.trim()))
+    }
+}
@@ -6,6 +6,7 @@ pub mod join_strategy;
 pub mod aggregate_strategy;
 pub mod result_sink_strategy;
 pub mod olap_table_sink_strategy;
+pub mod local_exchange_strategy;
 
 pub use strategy::SpecializedMetricsStrategy;
 
@@ -18,6 +19,7 @@ pub struct SpecializedMetricsParser {
     aggregate: AggregateStrategy,
     result_sink: ResultSinkStrategy,
     olap_table_sink: OlapTableSinkStrategy,
+    local_exchange: LocalExchangeStrategy,
 }
 
 impl Default for SpecializedMetricsParser {
@@ -36,6 +38,7 @@ impl SpecializedMetricsParser {
             aggregate: AggregateStrategy,
             result_sink: ResultSinkStrategy,
             olap_table_sink: OlapTableSinkStrategy,
+            local_exchange: LocalExchangeStrategy,
         }
     }
     
@@ -52,6 +55,7 @@ impl SpecializedMetricsParser {
             "AGGREGATE" | "AGGREGATION" => self.aggregate.parse(text),
             "RESULT_SINK" => self.result_sink.parse(text),
             "OLAP_TABLE_SINK" => self.olap_table_sink.parse(text),
+            "LOCAL_EXCHANGE_SINK" => self.local_exchange.parse(text),
             _ => {
                 println!("DEBUG: No specialized parser for operator: '{}'", operator_name);
                 OperatorSpecializedMetrics::None
@@ -65,3 +69,4 @@ pub use join_strategy::JoinStrategy;
 pub use aggregate_strategy::AggregateStrategy;
 pub use result_sink_strategy::ResultSinkStrategy;
 pub use olap_table_sink_strategy::OlapTableSinkStrategy;
+pub use local_exchange_strategy::LocalExchangeStrategy;
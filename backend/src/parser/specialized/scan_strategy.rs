@@ -1,11 +1,12 @@
 //! 
 
 use crate::models::{
-    OperatorSpecializedMetrics, OlapScanSpecializedMetrics, ConnectorScanSpecializedMetrics,
+    OperatorSpecializedMetrics, OlapScanSpecializedMetrics, ConnectorScanSpecializedMetrics, ScanRowFunnel,
+    ExternalCatalog, ExternalScanMetrics,
 };
 use super::strategy::SpecializedMetricsStrategy;
 use crate::parser::core::ValueParser;
-use std::time::Duration;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone)]
 pub struct ScanStrategy;
@@ -33,36 +34,82 @@ impl SpecializedMetricsStrategy for ScanStrategy {
 
 impl ScanStrategy {
     fn parse_olap_scan(text: &str) -> OlapScanSpecializedMetrics {
-        let mut table = String::new();
-        let mut rollup = String::new();
-        let mut scan_time: Option<Duration> = None;
-        let mut io_time: Option<Duration> = None;
-        let mut bytes_read: Option<u64> = None;
-        let mut rows_read: Option<u64> = None;
+        Self::olap_from_pairs(text.lines().filter_map(|line| Self::parse_kv_line(line.trim())))
+    }
+    
+    /// Builds native-table scan metrics from an operator's flattened unique metrics.
+    /// Also used for `CONNECTOR_SCAN` over `LakeDataSource`, which reports the same counters.
+    pub fn olap_from_metrics(metrics: &HashMap<String, String>) -> OlapScanSpecializedMetrics {
+        Self::olap_from_pairs(metrics.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+    }
+    
+    fn olap_from_pairs<'a>(pairs: impl Iterator<Item = (&'a str, &'a str)>) -> OlapScanSpecializedMetrics {
+        let mut metrics = OlapScanSpecializedMetrics {
+            table: String::new(),
+            rollup: String::new(),
+            shared_scan: false,
+            scan_time: None,
+            io_time: None,
+            bytes_read: None,
+            rows_read: None,
+            raw_rows_read: None,
+            tablet_count: None,
+            pushdown_predicates: None,
+            predicates: String::new(),
+            segments_read_count: None,
+            rowsets_read_count: None,
+            segment_init: None,
+            create_segment_iter: None,
+            bloom_filter_time: None,
+            funnel: ScanRowFunnel::default(),
+        };
         
-        for line in text.lines() {
-            let trimmed = line.trim();
-            if let Some((key, value)) = Self::parse_kv_line(trimmed) {
-                match key {
-                    "Table" => table = value.to_string(),
-                    "Rollup" => rollup = value.to_string(),
-                    "ScanTime" => scan_time = ValueParser::parse_duration(value).ok(),
-                    "IOTime" => io_time = ValueParser::parse_duration(value).ok(),
-                    "BytesRead" => bytes_read = ValueParser::parse_bytes(value).ok(),
-                    "RowsRead" => rows_read = ValueParser::parse_number(value).ok(),
-                    _ => {}
-                }
+        for (key, value) in pairs {
+            Self::apply_funnel_counter(&mut metrics.funnel, key, value);
+            match key {
+                "Table" => metrics.table = value.to_string(),
+                "Rollup" => metrics.rollup = value.to_string(),
+                "SharedScan" => metrics.shared_scan = value.to_lowercase() == "true",
+                "ScanTime" => metrics.scan_time = ValueParser::parse_duration(value).ok(),
+                "IOTime" => metrics.io_time = ValueParser::parse_duration(value).ok(),
+                "BytesRead" => metrics.bytes_read = ValueParser::parse_bytes(value).ok(),
+                "RowsRead" => metrics.rows_read = ValueParser::parse_number(value).ok(),
+                "RawRowsRead" => metrics.raw_rows_read = ValueParser::parse_number(value).ok(),
+                "TabletCount" => metrics.tablet_count = ValueParser::parse_number(value).ok(),
+                "PushdownPredicates" => metrics.pushdown_predicates = ValueParser::parse_number(value).ok(),
+                "Predicates" => metrics.predicates = value.to_string(),
+                "SegmentsReadCount" => metrics.segments_read_count = ValueParser::parse_number(value).ok(),
+                "RowsetsReadCount" => metrics.rowsets_read_count = ValueParser::parse_number(value).ok(),
+                "SegmentInit" => metrics.segment_init = ValueParser::parse_duration(value).ok(),
+                "CreateSegmentIter" => metrics.create_segment_iter = ValueParser::parse_duration(value).ok(),
+                "BloomFilterFilter" => metrics.bloom_filter_time = ValueParser::parse_duration(value).ok(),
+                _ => {}
             }
         }
         
-        OlapScanSpecializedMetrics {
-            table,
-            rollup,
-            shared_scan: false,
-            scan_time,
-            io_time,
-            bytes_read,
-            rows_read,
+        metrics
+    }
+    
+    fn apply_funnel_counter(funnel: &mut ScanRowFunnel, key: &str, value: &str) {
+        let rows = || ValueParser::parse_number::<u64>(value).ok();
+        let add = |slot: &mut Option<u64>| {
+            if let Some(v) = rows() {
+                *slot = Some(slot.unwrap_or(0) + v);
+            }
+        };
+        match key {
+            "RemainingRowsAfterShortKeyFilter" => funnel.rows_after_short_key = rows(),
+            "ShortKeyFilterRows" => funnel.short_key_filter_rows = rows(),
+            "ZoneMapIndexFilterRows" | "SegmentZoneMapFilterRows" | "SegmentRuntimeZoneMapFilterRows" => {
+                add(&mut funnel.zone_map_filter_rows)
+            }
+            "BloomFilterFilterRows" => funnel.bloom_filter_rows = rows(),
+            "BitmapIndexFilterRows" => funnel.bitmap_index_filter_rows = rows(),
+            "DelVecFilterRows" => funnel.del_vec_filter_rows = rows(),
+            "RawRowsRead" => funnel.raw_rows_read = rows(),
+            "PredFilterRows" => funnel.pred_filter_rows = rows(),
+            "RowsRead" => funnel.rows_read = rows(),
+            _ => {}
         }
     }
     
@@ -70,28 +117,7 @@ impl ScanStrategy {
         println!("DEBUG: parse_connector_scan called with text length: {}", text.len());
         println!("DEBUG: First 500 chars: {}", &text[..text.len().min(500)]);
         
-        let mut data_source_type = String::new();
-        let mut table = String::new();
-        let mut rollup = String::new();
-        let mut morsel_queue_type = String::new();
-        let mut shared_scan = false;
-        let mut io_time: Option<Duration> = None;
-        let mut io_task_exec_time: Option<Duration> = None;
-        let mut scan_time: Option<Duration> = None;
-        let mut bytes_read: Option<u64> = None;
-        let mut uncompressed_bytes_read: Option<u64> = None;
-        let mut rows_read: Option<u64> = None;
-        let mut raw_rows_read: Option<u64> = None;
-        let mut compressed_bytes_read_local_disk: Option<u64> = None;
-        let mut compressed_bytes_read_remote: Option<u64> = None;
-        let mut compressed_bytes_read_request: Option<u64> = None;
-        let mut io_count_local_disk: Option<u64> = None;
-        let mut io_count_remote: Option<u64> = None;
-        let mut io_time_local_disk: Option<Duration> = None;
-        let mut io_time_remote: Option<Duration> = None;
-        let mut segment_init: Option<Duration> = None;
-        let mut segment_read: Option<Duration> = None;
-        let mut segment_read_count: Option<u64> = None;
+        let mut metrics = Self::empty_connector_scan();
         
         let mut _in_io_statistics = false;
         let mut in_io_task_exec = false;
@@ -114,68 +140,161 @@ impl ScanStrategy {
             
             if let Some((key, value)) = Self::parse_kv_line(trimmed) {
                 println!("DEBUG: Parsed key='{}', value='{}'", key, value);
-                match key {
-                    "DataSourceType" => data_source_type = value.to_string(),
-                    "Table" => table = value.to_string(),
-                    "Rollup" => rollup = value.to_string(),
-                    "MorselQueueType" => morsel_queue_type = value.to_string(),
-                    "SharedScan" => shared_scan = value.to_lowercase() == "true",
-                    "ScanTime" => scan_time = ValueParser::parse_duration(value).ok(),
-                    "IOTime" => {
-                        if in_io_task_exec {
-                            io_task_exec_time = ValueParser::parse_duration(value).ok();
-                        } else {
-                            io_time = ValueParser::parse_duration(value).ok();
-                        }
-                    }
-                    "BytesRead" => bytes_read = ValueParser::parse_bytes(value).ok(),
-                    "UncompressedBytesRead" => uncompressed_bytes_read = ValueParser::parse_bytes(value).ok(),
-                    "RowsRead" => rows_read = ValueParser::parse_number(value).ok(),
-                    "RawRowsRead" => raw_rows_read = ValueParser::parse_number(value).ok(),
-                    "CompressedBytesReadLocalDisk" => {
-                        compressed_bytes_read_local_disk = ValueParser::parse_bytes(value).ok();
-                    }
-                    "CompressedBytesReadRemote" => {
-                        compressed_bytes_read_remote = ValueParser::parse_bytes(value).ok();
-                    }
-                    "CompressedBytesReadRequest" => {
-                        compressed_bytes_read_request = ValueParser::parse_bytes(value).ok();
-                    }
-                    "IOCountLocalDisk" => io_count_local_disk = ValueParser::parse_number(value).ok(),
-                    "IOCountRemote" => io_count_remote = ValueParser::parse_number(value).ok(),
-                    "IOTimeLocalDisk" => io_time_local_disk = ValueParser::parse_duration(value).ok(),
-                    "IOTimeRemote" => io_time_remote = ValueParser::parse_duration(value).ok(),
-                    "SegmentInit" => segment_init = ValueParser::parse_duration(value).ok(),
-                    "SegmentRead" => segment_read = ValueParser::parse_duration(value).ok(),
-                    "SegmentsReadCount" => segment_read_count = ValueParser::parse_number(value).ok(),
-                    _ => {}
+                Self::apply_connector_counter(&mut metrics, key, value, in_io_task_exec);
+            }
+        }
+        
+        metrics.external = ExternalCatalog::from_data_source_type(&metrics.data_source_type).map(|catalog| {
+            Self::external_from_pairs(catalog, text.lines().filter_map(|line| Self::parse_kv_line(line.trim())))
+        });
+        metrics
+    }
+    
+    /// Builds connector scan metrics from an operator's flattened unique metrics.
+    /// The nesting is lost there, so `IOTime` is taken as the scan's IO time.
+    pub fn connector_from_metrics(metrics: &HashMap<String, String>) -> ConnectorScanSpecializedMetrics {
+        let mut result = Self::empty_connector_scan();
+        for (key, value) in metrics {
+            Self::apply_connector_counter(&mut result, key, value, false);
+        }
+        result.external = ExternalCatalog::from_data_source_type(&result.data_source_type).map(|catalog| {
+            Self::external_from_pairs(catalog, metrics.iter().map(|(k, v)| (k.as_str(), v.as_str())))
+        });
+        result
+    }
+    
+    /// Collects the file-format and catalog specific counters of an external scan.
+    /// Counter names differ between versions, so the known aliases are all accepted.
+    fn external_from_pairs<'a>(catalog: ExternalCatalog, pairs: impl Iterator<Item = (&'a str, &'a str)>) -> ExternalScanMetrics {
+        let mut metrics = ExternalScanMetrics {
+            catalog,
+            file_format: None,
+            scan_ranges: None,
+            scan_ranges_size: None,
+            row_groups_total: None,
+            row_groups_filtered: None,
+            stripes_total: None,
+            stripes_filtered: None,
+            page_index_filtered_rows: None,
+            delete_files_per_scan: None,
+            delete_file_build_time: None,
+            delete_file_build_filter_time: None,
+            open_file_time: None,
+            reader_init_time: None,
+        };
+        let mut refined = None;
+        
+        for (key, value) in pairs {
+            let count = || ValueParser::parse_number::<u64>(value).ok();
+            let time = || ValueParser::parse_duration(value).ok();
+            match key {
+                "ScanRanges" => metrics.scan_ranges = count(),
+                "ScanRangesSize" => metrics.scan_ranges_size = ValueParser::parse_bytes(value).ok(),
+                "TotalRowGroups" | "RowGroupsTotal" => metrics.row_groups_total = count(),
+                "FilteredRowGroups" | "RowGroupsFiltered" | "SkipRowGroups" => metrics.row_groups_filtered = count(),
+                "TotalStripeNumber" | "StripeNumber" => metrics.stripes_total = count(),
+                "FilteredStripeNumber" | "SkipStripes" => metrics.stripes_filtered = count(),
+                "PageIndexFilterRows" | "PageSkipRows" => metrics.page_index_filtered_rows = count(),
+                "DeleteFilesPerScan" => metrics.delete_files_per_scan = count(),
+                "DeleteFileBuildTime" => metrics.delete_file_build_time = time(),
+                "DeleteFileBuildFilterTime" => metrics.delete_file_build_filter_time = time(),
+                "OpenFile" | "OpenFileTime" => metrics.open_file_time = time(),
+                "ReaderInit" | "ReaderInitTime" => metrics.reader_init_time = time(),
+                _ => {}
+            }
+            
+            if metrics.file_format.is_none() {
+                if key.contains("RowGroup") || key.starts_with("PageIndex") {
+                    metrics.file_format = Some("Parquet".to_string());
+                } else if key.contains("Stripe") {
+                    metrics.file_format = Some("ORC".to_string());
+                } else if key.starts_with("JNI") || key.starts_with("Jni") {
+                    metrics.file_format = Some("JNI".to_string());
                 }
             }
+            if key.starts_with("DeleteFile") || key.starts_with("IcebergV2") {
+                refined = Some(ExternalCatalog::Iceberg);
+            } else if key.starts_with("Hudi") {
+                refined = refined.or(Some(ExternalCatalog::Hudi));
+            } else if key.starts_with("Paimon") {
+                refined = refined.or(Some(ExternalCatalog::Paimon));
+            } else if key.starts_with("DeltaLake") {
+                refined = refined.or(Some(ExternalCatalog::DeltaLake));
+            }
         }
         
+        if metrics.catalog == ExternalCatalog::Hive {
+            metrics.catalog = refined.unwrap_or(ExternalCatalog::Hive);
+        }
+        metrics
+    }
+    
+    fn empty_connector_scan() -> ConnectorScanSpecializedMetrics {
         ConnectorScanSpecializedMetrics {
-            data_source_type,
-            table,
-            rollup,
-            shared_scan,
-            morsel_queue_type,
-            io_time,
-            io_task_exec_time,
-            scan_time,
-            bytes_read,
-            uncompressed_bytes_read,
-            rows_read,
-            raw_rows_read,
-            compressed_bytes_read_local_disk,
-            compressed_bytes_read_remote,
-            compressed_bytes_read_request,
-            io_count_local_disk,
-            io_count_remote,
-            io_time_local_disk,
-            io_time_remote,
-            segment_init,
-            segment_read,
-            segment_read_count,
+            data_source_type: String::new(),
+            table: String::new(),
+            rollup: String::new(),
+            shared_scan: false,
+            morsel_queue_type: String::new(),
+            io_time: None,
+            io_task_exec_time: None,
+            scan_time: None,
+            bytes_read: None,
+            uncompressed_bytes_read: None,
+            rows_read: None,
+            raw_rows_read: None,
+            compressed_bytes_read_local_disk: None,
+            compressed_bytes_read_remote: None,
+            compressed_bytes_read_request: None,
+            io_count_local_disk: None,
+            io_count_remote: None,
+            io_time_local_disk: None,
+            io_time_remote: None,
+            segment_init: None,
+            segment_read: None,
+            segment_read_count: None,
+            funnel: ScanRowFunnel::default(),
+            external: None,
+        }
+    }
+    
+    fn apply_connector_counter(metrics: &mut ConnectorScanSpecializedMetrics, key: &str, value: &str, in_io_task_exec: bool) {
+        Self::apply_funnel_counter(&mut metrics.funnel, key, value);
+        match key {
+            "DataSourceType" => metrics.data_source_type = value.to_string(),
+            "Table" => metrics.table = value.to_string(),
+            "Rollup" => metrics.rollup = value.to_string(),
+            "MorselQueueType" => metrics.morsel_queue_type = value.to_string(),
+            "SharedScan" => metrics.shared_scan = value.to_lowercase() == "true",
+            "ScanTime" => metrics.scan_time = ValueParser::parse_duration(value).ok(),
+            "IOTime" => {
+                if in_io_task_exec {
+                    metrics.io_task_exec_time = ValueParser::parse_duration(value).ok();
+                } else {
+                    metrics.io_time = ValueParser::parse_duration(value).ok();
+                }
+            }
+            "BytesRead" => metrics.bytes_read = ValueParser::parse_bytes(value).ok(),
+            "UncompressedBytesRead" => metrics.uncompressed_bytes_read = ValueParser::parse_bytes(value).ok(),
+            "RowsRead" => metrics.rows_read = ValueParser::parse_number(value).ok(),
+            "RawRowsRead" => metrics.raw_rows_read = ValueParser::parse_number(value).ok(),
+            "CompressedBytesReadLocalDisk" => {
+                metrics.compressed_bytes_read_local_disk = ValueParser::parse_bytes(value).ok();
+            }
+            "CompressedBytesReadRemote" => {
+                metrics.compressed_bytes_read_remote = ValueParser::parse_bytes(value).ok();
+            }
+            "CompressedBytesReadRequest" => {
+                metrics.compressed_bytes_read_request = ValueParser::parse_bytes(value).ok();
+            }
+            "IOCountLocalDisk" => metrics.io_count_local_disk = ValueParser::parse_number(value).ok(),
+            "IOCountRemote" => metrics.io_count_remote = ValueParser::parse_number(value).ok(),
+            "IOTimeLocalDisk" => metrics.io_time_local_disk = ValueParser::parse_duration(value).ok(),
+            "IOTimeRemote" => metrics.io_time_remote = ValueParser::parse_duration(value).ok(),
+            "SegmentInit" => metrics.segment_init = ValueParser::parse_duration(value).ok(),
+            "SegmentRead" => metrics.segment_read = ValueParser::parse_duration(value).ok(),
+            "SegmentsReadCount" => metrics.segment_read_count = ValueParser::parse_number(value).ok(),
+            _ => {}
         }
     }
     
@@ -231,4 +350,62 @@ OLAP_SCAN (plan_node_id=0):
             panic!("Expected OlapScan metrics");
         }
     }
+    
+    #[test]
+    fn test_parse_olap_scan_funnel() {
+        let text = r#"
+OLAP_SCAN (plan_node_id=0):
+  UniqueMetrics:
+     - TabletCount: 32
+     - RawRowsRead: 94.287M (94286609)
+     - RowsRead: 2.251M (2250692)
+       - ShortKeyFilterRows: 10
+       - SegmentZoneMapFilterRows: 100
+       - ZoneMapIndexFilterRows: 1.000K (1000)
+       - PredFilterRows: 92.036M (92035917)
+       - RowsetsReadCount: 89.600K (89600)
+"#;
+        
+        if let OperatorSpecializedMetrics::OlapScan(metrics) = ScanStrategy.parse(text) {
+            assert_eq!(metrics.tablet_count, Some(32));
+            assert_eq!(metrics.rowsets_read_count, Some(89600));
+            assert_eq!(metrics.funnel.zone_map_filter_rows, Some(1100));
+            assert_eq!(metrics.funnel.index_filtered_rows(), 1110);
+            assert_eq!(metrics.funnel.candidate_rows(), Some(94286609 + 1110));
+            assert_eq!(metrics.funnel.raw_rows_read.unwrap() - metrics.funnel.pred_filter_rows.unwrap(), 2250692);
+        } else {
+            panic!("Expected OlapScan metrics");
+        }
+    }
+    
+    #[test]
+    fn test_parse_external_scan() {
+        let text = r#"
+CONNECTOR_SCAN (plan_node_id=0):
+  UniqueMetrics:
+     - DataSourceType: HiveDataSource
+     - Table: orders
+     - ScanRanges: 12.345K (12345)
+     - TotalRowGroups: 400
+     - FilteredRowGroups: 0
+     - IcebergV2FormatTimer:
+       - DeleteFileBuildTime: 2s
+       - DeleteFilesPerScan: 16
+"#;
+        
+        if let OperatorSpecializedMetrics::ConnectorScan(metrics) = ScanStrategy.parse(text) {
+            let external = metrics.external.expect("external scan metrics");
+            assert_eq!(external.catalog, ExternalCatalog::Iceberg);
+            assert_eq!(external.file_format.as_deref(), Some("Parquet"));
+            assert_eq!(external.scan_ranges, Some(12345));
+            assert_eq!(external.row_groups_filtered, Some(0));
+            assert_eq!(external.delete_files_per_scan, Some(16));
+        } else {
+            panic!("Expected ConnectorScan metrics");
+        }
+        
+        let mut lake = HashMap::new();
+        lake.insert("DataSourceType".to_string(), "LakeDataSource".to_string());
+        assert!(ScanStrategy::connector_from_metrics(&lake).external.is_none());
+    }
 }
@@ -0,0 +1,287 @@
+//! Metric expressions of hotspot rules.
+//!
+//! Arithmetic (`+ - * /`), comparisons (`> >= < <= == !=`), `&& || !`, parentheses and the
+//! functions `max(..)`, `min(..)`, `default(expr, fallback)` and `has(metric)` over numeric
+//! variables such as `unique.SegmentsReadCount` or `query.wall_time_ms`. Booleans are 1 / 0.
+//! A missing variable or a division by zero makes the whole expression `None`, so a rule
+//! whose metrics are absent simply does not fire.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Var(String),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+    Ne,
+    And,
+    Or,
+}
+
+const FUNCTIONS: &[&str] = &["max", "min", "default", "has"];
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+impl Expr {
+    pub fn parse(input: &str) -> Result<Expr, String> {
+        let tokens = tokenize(input)?;
+        let mut parser = Parser { tokens, pos: 0 };
+        let expr = parser.parse_or()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(expr),
+            Some(token) => Err(format!("unexpected {:?} in '{}'", token, input)),
+        }
+    }
+
+    pub fn eval(&self, vars: &dyn Fn(&str) -> Option<f64>) -> Option<f64> {
+        match self {
+            Expr::Number(n) => Some(*n),
+            Expr::Var(name) => vars(name),
+            Expr::Neg(inner) => inner.eval(vars).map(|v| -v),
+            Expr::Not(inner) => inner.eval(vars).map(|v| bool_value(v == 0.0)),
+            Expr::Binary(op, lhs, rhs) => {
+                let (l, r) = (lhs.eval(vars)?, rhs.eval(vars)?);
+                match op {
+                    BinaryOp::Add => Some(l + r),
+                    BinaryOp::Sub => Some(l - r),
+                    BinaryOp::Mul => Some(l * r),
+                    BinaryOp::Div => (r != 0.0).then(|| l / r),
+                    BinaryOp::Gt => Some(bool_value(l > r)),
+                    BinaryOp::Ge => Some(bool_value(l >= r)),
+                    BinaryOp::Lt => Some(bool_value(l < r)),
+                    BinaryOp::Le => Some(bool_value(l <= r)),
+                    BinaryOp::Eq => Some(bool_value(l == r)),
+                    BinaryOp::Ne => Some(bool_value(l != r)),
+                    BinaryOp::And => Some(bool_value(l != 0.0 && r != 0.0)),
+                    BinaryOp::Or => Some(bool_value(l != 0.0 || r != 0.0)),
+                }
+            }
+            Expr::Call(name, args) => match name.as_str() {
+                "has" => Some(bool_value(args[0].eval(vars).is_some())),
+                "default" => args[0].eval(vars).or_else(|| args[1].eval(vars)),
+                "max" => args.iter().map(|a| a.eval(vars)).collect::<Option<Vec<_>>>()?.into_iter().reduce(f64::max),
+                "min" => args.iter().map(|a| a.eval(vars)).collect::<Option<Vec<_>>>()?.into_iter().reduce(f64::min),
+                _ => None,
+            },
+        }
+    }
+}
+
+fn bool_value(b: bool) -> f64 {
+    if b { 1.0 } else { 0.0 }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit() || (c == '.' && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit())) {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_digit() || chars[i] == '.' || chars[i] == '_') {
+                i += 1;
+            }
+            let text: String = chars[start..i].iter().filter(|c| **c != '_').collect();
+            tokens.push(Token::Number(text.parse().map_err(|_| format!("invalid number '{}'", text))?));
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let start = i;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '_' || chars[i] == '.') {
+                i += 1;
+            }
+            tokens.push(Token::Ident(chars[start..i].iter().collect()));
+        } else {
+            let two: String = chars[i..(i + 2).min(chars.len())].iter().collect();
+            let op = [">=", "<=", "==", "!=", "&&", "||"].into_iter().find(|op| *op == two);
+            if let Some(op) = op {
+                tokens.push(Token::Op(op));
+                i += 2;
+                continue;
+            }
+            tokens.push(match c {
+                '(' => Token::LParen,
+                ')' => Token::RParen,
+                ',' => Token::Comma,
+                '+' => Token::Op("+"),
+                '-' => Token::Op("-"),
+                '*' => Token::Op("*"),
+                '/' => Token::Op("/"),
+                '>' => Token::Op(">"),
+                '<' => Token::Op("<"),
+                '!' => Token::Op("!"),
+                _ => return Err(format!("unexpected character '{}' in '{}'", c, input)),
+            });
+            i += 1;
+        }
+    }
+
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn binary_level(&mut self, ops: &[(&str, BinaryOp)], next: fn(&mut Self) -> Result<Expr, String>) -> Result<Expr, String> {
+        let mut lhs = next(self)?;
+        while let Some(op) = self.peek_op().and_then(|op| ops.iter().find(|(s, _)| *s == op)).map(|(_, op)| *op) {
+            self.pos += 1;
+            let rhs = next(self)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[("||", BinaryOp::Or)], Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[("&&", BinaryOp::And)], Self::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[
+            (">", BinaryOp::Gt), (">=", BinaryOp::Ge), ("<", BinaryOp::Lt),
+            ("<=", BinaryOp::Le), ("==", BinaryOp::Eq), ("!=", BinaryOp::Ne),
+        ], Self::parse_additive)
+    }
+
+    fn parse_additive(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[("+", BinaryOp::Add), ("-", BinaryOp::Sub)], Self::parse_multiplicative)
+    }
+
+    fn parse_multiplicative(&mut self) -> Result<Expr, String> {
+        self.binary_level(&[("*", BinaryOp::Mul), ("/", BinaryOp::Div)], Self::parse_unary)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.peek_op() {
+            Some("-") => {
+                self.pos += 1;
+                Ok(Expr::Neg(Box::new(self.parse_unary()?)))
+            }
+            Some("!") => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            _ => self.parse_primary(),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        let token = self.tokens.get(self.pos).cloned().ok_or("unexpected end of expression")?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::LParen => {
+                let expr = self.parse_or()?;
+                self.expect(Token::RParen)?;
+                Ok(expr)
+            }
+            Token::Ident(name) if self.tokens.get(self.pos) == Some(&Token::LParen) => {
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.tokens.get(self.pos) != Some(&Token::RParen) {
+                    loop {
+                        args.push(self.parse_or()?);
+                        if self.tokens.get(self.pos) != Some(&Token::Comma) {
+                            break;
+                        }
+                        self.pos += 1;
+                    }
+                }
+                self.expect(Token::RParen)?;
+                Self::check_call(&name, &args)?;
+                Ok(Expr::Call(name, args))
+            }
+            Token::Ident(name) => Ok(Expr::Var(name)),
+            other => Err(format!("unexpected {:?}", other)),
+        }
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), String> {
+        if self.tokens.get(self.pos) == Some(&token) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected {:?}", token))
+        }
+    }
+
+    fn check_call(name: &str, args: &[Expr]) -> Result<(), String> {
+        let valid = match name {
+            "has" => args.len() == 1 && matches!(args[0], Expr::Var(_)),
+            "default" => args.len() == 2,
+            "max" | "min" => !args.is_empty(),
+            _ => return Err(format!("unknown function '{}', expected one of {:?}", name, FUNCTIONS)),
+        };
+        if valid { Ok(()) } else { Err(format!("wrong arguments for {}()", name)) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(input: &str) -> Option<f64> {
+        let vars = |name: &str| match name {
+            "unique.IOTime" => Some(900.0),
+            "common.ScanTime" => Some(1000.0),
+            _ => None,
+        };
+        Expr::parse(input).unwrap().eval(&vars)
+    }
+
+    #[test]
+    fn test_expression_eval() {
+        assert_eq!(eval("unique.IOTime / max(common.ScanTime, 1)"), Some(0.9));
+        assert_eq!(eval("1 + 2 * 3 - -1"), Some(8.0));
+        assert_eq!(eval("common.ScanTime >= 1_000 && !has(unique.ShortKeyFilterRows)"), Some(1.0));
+        assert_eq!(eval("default(unique.ShortKeyFilterRows, 0) > 0"), Some(0.0));
+        assert_eq!(eval("unique.Missing * 2"), None);
+        assert_eq!(eval("1 / (common.ScanTime - 1000)"), None);
+    }
+
+    #[test]
+    fn test_expression_errors() {
+        assert!(Expr::parse("1 +").is_err());
+        assert!(Expr::parse("avg(1, 2)").is_err());
+        assert!(Expr::parse("has(1)").is_err());
+        assert!(Expr::parse("(1 + 2").is_err());
+    }
+}
@@ -0,0 +1,368 @@
+//! Declarative hotspot rules.
+//!
+//! The built-in rules ship as `rules/default_rules.toml` and are compiled into the binary.
+//! A team-specific rule file can override them at startup (`--rules <file>` or the
+//! `PROFILE_ANALYZER_RULES` environment variable): rules with the same `id` replace the
+//! built-in ones, ids listed in `disabled = [...]` are switched off, new ids are added, and
+//! `[thresholds]` entries override the built-in values key by key. The `[score]` section
+//! configures the performance score formula (see [`score`]).
+
+pub mod expression;
+pub mod score;
+
+pub use expression::Expr;
+pub use score::{ScoreConfig, ScoreFile};
+
+use crate::i18n::Localized;
+use crate::models::{Confidence, HotSeverity, IssueCode, MetricUnit};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+use thiserror::Error;
+
+pub const DEFAULT_RULES: &str = include_str!("../../rules/default_rules.toml");
+
+/// Environment variable naming a rule override file, read on first use.
+pub const RULES_ENV_VAR: &str = "PROFILE_ANALYZER_RULES";
+
+#[derive(Debug, Error)]
+pub enum RuleError {
+    #[error("Failed to read rule file {path}: {source}")]
+    Io { path: String, source: std::io::Error },
+
+    #[error("Invalid rule file: {0}")]
+    Toml(#[from] toml::de::Error),
+
+    #[error("Invalid rule '{rule}': {message}")]
+    InvalidRule { rule: String, message: String },
+}
+
+/// What a rule is evaluated against, and which variables its expressions can use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleScope {
+    /// Once per query: `query.*`.
+    Query,
+    /// Each execution tree node: `node.*`, `scan.*` and `query.*`.
+    Node,
+    /// Each pipeline operator: `common.<Counter>`, `unique.<Counter>` and `query.*`.
+    Operator,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Fires when the metric exceeds a level's threshold.
+    #[default]
+    Above,
+    Below,
+}
+
+/// How `{value}` and `{var|format}` placeholders are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValueFormat {
+    #[default]
+    Number,
+    Integer,
+    Percent,
+    /// Milliseconds rendered as seconds.
+    Seconds,
+    Bytes,
+}
+
+impl ValueFormat {
+    pub fn unit(self) -> MetricUnit {
+        match self {
+            ValueFormat::Number | ValueFormat::Integer => MetricUnit::Count,
+            ValueFormat::Percent => MetricUnit::Ratio,
+            ValueFormat::Seconds => MetricUnit::Milliseconds,
+            ValueFormat::Bytes => MetricUnit::Bytes,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleLevel {
+    pub severity: HotSeverity,
+    pub threshold: f64,
+    pub message: Localized<String>,
+    #[serde(default)]
+    pub suggestions: Localized<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuleDefinition {
+    pub id: String,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub scope: RuleScope,
+    /// Operator names the rule applies to (`HASH_JOIN*` matches by prefix); empty means all.
+    #[serde(default)]
+    pub operators: Vec<String>,
+    #[serde(default)]
+    pub exclude_operators: Vec<String>,
+    pub issue_type: IssueCode,
+    /// Defaults to `High`: a rule compares a measured value against a threshold.
+    #[serde(default = "default_confidence")]
+    pub confidence: Confidence,
+    pub metric: String,
+    /// Extra precondition; the rule is skipped unless it evaluates to non-zero.
+    #[serde(default)]
+    pub when: Option<String>,
+    #[serde(default)]
+    pub direction: Direction,
+    #[serde(default)]
+    pub format: ValueFormat,
+    /// Hotspot path for query-scope rules; node and operator rules use their own location.
+    #[serde(default)]
+    pub node_path: Option<String>,
+    pub levels: Vec<RuleLevel>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+fn default_confidence() -> Confidence {
+    Confidence::High
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RuleFile {
+    #[serde(default)]
+    pub thresholds: HashMap<String, f64>,
+    #[serde(default)]
+    pub rules: Vec<RuleDefinition>,
+    /// Ids of rules to switch off without restating them.
+    #[serde(default)]
+    pub disabled: Vec<String>,
+    #[serde(default)]
+    pub score: ScoreFile,
+}
+
+impl RuleFile {
+    pub fn parse(text: &str) -> Result<RuleFile, RuleError> {
+        Ok(toml::from_str(text)?)
+    }
+
+    pub fn from_path(path: &Path) -> Result<RuleFile, RuleError> {
+        let text = std::fs::read_to_string(path).map_err(|source| RuleError::Io {
+            path: path.display().to_string(),
+            source,
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Overlays `overrides`: rules replace same-id rules, `disabled` ids are switched off,
+    /// thresholds replace same-name values, score settings merge as described on [`ScoreFile`].
+    pub fn merge(mut self, overrides: RuleFile) -> RuleFile {
+        self.thresholds.extend(overrides.thresholds);
+        self.score = self.score.merge(overrides.score);
+        for rule in overrides.rules {
+            match self.rules.iter_mut().find(|r| r.id == rule.id) {
+                Some(existing) => *existing = rule,
+                None => self.rules.push(rule),
+            }
+        }
+        for rule in self.rules.iter_mut().filter(|r| overrides.disabled.contains(&r.id)) {
+            rule.enabled = false;
+        }
+        self.disabled.extend(overrides.disabled);
+        self
+    }
+}
+
+/// A rule with its expressions parsed and its levels ordered strictest first.
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    pub definition: RuleDefinition,
+    pub metric: Expr,
+    pub when: Option<Expr>,
+}
+
+impl CompiledRule {
+    pub fn applies_to(&self, operator_name: &str) -> bool {
+        let matches = |pattern: &String| match pattern.strip_suffix('*') {
+            Some(prefix) => operator_name.starts_with(prefix),
+            None => operator_name == pattern,
+        };
+        (self.definition.operators.is_empty() || self.definition.operators.iter().any(matches))
+            && !self.definition.exclude_operators.iter().any(matches)
+    }
+
+    /// The strictest level the value reaches.
+    pub fn level_for(&self, value: f64) -> Option<&RuleLevel> {
+        self.definition.levels.iter().find(|level| match self.definition.direction {
+            Direction::Above => value > level.threshold,
+            Direction::Below => value < level.threshold,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    pub thresholds: HashMap<String, f64>,
+    pub rules: Vec<CompiledRule>,
+    pub score: ScoreConfig,
+}
+
+impl RuleSet {
+    pub fn compile(file: RuleFile) -> Result<RuleSet, RuleError> {
+        let score = ScoreConfig::compile(file.score)?;
+        let mut rules = Vec::new();
+        for mut definition in file.rules.into_iter().filter(|r| r.enabled) {
+            let invalid = |message: String| RuleError::InvalidRule { rule: definition.id.clone(), message };
+            if definition.levels.is_empty() {
+                return Err(invalid("at least one level is required".to_string()));
+            }
+            if definition.levels.iter().any(|l| !l.message.is_valid() || !l.suggestions.is_valid()) {
+                return Err(invalid("localized texts need at least one language".to_string()));
+            }
+            let metric = Expr::parse(&definition.metric).map_err(invalid)?;
+            let when = definition.when.as_deref().map(Expr::parse).transpose().map_err(invalid)?;
+
+            match definition.direction {
+                Direction::Above => definition.levels.sort_by(|a, b| b.threshold.total_cmp(&a.threshold)),
+                Direction::Below => definition.levels.sort_by(|a, b| a.threshold.total_cmp(&b.threshold)),
+            }
+            rules.push(CompiledRule { definition, metric, when });
+        }
+
+        Ok(RuleSet { thresholds: file.thresholds, rules, score })
+    }
+
+    pub fn builtin() -> RuleSet {
+        Self::compile(RuleFile::parse(DEFAULT_RULES).expect("built-in rule file is valid"))
+            .expect("built-in rules compile")
+    }
+
+    /// Built-in rules overlaid with the rule file at `path`.
+    pub fn with_overrides(path: &Path) -> Result<RuleSet, RuleError> {
+        let defaults = RuleFile::parse(DEFAULT_RULES)?;
+        Self::compile(defaults.merge(RuleFile::from_path(path)?))
+    }
+
+    pub fn threshold(&self, name: &str) -> Option<f64> {
+        self.thresholds.get(name).copied()
+    }
+
+    pub fn rules_for(&self, scope: RuleScope) -> impl Iterator<Item = &CompiledRule> {
+        self.rules.iter().filter(move |r| r.definition.scope == scope)
+    }
+}
+
+static ACTIVE_RULES: Lazy<RwLock<Arc<RuleSet>>> = Lazy::new(|| {
+    let rules = match std::env::var(RULES_ENV_VAR) {
+        Ok(path) => RuleSet::with_overrides(Path::new(&path)).unwrap_or_else(|e| {
+            tracing::warn!("Ignoring rule overrides from {}: {}", path, e);
+            RuleSet::builtin()
+        }),
+        Err(_) => RuleSet::builtin(),
+    };
+    RwLock::new(Arc::new(rules))
+});
+
+/// The rule set analyses currently run with.
+pub fn active() -> Arc<RuleSet> {
+    ACTIVE_RULES.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn install(rules: RuleSet) {
+    *ACTIVE_RULES.write().unwrap_or_else(|e| e.into_inner()) = Arc::new(rules);
+}
+
+/// Loads `path` over the built-in rules and makes the result active.
+pub fn load_overrides(path: &Path) -> Result<(), RuleError> {
+    install(RuleSet::with_overrides(path)?);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ScoreDimension;
+
+    #[test]
+    fn test_builtin_rules_compile() {
+        let rules = RuleSet::builtin();
+        assert!(rules.rules_for(RuleScope::Operator).count() > 5);
+        assert_eq!(rules.threshold("most_consuming_percentage"), Some(30.0));
+        assert_eq!(rules.score.severity_penalty(&HotSeverity::Critical), 25.0);
+        assert_eq!(rules.score.confidence_weight(Confidence::Medium), 0.75);
+        assert_eq!(rules.score.adjustments[0].level_for(2_000_000.0).unwrap().points, -10.0);
+    }
+
+    #[test]
+    fn test_merge_overrides() {
+        let overrides = RuleFile::parse(r#"
+[thresholds]
+most_consuming_percentage = 40.0
+
+[score]
+repeat_decay = 1.0
+
+[score.severity_penalty]
+Mild = 5.0
+
+[score.dimensions]
+LowSelectivity = "io"
+
+[[rules]]
+id = "segments_read_count"
+scope = "operator"
+operators = ["CONNECTOR_SCAN"]
+issue_type = "fragmented_rowsets"
+metric = "unique.SegmentsReadCount"
+format = "integer"
+levels = [{ severity = "Severe", threshold = 5000, message = "读取 {value} 个Segment" }]
+
+[[rules]]
+id = "operator_high_memory"
+enabled = false
+scope = "operator"
+issue_type = "HighMemoryUsage"
+metric = "common.MemoryUsage"
+levels = [{ severity = "Moderate", threshold = 1, message = "" }]
+"#).unwrap();
+        let rules = RuleSet::compile(RuleFile::parse(DEFAULT_RULES).unwrap().merge(overrides)).unwrap();
+
+        assert_eq!(rules.threshold("most_consuming_percentage"), Some(40.0));
+        assert_eq!(rules.score.repeat_decay, 1.0);
+        assert_eq!(rules.score.severity_penalty(&HotSeverity::Mild), 5.0);
+        assert_eq!(rules.score.severity_penalty(&HotSeverity::Severe), 15.0);
+        assert_eq!(rules.score.dimension(&IssueCode::Custom("LowSelectivity".to_string())), ScoreDimension::Io);
+        assert!(rules.rules.iter().all(|r| r.definition.id != "operator_high_memory"));
+        let segments = rules.rules.iter().find(|r| r.definition.id == "segments_read_count").unwrap();
+        assert_eq!(segments.definition.levels.len(), 1);
+        assert_eq!(segments.level_for(6000.0).unwrap().severity, HotSeverity::Severe);
+        assert!(segments.level_for(4000.0).is_none());
+    }
+
+    #[test]
+    fn test_disable_rules_by_id() {
+        let overrides = RuleFile::parse(r#"
+disabled = ["operator_high_memory", "segments_read_count"]
+"#).unwrap();
+        let rules = RuleSet::compile(RuleFile::parse(DEFAULT_RULES).unwrap().merge(overrides)).unwrap();
+
+        assert!(rules.rules.iter().all(|r| r.definition.id != "operator_high_memory"));
+        assert!(rules.rules.iter().all(|r| r.definition.id != "segments_read_count"));
+        assert_eq!(rules.rules.len(), RuleSet::builtin().rules.len() - 2);
+    }
+
+    #[test]
+    fn test_invalid_rule() {
+        let file = RuleFile::parse(r#"
+[[rules]]
+id = "broken"
+scope = "query"
+issue_type = "Broken"
+metric = "query.total_time_ms >"
+levels = [{ severity = "Mild", threshold = 0, message = "" }]
+"#).unwrap();
+        assert!(matches!(RuleSet::compile(file), Err(RuleError::InvalidRule { .. })));
+    }
+}
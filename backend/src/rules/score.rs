@@ -0,0 +1,142 @@
+//! The performance score formula, configured by the `[score]` section of the rule file.
+//!
+//! Every hotspot costs `severity_penalty[severity] × confidence_weight[confidence] × repeat_decay^n`
+//! points, where `n` counts the hotspots with the same issue code ranked before it, so one issue
+//! repeated on many operators does not sink the score on its own. `[[score.adjustments]]` add
+//! penalties or bonuses from query metrics. The score is `base` plus all items, clamped to 0..=100.
+
+use super::{Direction, Expr, RuleError};
+use crate::i18n::Localized;
+use crate::models::{Confidence, HotSeverity, IssueCode, ScoreDimension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// The `[score]` section as written. Override files only list what they change: scalars and map
+/// keys replace the built-in values one by one, adjustments replace same-id adjustments.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScoreFile {
+    #[serde(default)]
+    pub base: Option<f64>,
+    #[serde(default)]
+    pub repeat_decay: Option<f64>,
+    #[serde(default)]
+    pub severity_penalty: HashMap<HotSeverity, f64>,
+    #[serde(default)]
+    pub confidence_weight: HashMap<Confidence, f64>,
+    /// Issue code to dimension, overriding `IssueCode::dimension`.
+    #[serde(default)]
+    pub dimensions: HashMap<IssueCode, ScoreDimension>,
+    #[serde(default)]
+    pub adjustments: Vec<ScoreAdjustment>,
+}
+
+impl ScoreFile {
+    pub fn merge(mut self, overrides: ScoreFile) -> ScoreFile {
+        self.base = overrides.base.or(self.base);
+        self.repeat_decay = overrides.repeat_decay.or(self.repeat_decay);
+        self.severity_penalty.extend(overrides.severity_penalty);
+        self.confidence_weight.extend(overrides.confidence_weight);
+        self.dimensions.extend(overrides.dimensions);
+        for adjustment in overrides.adjustments {
+            match self.adjustments.iter_mut().find(|a| a.id == adjustment.id) {
+                Some(existing) => *existing = adjustment,
+                None => self.adjustments.push(adjustment),
+            }
+        }
+        self
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdjustmentLevel {
+    pub threshold: f64,
+    /// Negative for a penalty, positive for a bonus.
+    pub points: f64,
+    pub label: Localized<String>,
+}
+
+/// Score points derived from a query-scope metric (`query.*` variables).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScoreAdjustment {
+    pub id: String,
+    #[serde(default = "super::default_enabled")]
+    pub enabled: bool,
+    pub metric: String,
+    #[serde(default)]
+    pub direction: Direction,
+    /// Sub-score the points also count towards; none means the overall score only.
+    #[serde(default)]
+    pub dimension: Option<ScoreDimension>,
+    pub levels: Vec<AdjustmentLevel>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CompiledAdjustment {
+    pub definition: ScoreAdjustment,
+    pub metric: Expr,
+}
+
+impl CompiledAdjustment {
+    /// The strictest level the value reaches.
+    pub fn level_for(&self, value: f64) -> Option<&AdjustmentLevel> {
+        self.definition.levels.iter().find(|level| match self.definition.direction {
+            Direction::Above => value > level.threshold,
+            Direction::Below => value < level.threshold,
+        })
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoreConfig {
+    pub base: f64,
+    pub repeat_decay: f64,
+    pub severity_penalty: HashMap<HotSeverity, f64>,
+    pub confidence_weight: HashMap<Confidence, f64>,
+    pub dimensions: HashMap<IssueCode, ScoreDimension>,
+    pub adjustments: Vec<CompiledAdjustment>,
+}
+
+impl ScoreConfig {
+    pub fn compile(file: ScoreFile) -> Result<ScoreConfig, RuleError> {
+        let mut adjustments = Vec::new();
+        for mut definition in file.adjustments.into_iter().filter(|a| a.enabled) {
+            let invalid = |message: String| RuleError::InvalidRule { rule: definition.id.clone(), message };
+            if definition.levels.is_empty() {
+                return Err(invalid("at least one level is required".to_string()));
+            }
+            if definition.levels.iter().any(|l| !l.label.is_valid()) {
+                return Err(invalid("localized texts need at least one language".to_string()));
+            }
+            let metric = Expr::parse(&definition.metric).map_err(invalid)?;
+
+            match definition.direction {
+                Direction::Above => definition.levels.sort_by(|a, b| b.threshold.total_cmp(&a.threshold)),
+                Direction::Below => definition.levels.sort_by(|a, b| a.threshold.total_cmp(&b.threshold)),
+            }
+            adjustments.push(CompiledAdjustment { definition, metric });
+        }
+
+        Ok(ScoreConfig {
+            base: file.base.unwrap_or(100.0),
+            repeat_decay: file.repeat_decay.unwrap_or(1.0),
+            severity_penalty: file.severity_penalty,
+            confidence_weight: file.confidence_weight,
+            dimensions: file.dimensions,
+            adjustments,
+        })
+    }
+
+    /// Severities without an entry cost nothing.
+    pub fn severity_penalty(&self, severity: &HotSeverity) -> f64 {
+        self.severity_penalty.get(severity).copied().unwrap_or(0.0)
+    }
+
+    /// Confidence levels without an entry count in full.
+    pub fn confidence_weight(&self, confidence: Confidence) -> f64 {
+        self.confidence_weight.get(&confidence).copied().unwrap_or(1.0)
+    }
+
+    pub fn dimension(&self, issue_type: &IssueCode) -> ScoreDimension {
+        self.dimensions.get(issue_type).copied().unwrap_or_else(|| issue_type.dimension())
+    }
+}
@@ -0,0 +1,103 @@
+//! Builders shared by the analyzer unit tests.
+//!
+//! Hand-built profiles start empty and are filled in by the caller, so each test only spells
+//! out the counters its analyzer reads. `load` parses one of the sample profiles instead.
+
+use crate::models::*;
+use crate::parser::ProfileComposer;
+use std::collections::HashMap;
+
+pub fn metrics(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+    pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+}
+
+pub fn operator(name: &str, plan_node_id: i32, common: &[(&str, &str)], unique: &[(&str, &str)]) -> Operator {
+    Operator {
+        name: name.to_string(),
+        plan_node_id: Some(plan_node_id.to_string()),
+        operator_id: None,
+        common_metrics: metrics(common),
+        unique_metrics: metrics(unique),
+        children: Vec::new(),
+    }
+}
+
+pub fn pipeline(id: &str, pipeline_metrics: &[(&str, &str)], operators: Vec<Operator>) -> Pipeline {
+    Pipeline { id: id.to_string(), metrics: metrics(pipeline_metrics), operators }
+}
+
+/// A fragment whose pipelines are numbered in order, for tests that don't care about pipeline metrics.
+pub fn fragment(id: &str, pipelines: Vec<Vec<Operator>>) -> Fragment {
+    with_pipelines(id, pipelines.into_iter().enumerate()
+        .map(|(idx, operators)| pipeline(&idx.to_string(), &[], operators))
+        .collect())
+}
+
+pub fn with_pipelines(id: &str, pipelines: Vec<Pipeline>) -> Fragment {
+    Fragment {
+        id: id.to_string(),
+        backend_addresses: Vec::new(),
+        instance_ids: Vec::new(),
+        metrics: HashMap::new(),
+        pipelines,
+    }
+}
+
+pub fn profile(fragments: Vec<Fragment>) -> Profile {
+    Profile {
+        summary: ProfileSummary::default(),
+        planner: PlannerInfo { details: HashMap::new(), timers: Vec::new() },
+        execution: ExecutionInfo { topology: String::new(), metrics: HashMap::new() },
+        fragments,
+        execution_tree: None,
+    }
+}
+
+pub fn tree_node(plan_node_id: i32, name: &str, children: &[i32]) -> ExecutionTreeNode {
+    ExecutionTreeNode {
+        id: format!("node_{}", plan_node_id),
+        operator_name: name.to_string(),
+        node_type: if name.ends_with("_SCAN") { NodeType::OlapScan } else { NodeType::Unknown },
+        plan_node_id: Some(plan_node_id),
+        parent_plan_node_id: None,
+        metrics: OperatorMetrics::default(),
+        children: children.iter().map(|id| format!("node_{}", id)).collect(),
+        depth: 0,
+        is_hotspot: false,
+        hotspot_severity: HotSeverity::Normal,
+        fragment_id: None,
+        pipeline_id: None,
+        time_percentage: None,
+        is_most_consuming: false,
+        is_second_most_consuming: false,
+        is_on_critical_path: false,
+        has_chunk_accumulate: false,
+        chunk_fill: None,
+        unique_metrics: HashMap::new(),
+    }
+}
+
+/// An execution tree rooted at the first node.
+pub fn tree(nodes: Vec<ExecutionTreeNode>) -> ExecutionTree {
+    ExecutionTree { root: nodes[0].clone(), nodes }
+}
+
+pub fn hotspot(issue_type: IssueCode, severity: HotSeverity) -> HotSpot {
+    HotSpot {
+        node_path: "Query".to_string(),
+        node: NodeRef::default(),
+        severity,
+        issue_type,
+        confidence: Confidence::High,
+        description: String::new(),
+        suggestions: Vec::new(),
+        evidence: Vec::new(),
+    }
+}
+
+/// Parses `profiles/<name>` from the repository root.
+pub fn load(name: &str) -> Profile {
+    let text = std::fs::read_to_string(format!("../profiles/{}", name))
+        .unwrap_or_else(|e| panic!("failed to read {}: {}", name, e));
+    ProfileComposer::new().parse(&text).unwrap_or_else(|e| panic!("failed to parse {}: {:?}", name, e))
+}